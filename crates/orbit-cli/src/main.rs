@@ -1,16 +1,55 @@
-use std::{fs, path::PathBuf};
+use std::{
+    fs,
+    io::IsTerminal,
+    path::{Path, PathBuf},
+    process::ExitCode,
+    sync::Arc,
+};
 
-use anyhow::{Context, Result, bail};
-use clap::{Parser, Subcommand};
-use orbit_core::{self, OrbitValue};
+use anyhow::{Result, bail};
+use clap::{Parser, Subcommand, ValueEnum};
+use orbit_core::{
+    CoreError, Diagnostic, Engine, EngineError, ErrorCode, OrbitValue, Renderer, SourceMap, Warning,
+};
+use orbit_fmt::FormatError;
 
 #[derive(Parser)]
 #[command(name = "orbit", version, about = "Orbit configuration language CLI")]
 struct Cli {
+    /// When to color diagnostics
+    #[arg(long, global = true, value_enum, default_value_t = ColorChoice::Auto)]
+    color: ColorChoice,
+    /// Report warnings as errors and fail when there are any
+    #[arg(long, global = true)]
+    deny_warnings: bool,
     #[command(subcommand)]
     command: Commands,
 }
 
+#[derive(Clone, Copy, ValueEnum)]
+enum ColorChoice {
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    fn renderer(self) -> Renderer {
+        let color = match self {
+            ColorChoice::Auto => {
+                std::io::stderr().is_terminal() && std::env::var_os("NO_COLOR").is_none()
+            }
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+        };
+        if color {
+            Renderer::colored()
+        } else {
+            Renderer::plain()
+        }
+    }
+}
+
 #[derive(Subcommand)]
 enum Commands {
     /// Parse a file and validate syntax
@@ -23,6 +62,15 @@ enum Commands {
         /// Output as JSON instead of YAML
         #[arg(long)]
         json: bool,
+        /// Merge the named profile over the base document (repeatable, later wins)
+        #[arg(long = "profile", value_name = "NAME")]
+        profiles: Vec<String>,
+        /// Let keys brought in by a spread be overridden instead of erroring
+        #[arg(long)]
+        allow_spread_overrides: bool,
+        /// Print values marked @secret instead of redacting them
+        #[arg(long)]
+        reveal_secrets: bool,
     },
     /// Format a file using the canonical Orbit style
     Format {
@@ -31,58 +79,177 @@ enum Commands {
         #[arg(long)]
         write: bool,
     },
+    /// Print the long-form explanation of an error code such as E0301
+    Explain { code: String },
 }
 
-fn main() -> Result<()> {
+fn main() -> ExitCode {
     let cli = Cli::parse();
-    match cli.command {
-        Commands::Parse { input } => parse_file(&input),
-        Commands::Ast { input } => print_ast(&input),
-        Commands::Eval { input, json } => eval_file(&input, json),
-        Commands::Format { input, write } => format_file(&input, write),
+    let renderer = cli.color.renderer();
+    match run(cli.command, cli.deny_warnings, renderer) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            // Language errors get the full diagnostic with source lines;
+            // anything else (I/O, serialization) is a one-line message.
+            let core = err
+                .downcast_ref::<CoreError>()
+                .or_else(|| match err.downcast_ref::<EngineError>() {
+                    Some(EngineError::Core(err)) => Some(err),
+                    _ => None,
+                })
+                .or_else(|| match err.downcast_ref::<FormatError>() {
+                    Some(FormatError::Core(err)) => Some(err),
+                    _ => None,
+                });
+            match core {
+                Some(core) => eprint!("{}", renderer.render(&Diagnostic::from(core))),
+                None => eprintln!("error: {err:#}"),
+            }
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(command: Commands, deny_warnings: bool, renderer: Renderer) -> Result<()> {
+    let engine = Engine::new().with_deny_warnings(deny_warnings);
+    match command {
+        Commands::Parse { input } => parse_file(&engine, &input, renderer),
+        Commands::Ast { input } => print_ast(&engine, &input, renderer),
+        Commands::Eval {
+            input,
+            json,
+            profiles,
+            allow_spread_overrides,
+            reveal_secrets,
+        } => {
+            let engine = profiles
+                .into_iter()
+                .fold(engine, Engine::with_profile)
+                .with_spread_overrides(allow_spread_overrides);
+            eval_file(&engine, &input, json, reveal_secrets, renderer)
+        }
+        Commands::Format { input, write } => format_file(&engine, &input, write, renderer),
+        Commands::Explain { code } => explain(&code),
     }
 }
 
-fn parse_file(path: &PathBuf) -> Result<()> {
-    let source = read_file(path)?;
-    let report = orbit_core::parse_with_recovery(&source)?;
-    if report.errors.is_empty() {
+fn parse_file(engine: &Engine, path: &Path, renderer: Renderer) -> Result<()> {
+    let source_map = engine.load(path)?;
+    let report = engine.parse_with_recovery(source_map.source());
+    let mut errors = report.errors;
+    if engine.options().deny_warnings {
+        errors.extend(
+            report
+                .warnings
+                .into_iter()
+                .map(|warning| CoreError::from(warning.into_error())),
+        );
+        errors.sort_by_key(|error| error.span().start);
+    } else {
+        for warning in &report.warnings {
+            report_warning(renderer, warning, &source_map);
+        }
+    }
+    if errors.is_empty() {
         println!("{} parsed successfully", path.display());
         Ok(())
     } else {
-        for error in &report.errors {
-            eprintln!("{}", error);
+        let count = errors.len();
+        for error in errors {
+            let error = error.with_source(source_map.clone());
+            eprintln!("{}", renderer.render(&Diagnostic::from(&error)));
         }
         bail!(
             "{} parse error(s) emitted while processing {}",
-            report.errors.len(),
+            count,
             path.display()
         );
     }
 }
 
-fn print_ast(path: &PathBuf) -> Result<()> {
-    let source = read_file(path)?;
-    let ast = orbit_core::parse(&source)?;
+fn print_ast(engine: &Engine, path: &Path, renderer: Renderer) -> Result<()> {
+    let source_map = engine.load(path)?;
+    let (ast, warnings) = engine
+        .parse_with_warnings(source_map.source())
+        .map_err(|err| err.with_source(source_map.clone()))?;
+    for warning in &warnings {
+        report_warning(renderer, warning, &source_map);
+    }
     let json = serde_json::to_string_pretty(&ast)?;
     println!("{}", json);
     Ok(())
 }
 
-fn eval_file(path: &PathBuf, json: bool) -> Result<()> {
-    let source = read_file(path)?;
-    let value = orbit_core::evaluate(&source)?;
-    if json {
-        print_json(&value)?;
-    } else {
-        print_yaml(&value)?;
+// Every document is evaluated in full, so all of its errors are reported at
+// once, and the values that are fine are printed even when some are not.
+fn eval_file(
+    engine: &Engine,
+    path: &Path,
+    json: bool,
+    reveal_secrets: bool,
+    renderer: Renderer,
+) -> Result<()> {
+    let source_map = engine.load(path)?;
+    let evaluations = engine
+        .evaluate_documents_report(source_map.source())
+        .map_err(|err| err.with_source(source_map.clone()))?;
+
+    let mut count = 0;
+    let mut values = Vec::with_capacity(evaluations.len());
+    for evaluation in evaluations {
+        for warning in &evaluation.warnings {
+            report_warning(renderer, warning, &source_map);
+        }
+        count += evaluation.errors.len();
+        for error in evaluation.errors {
+            let error = error.with_source(source_map.clone());
+            eprintln!("{}", renderer.render(&Diagnostic::from(&error)));
+        }
+        values.push(if reveal_secrets {
+            evaluation.value.reveal_secrets()
+        } else {
+            evaluation.value
+        });
+    }
+
+    // Every error is reported, but stdout stays empty rather than holding a
+    // value with parts missing.
+    if count > 0 {
+        bail!(
+            "{} error(s) emitted while evaluating {}",
+            count,
+            path.display()
+        );
+    }
+
+    // A single document prints as before; several become a JSON array or a
+    // YAML stream.
+    match (values.len(), json) {
+        (1, true) => print_json(&values[0])?,
+        (1, false) => print_yaml(&values[0])?,
+        (_, true) => print_json(&OrbitValue::List(values))?,
+        (_, false) => {
+            for value in &values {
+                println!("---");
+                print_yaml(value)?;
+            }
+        }
     }
     Ok(())
 }
 
-fn format_file(path: &PathBuf, write_back: bool) -> Result<()> {
-    let source = read_file(path)?;
-    let formatted = orbit_fmt::format_source(&source)?;
+fn format_file(engine: &Engine, path: &Path, write_back: bool, renderer: Renderer) -> Result<()> {
+    let source_map = engine.load(path)?;
+    // Formatting never looks at warnings, so the source is only checked for
+    // them when they are denied.
+    if engine.options().deny_warnings {
+        let tree = engine.parse_cst(source_map.source());
+        check_warnings(engine, path, tree.warnings(), &source_map, renderer)?;
+    }
+    let formatted = orbit_fmt::format_source(source_map.source()).map_err(|err| match err {
+        FormatError::Core(err) => FormatError::Core(err.with_source(source_map.clone())),
+        other => other,
+    })?;
     if write_back {
         fs::write(path, formatted)?;
         println!("{} formatted", path.display());
@@ -92,8 +259,46 @@ fn format_file(path: &PathBuf, write_back: bool) -> Result<()> {
     Ok(())
 }
 
-fn read_file(path: &PathBuf) -> Result<String> {
-    fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))
+fn explain(code: &str) -> Result<()> {
+    let Ok(code) = code.parse::<ErrorCode>() else {
+        bail!("unknown error code '{code}'");
+    };
+    println!("{code}: {}\n\n{}", code.title(), code.explanation());
+    Ok(())
+}
+
+// Prints the lexer and parser warnings, or fails with them as errors when
+// they are denied.
+fn check_warnings(
+    engine: &Engine,
+    path: &Path,
+    warnings: &[Warning],
+    source_map: &Arc<SourceMap>,
+    renderer: Renderer,
+) -> Result<()> {
+    if !engine.options().deny_warnings {
+        for warning in warnings {
+            report_warning(renderer, warning, source_map);
+        }
+        return Ok(());
+    }
+    for warning in warnings {
+        let error = warning.clone().into_error().with_source(source_map.clone());
+        eprintln!("{}", renderer.render(&Diagnostic::from(&error)));
+    }
+    if !warnings.is_empty() {
+        bail!(
+            "{} denied warning(s) emitted while processing {}",
+            warnings.len(),
+            path.display()
+        );
+    }
+    Ok(())
+}
+
+fn report_warning(renderer: Renderer, warning: &Warning, source_map: &Arc<SourceMap>) {
+    let diagnostic = Diagnostic::from(warning).with_source(source_map.clone());
+    eprintln!("{}", renderer.render(&diagnostic));
 }
 
 fn print_json(value: &OrbitValue) -> Result<()> {
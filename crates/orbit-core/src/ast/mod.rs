@@ -1,5 +1,5 @@
 pub mod node;
 pub mod span;
 
-pub use self::node::{AstNode, ObjectEntry, ValueNode};
-pub use self::span::Span;
+pub use self::node::{Annotation, AstNode, DocComment, ObjectEntry, ValueNode};
+pub use self::span::{FileId, LineCol, LineIndex, SourceMap, Span};
@@ -14,11 +14,30 @@ pub enum AstNode {
         key: String,
         value: ValueNode,
         span: Span,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        docs: Vec<DocComment>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        annotations: Vec<Annotation>,
     },
     Block {
         name: String,
         body: Vec<AstNode>,
         span: Span,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        docs: Vec<DocComment>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        annotations: Vec<Annotation>,
+    },
+    Profile {
+        name: String,
+        body: Vec<AstNode>,
+        span: Span,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        docs: Vec<DocComment>,
+    },
+    // Stands in for tokens a recovering parse had to skip.
+    Error {
+        span: Span,
     },
 }
 
@@ -27,13 +46,33 @@ impl AstNode {
         match self {
             AstNode::Document { span, .. }
             | AstNode::Entry { span, .. }
-            | AstNode::Block { span, .. } => *span,
+            | AstNode::Block { span, .. }
+            | AstNode::Profile { span, .. }
+            | AstNode::Error { span } => *span,
+        }
+    }
+
+    pub fn docs(&self) -> &[DocComment] {
+        match self {
+            AstNode::Entry { docs, .. }
+            | AstNode::Block { docs, .. }
+            | AstNode::Profile { docs, .. } => docs,
+            _ => &[],
+        }
+    }
+
+    pub fn annotations(&self) -> &[Annotation] {
+        match self {
+            AstNode::Entry { annotations, .. } | AstNode::Block { annotations, .. } => annotations,
+            _ => &[],
         }
     }
 
     pub fn as_body(&self) -> Option<&[AstNode]> {
         match self {
-            AstNode::Document { body, .. } | AstNode::Block { body, .. } => Some(body),
+            AstNode::Document { body, .. }
+            | AstNode::Block { body, .. }
+            | AstNode::Profile { body, .. } => Some(body),
             _ => None,
         }
     }
@@ -46,6 +85,15 @@ pub enum ValueNode {
         value: String,
         span: Span,
     },
+    Bytes {
+        value: Vec<u8>,
+        span: Span,
+    },
+    Tagged {
+        tag: String,
+        value: String,
+        span: Span,
+    },
     Number {
         value: OrbitNumber,
         span: Span,
@@ -62,23 +110,80 @@ pub enum ValueNode {
         entries: Vec<ObjectEntry>,
         span: Span,
     },
+    Spread {
+        name: String,
+        span: Span,
+    },
+    // A value a recovering parse could not read; the span may be empty when
+    // the value was missing altogether.
+    Error {
+        span: Span,
+    },
 }
 
 impl ValueNode {
     pub fn span(&self) -> Span {
         match self {
             ValueNode::String { span, .. }
+            | ValueNode::Bytes { span, .. }
+            | ValueNode::Tagged { span, .. }
             | ValueNode::Number { span, .. }
             | ValueNode::Bool { span, .. }
             | ValueNode::List { span, .. }
-            | ValueNode::Object { span, .. } => *span,
+            | ValueNode::Object { span, .. }
+            | ValueNode::Spread { span, .. }
+            | ValueNode::Error { span } => *span,
         }
     }
 }
 
+// A spread inside an object literal is stored as an entry whose value is
+// `ValueNode::Spread`; its key is left empty and carries no meaning.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ObjectEntry {
     pub key: String,
     pub value: ValueNode,
     pub span: Span,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub docs: Vec<DocComment>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub annotations: Vec<Annotation>,
+}
+
+impl ObjectEntry {
+    pub fn is_spread(&self) -> bool {
+        matches!(self.value, ValueNode::Spread { .. })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DocComment {
+    pub text: String,
+    pub span: Span,
+}
+
+impl DocComment {
+    pub fn new(text: impl Into<String>, span: Span) -> Self {
+        Self {
+            text: text.into(),
+            span,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Annotation {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<ValueNode>,
+    pub span: Span,
+}
+
+impl Annotation {
+    pub fn string_arg(&self, index: usize) -> Option<&str> {
+        match self.args.get(index) {
+            Some(ValueNode::String { value, .. }) => Some(value),
+            _ => None,
+        }
+    }
 }
@@ -1,20 +1,62 @@
+use std::fmt;
+
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+// Identifies a source registered in a `FileDatabase`. The default id means
+// "no particular file", which is what spans from the plain `parse` entry
+// points carry.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize,
+)]
+#[serde(transparent)]
+pub struct FileId(u32);
+
+impl FileId {
+    pub const fn new(raw: u32) -> Self {
+        Self(raw)
+    }
+
+    pub const fn raw(self) -> u32 {
+        self.0
+    }
+
+    pub fn is_anonymous(&self) -> bool {
+        self.0 == 0
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
 pub struct Span {
     pub start: usize,
     pub end: usize,
+    #[serde(default, skip_serializing_if = "FileId::is_anonymous")]
+    pub(crate) file: FileId,
 }
 
 impl Span {
     pub const fn new(start: usize, end: usize) -> Self {
-        Self { start, end }
+        Self {
+            start,
+            end,
+            file: FileId::new(0),
+        }
+    }
+
+    pub const fn in_file(self, file: FileId) -> Self {
+        Self { file, ..self }
+    }
+
+    pub const fn file(self) -> FileId {
+        self.file
     }
 
+    // Keeps the file of `self`; both spans are expected to come from the
+    // same source.
     pub fn union(self, other: Span) -> Self {
         Self {
             start: self.start.min(other.start),
             end: self.end.max(other.end),
+            file: self.file,
         }
     }
 
@@ -26,3 +68,147 @@ impl Span {
         self.start == self.end
     }
 }
+
+// Anonymous spans print as they did before files existed, which keeps error
+// messages for single-source callers unchanged.
+impl fmt::Debug for Span {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug = f.debug_struct("Span");
+        debug.field("start", &self.start).field("end", &self.end);
+        if !self.file.is_anonymous() {
+            debug.field("file", &self.file);
+        }
+        debug.finish()
+    }
+}
+
+// 1-based position of a byte offset. `column` counts Unicode scalar values
+// (or UTF-16 code units, see `LineIndex::line_col_utf16`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LineCol {
+    pub line: usize,
+    pub column: usize,
+}
+
+// Byte offsets of line starts. A line ends after `\n`, `\r\n`, or a lone
+// `\r`, matching the lexer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineIndex {
+    line_starts: Vec<usize>,
+}
+
+impl LineIndex {
+    pub fn new(source: &str) -> Self {
+        let bytes = source.as_bytes();
+        let mut line_starts = vec![0];
+        for (at, byte) in bytes.iter().enumerate() {
+            match byte {
+                b'\n' => line_starts.push(at + 1),
+                b'\r' if bytes.get(at + 1) != Some(&b'\n') => line_starts.push(at + 1),
+                _ => {}
+            }
+        }
+        Self { line_starts }
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+
+    // Offsets past the end or inside a multi-byte character are clamped to
+    // the nearest preceding character boundary of `source`.
+    pub fn line_col(&self, source: &str, offset: usize) -> LineCol {
+        let (line, prefix) = self.line_prefix(source, offset);
+        LineCol {
+            line,
+            column: prefix.chars().count() + 1,
+        }
+    }
+
+    pub fn line_col_utf16(&self, source: &str, offset: usize) -> LineCol {
+        let (line, prefix) = self.line_prefix(source, offset);
+        LineCol {
+            line,
+            column: prefix.encode_utf16().count() + 1,
+        }
+    }
+
+    // Byte offset of a 1-based line, or `None` past the last line.
+    pub fn line_start(&self, line: usize) -> Option<usize> {
+        line.checked_sub(1)
+            .and_then(|index| self.line_starts.get(index))
+            .copied()
+    }
+
+    fn line_prefix<'s>(&self, source: &'s str, offset: usize) -> (usize, &'s str) {
+        let mut offset = offset.min(source.len());
+        while !source.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        let line = self.line_starts.partition_point(|&start| start <= offset);
+        let start = self.line_starts[line - 1];
+        (line, &source[start..offset])
+    }
+}
+
+// A named source text with its line index, attached to errors so they can
+// print `name:line:column`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceMap {
+    name: String,
+    source: String,
+    index: LineIndex,
+}
+
+impl SourceMap {
+    pub fn new(name: impl Into<String>, source: impl Into<String>) -> Self {
+        let source = source.into();
+        let index = LineIndex::new(&source);
+        Self {
+            name: name.into(),
+            source,
+            index,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn line_index(&self) -> &LineIndex {
+        &self.index
+    }
+
+    pub fn line_col(&self, offset: usize) -> LineCol {
+        self.index.line_col(&self.source, offset)
+    }
+
+    pub fn line_col_utf16(&self, offset: usize) -> LineCol {
+        self.index.line_col_utf16(&self.source, offset)
+    }
+
+    pub fn location(&self, span: Span) -> String {
+        let LineCol { line, column } = self.line_col(span.start);
+        format!("{}:{line}:{column}", self.name)
+    }
+
+    // The whole lines `span` touches, without the final line break.
+    pub fn snippet(&self, span: Span) -> &str {
+        let start = self
+            .index
+            .line_start(self.line_col(span.start).line)
+            .unwrap_or(0);
+        let mut end = span.end.clamp(start, self.source.len());
+        while !self.source.is_char_boundary(end) {
+            end -= 1;
+        }
+        let end = self.source[end..]
+            .find(['\n', '\r'])
+            .map_or(self.source.len(), |at| end + at);
+        &self.source[start..end]
+    }
+}
@@ -0,0 +1,126 @@
+use std::sync::Arc;
+
+use crate::{lexer::Token, parser::Event};
+
+use super::{
+    green::{GreenChild, GreenNode, GreenToken},
+    kind::SyntaxKind,
+};
+
+// Builds the tree for the events of one parse. The lexer skips spaces and
+// tabs, so the gaps between its tokens become whitespace tokens here. Trivia
+// in front of a node goes to its parent, which makes a node start at its
+// first token or leading doc comment.
+pub(crate) fn build(source: &str, tokens: &[Token<'_>], events: &[Event]) -> Arc<GreenNode> {
+    let mut builder = Builder {
+        source,
+        tokens,
+        next: 0,
+        offset: 0,
+        stack: vec![(SyntaxKind::Root, Vec::new())],
+    };
+    for (position, event) in events.iter().enumerate() {
+        match *event {
+            Event::Start { kind, first } => {
+                if let Some(first) = first.or_else(|| first_token(&events[position + 1..])) {
+                    builder.flush(first);
+                }
+                builder.stack.push((kind, Vec::new()));
+            }
+            Event::Token(index) => {
+                builder.flush(index);
+                builder.token(index);
+            }
+            Event::Finish => builder.finish(),
+        }
+    }
+    // The last token is always the end of input.
+    builder.flush(tokens.len() - 1);
+    // Text behind the token limit was never lexed.
+    if builder.offset < source.len() {
+        builder.stack.push((SyntaxKind::Error, Vec::new()));
+        builder.push(SyntaxKind::Error, source.len());
+        builder.finish();
+    }
+    while builder.stack.len() > 1 {
+        builder.finish();
+    }
+    let (kind, children) = builder.stack.remove(0);
+    Arc::new(GreenNode::new(kind, children))
+}
+
+// The first token of the node whose `Start` precedes `events`, if it has
+// any.
+fn first_token(events: &[Event]) -> Option<usize> {
+    let mut depth = 0usize;
+    for event in events {
+        match *event {
+            Event::Start {
+                first: Some(first), ..
+            } => return Some(first),
+            Event::Start { first: None, .. } => depth += 1,
+            Event::Token(index) => return Some(index),
+            Event::Finish if depth == 0 => return None,
+            Event::Finish => depth -= 1,
+        }
+    }
+    None
+}
+
+struct Builder<'s, 't> {
+    source: &'s str,
+    tokens: &'t [Token<'s>],
+    // The first lexer token not yet in the tree, and where it starts.
+    next: usize,
+    offset: usize,
+    stack: Vec<(SyntaxKind, Vec<GreenChild>)>,
+}
+
+impl Builder<'_, '_> {
+    // Adds the tokens before `index` and the whitespace in front of it to the
+    // innermost open node.
+    fn flush(&mut self, index: usize) {
+        while self.next < index {
+            self.token(self.next);
+        }
+        self.whitespace(self.tokens[index].span.start);
+    }
+
+    fn token(&mut self, index: usize) {
+        let token = &self.tokens[index];
+        let span = token.span;
+        self.whitespace(span.start);
+        if let Some(kind) = SyntaxKind::from_token(&token.kind) {
+            self.push(kind, span.end);
+        }
+        self.next = index + 1;
+    }
+
+    fn whitespace(&mut self, end: usize) {
+        if end > self.offset {
+            self.push(SyntaxKind::Whitespace, end);
+        }
+    }
+
+    fn push(&mut self, kind: SyntaxKind, end: usize) {
+        let token = GreenToken::new(kind, &self.source[self.offset..end]);
+        self.offset = end;
+        if let Some((_, children)) = self.stack.last_mut() {
+            children.push(GreenChild::Token(token));
+        }
+    }
+
+    // The root stays open until every event has been seen.
+    fn finish(&mut self) {
+        if self.stack.len() < 2 {
+            return;
+        }
+        let Some((kind, children)) = self.stack.pop() else {
+            return;
+        };
+        let node = GreenChild::Node(Arc::new(GreenNode::new(kind, children)));
+        if let Some((_, children)) = self.stack.last_mut() {
+            children.push(node);
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use std::sync::Arc;
+
+use super::kind::SyntaxKind;
+
+// The stored form of the tree. Nodes know their width but not their
+// position, so an unchanged subtree can be shared between the trees of two
+// versions of a source even when the text before it changed.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct GreenNode {
+    kind: SyntaxKind,
+    len: usize,
+    children: Vec<GreenChild>,
+}
+
+impl GreenNode {
+    pub(crate) fn new(kind: SyntaxKind, children: Vec<GreenChild>) -> Self {
+        let len = children.iter().map(GreenChild::len).sum();
+        Self {
+            kind,
+            len,
+            children,
+        }
+    }
+
+    pub(crate) fn kind(&self) -> SyntaxKind {
+        self.kind
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+
+    pub(crate) fn children(&self) -> &[GreenChild] {
+        &self.children
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) enum GreenChild {
+    Node(Arc<GreenNode>),
+    Token(GreenToken),
+}
+
+impl GreenChild {
+    pub(crate) fn len(&self) -> usize {
+        match self {
+            GreenChild::Node(node) => node.len(),
+            GreenChild::Token(token) => token.text.len(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct GreenToken {
+    kind: SyntaxKind,
+    text: Arc<str>,
+}
+
+impl GreenToken {
+    pub(crate) fn new(kind: SyntaxKind, text: &str) -> Self {
+        Self {
+            kind,
+            text: text.into(),
+        }
+    }
+
+    pub(crate) fn kind(&self) -> SyntaxKind {
+        self.kind
+    }
+
+    pub(crate) fn text(&self) -> &str {
+        &self.text
+    }
+}
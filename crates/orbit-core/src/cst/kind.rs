@@ -0,0 +1,121 @@
+use serde::Serialize;
+
+use crate::lexer::TokenKind;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub enum SyntaxKind {
+    // Nodes
+    Root,
+    Document,
+    Entry,
+    Block,
+    Profile,
+    Annotation,
+    List,
+    Object,
+    ObjectEntry,
+    Spread,
+    Literal,
+    Error,
+
+    // Tokens
+    Ident,
+    String,
+    Bytes,
+    Tagged,
+    Number,
+    Bool,
+    LBrace,
+    RBrace,
+    LBracket,
+    RBracket,
+    LParen,
+    RParen,
+    At,
+    Colon,
+    Comma,
+    Ellipsis,
+    DocumentSeparator,
+    Whitespace,
+    Newline,
+    Comment,
+    DocComment,
+}
+
+impl SyntaxKind {
+    pub fn is_trivia(self) -> bool {
+        matches!(
+            self,
+            SyntaxKind::Whitespace
+                | SyntaxKind::Newline
+                | SyntaxKind::Comment
+                | SyntaxKind::DocComment
+        )
+    }
+
+    // How the kind reads in messages such as "expected one of ':', '{'".
+    pub fn describe(self) -> &'static str {
+        match self {
+            SyntaxKind::Root => "root",
+            SyntaxKind::Document => "document",
+            SyntaxKind::Entry => "entry",
+            SyntaxKind::Block => "block",
+            SyntaxKind::Profile => "profile",
+            SyntaxKind::Annotation => "annotation",
+            SyntaxKind::List => "list",
+            SyntaxKind::Object => "object",
+            SyntaxKind::ObjectEntry => "object entry",
+            SyntaxKind::Spread => "spread",
+            SyntaxKind::Literal => "literal",
+            SyntaxKind::Error => "invalid token",
+            SyntaxKind::Ident => "identifier",
+            SyntaxKind::String => "string",
+            SyntaxKind::Bytes => "bytes",
+            SyntaxKind::Tagged => "tagged literal",
+            SyntaxKind::Number => "number",
+            SyntaxKind::Bool => "boolean",
+            SyntaxKind::LBrace => "'{'",
+            SyntaxKind::RBrace => "'}'",
+            SyntaxKind::LBracket => "'['",
+            SyntaxKind::RBracket => "']'",
+            SyntaxKind::LParen => "'('",
+            SyntaxKind::RParen => "')'",
+            SyntaxKind::At => "'@'",
+            SyntaxKind::Colon => "':'",
+            SyntaxKind::Comma => "','",
+            SyntaxKind::Ellipsis => "'...'",
+            SyntaxKind::DocumentSeparator => "'---'",
+            SyntaxKind::Whitespace => "whitespace",
+            SyntaxKind::Newline => "newline",
+            SyntaxKind::Comment => "comment",
+            SyntaxKind::DocComment => "doc comment",
+        }
+    }
+
+    pub(crate) fn from_token(kind: &TokenKind<'_>) -> Option<Self> {
+        Some(match kind {
+            TokenKind::Ident(_) => SyntaxKind::Ident,
+            TokenKind::String(_) => SyntaxKind::String,
+            TokenKind::Bytes(_) => SyntaxKind::Bytes,
+            TokenKind::Tagged(..) => SyntaxKind::Tagged,
+            TokenKind::Number(_) => SyntaxKind::Number,
+            TokenKind::Bool(_) => SyntaxKind::Bool,
+            TokenKind::LBrace => SyntaxKind::LBrace,
+            TokenKind::RBrace => SyntaxKind::RBrace,
+            TokenKind::LBracket => SyntaxKind::LBracket,
+            TokenKind::RBracket => SyntaxKind::RBracket,
+            TokenKind::LParen => SyntaxKind::LParen,
+            TokenKind::RParen => SyntaxKind::RParen,
+            TokenKind::At => SyntaxKind::At,
+            TokenKind::Colon => SyntaxKind::Colon,
+            TokenKind::Comma => SyntaxKind::Comma,
+            TokenKind::Ellipsis => SyntaxKind::Ellipsis,
+            TokenKind::DocumentSeparator => SyntaxKind::DocumentSeparator,
+            TokenKind::Newline => SyntaxKind::Newline,
+            TokenKind::Comment(_) => SyntaxKind::Comment,
+            TokenKind::DocComment(_) => SyntaxKind::DocComment,
+            TokenKind::Error(_) => SyntaxKind::Error,
+            TokenKind::Eof => return None,
+        })
+    }
+}
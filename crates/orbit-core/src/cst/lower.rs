@@ -0,0 +1,287 @@
+use crate::{
+    ast::{Annotation, AstNode, DocComment, FileId, ObjectEntry, Span, ValueNode},
+    lexer::{BASE64_TAG, DOC_COMMENT_PREFIX, string_value, unicode::normalize_identifier},
+    parser::{grammar, parse_number_literal},
+    value::bytes::{from_base64, from_hex},
+};
+
+use super::{
+    kind::SyntaxKind,
+    tree::{SyntaxElement, SyntaxNode, SyntaxToken},
+};
+
+// Derives the AST from the syntax tree. Keys, names, and literal values are
+// read back from the token text; anything the parser wrapped in an error node
+// becomes an error node of the AST.
+
+// A source parsed as a single document spans from its first token that is
+// not whitespace to the end of the input.
+pub(crate) fn document(root: SyntaxNode<'_>, file: FileId) -> AstNode {
+    let lower = Lower { file };
+    let end = root.span().end;
+    let start = root
+        .tokens()
+        .find(|token| token.kind() != SyntaxKind::Whitespace)
+        .map_or(end, |token| token.span().start);
+    let body = root
+        .child_nodes()
+        .find(|node| node.kind() == SyntaxKind::Document)
+        .map(|node| lower.body(node))
+        .unwrap_or_default();
+    grammar::document(body, lower.span(Span::new(start, end)))
+}
+
+// Each document of a multi-document source spans its own tokens; an empty
+// one sits where the next separator or the end of input is.
+pub(crate) fn documents(root: SyntaxNode<'_>, file: FileId) -> Vec<AstNode> {
+    let lower = Lower { file };
+    let children: Vec<_> = root.children().collect();
+    let mut documents = Vec::new();
+    for (position, child) in children.iter().enumerate() {
+        let SyntaxElement::Node(node) = child else {
+            continue;
+        };
+        if node.kind() != SyntaxKind::Document {
+            continue;
+        }
+        let mut tokens = node.tokens().filter(|token| !token.kind().is_trivia());
+        let span = match (tokens.next(), tokens.last()) {
+            (Some(first), last) => {
+                let end = last.unwrap_or(first).span().end;
+                Span::new(first.span().start, end)
+            }
+            (None, _) => {
+                let next = children[position + 1..]
+                    .iter()
+                    .find(|element| !element.kind().is_trivia())
+                    .map_or(root.span().end, |element| element.span().start);
+                Span::new(next, next)
+            }
+        };
+        documents.push(grammar::document(lower.body(*node), lower.span(span)));
+    }
+    documents
+}
+
+struct Lower {
+    file: FileId,
+}
+
+impl Lower {
+    fn span(&self, span: Span) -> Span {
+        span.in_file(self.file)
+    }
+
+    fn body(&self, node: SyntaxNode<'_>) -> Vec<AstNode> {
+        node.child_nodes()
+            .filter_map(|child| self.statement(child))
+            .collect()
+    }
+
+    fn statement(&self, node: SyntaxNode<'_>) -> Option<AstNode> {
+        let docs = self.docs(node);
+        let annotations = self.annotations(node);
+        let mut tokens = significant_tokens(node);
+        Some(match node.kind() {
+            SyntaxKind::Entry => {
+                let key = tokens.next()?;
+                let value = self.value_child(node, key.span().end);
+                let span = self.span(key.span().union(value.span()));
+                AstNode::Entry {
+                    key: normalize_identifier(key.text()),
+                    value,
+                    span,
+                    docs,
+                    annotations,
+                }
+            }
+            SyntaxKind::Block => {
+                let name = tokens.next()?;
+                AstNode::Block {
+                    name: normalize_identifier(name.text()),
+                    body: self.body(node),
+                    span: self.span(Span::new(name.span().start, node.span().end)),
+                    docs,
+                    annotations,
+                }
+            }
+            SyntaxKind::Profile => {
+                let keyword = tokens.next()?;
+                let name = tokens.next()?;
+                AstNode::Profile {
+                    name: normalize_identifier(name.text()),
+                    body: self.body(node),
+                    span: self.span(Span::new(keyword.span().start, node.span().end)),
+                    docs,
+                }
+            }
+            SyntaxKind::Error => {
+                let start = tokens
+                    .next()
+                    .map_or(node.span().start, |token| token.span().start);
+                AstNode::Error {
+                    span: self.span(Span::new(start, node.span().end)),
+                }
+            }
+            _ => return None,
+        })
+    }
+
+    // The value is the last node of an entry; annotations come before the key.
+    fn value_child(&self, node: SyntaxNode<'_>, fallback: usize) -> ValueNode {
+        node.child_nodes()
+            .last()
+            .filter(|child| child.kind() != SyntaxKind::Annotation)
+            .map_or_else(
+                || ValueNode::Error {
+                    span: self.span(Span::new(fallback, fallback)),
+                },
+                |child| self.value(child),
+            )
+    }
+
+    fn value(&self, node: SyntaxNode<'_>) -> ValueNode {
+        let span = self.span(node.span());
+        let error = ValueNode::Error { span };
+        match node.kind() {
+            SyntaxKind::Literal => significant_tokens(node)
+                .next()
+                .and_then(|token| self.literal(token))
+                .unwrap_or(error),
+            SyntaxKind::List => ValueNode::List {
+                items: node.child_nodes().map(|item| self.value(item)).collect(),
+                span,
+            },
+            SyntaxKind::Object => ValueNode::Object {
+                entries: node
+                    .child_nodes()
+                    .filter(|entry| entry.kind() == SyntaxKind::ObjectEntry)
+                    .map(|entry| self.object_entry(entry))
+                    .collect(),
+                span,
+            },
+            SyntaxKind::Spread => match significant_tokens(node).nth(1) {
+                Some(name) => ValueNode::Spread {
+                    name: normalize_identifier(name.text()),
+                    span,
+                },
+                None => error,
+            },
+            _ => error,
+        }
+    }
+
+    fn literal(&self, token: SyntaxToken<'_>) -> Option<ValueNode> {
+        let span = self.span(token.span());
+        let text = token.text();
+        Some(match token.kind() {
+            SyntaxKind::String => ValueNode::String {
+                value: string_value(text),
+                span,
+            },
+            SyntaxKind::Bytes => {
+                let (encoding, quoted) = text.split_at(text.find('"')?);
+                let contents = string_value(quoted);
+                let value = if encoding == BASE64_TAG {
+                    from_base64(&contents)
+                } else {
+                    from_hex(&contents)
+                };
+                ValueNode::Bytes {
+                    value: value?,
+                    span,
+                }
+            }
+            SyntaxKind::Tagged => {
+                let (tag, quoted) = text.split_at(text.find('"')?);
+                ValueNode::Tagged {
+                    tag: normalize_identifier(tag),
+                    value: string_value(quoted),
+                    span,
+                }
+            }
+            SyntaxKind::Number => ValueNode::Number {
+                value: parse_number_literal(text, span).ok()?,
+                span,
+            },
+            SyntaxKind::Bool => ValueNode::Bool {
+                value: text == "true",
+                span,
+            },
+            _ => return None,
+        })
+    }
+
+    // An entry without a key holds a spread.
+    fn object_entry(&self, node: SyntaxNode<'_>) -> ObjectEntry {
+        let docs = self.docs(node);
+        let annotations = self.annotations(node);
+        let Some(key) = significant_tokens(node).next() else {
+            let value = node.child_nodes().next().map_or_else(
+                || ValueNode::Error {
+                    span: self.span(node.span()),
+                },
+                |child| self.value(child),
+            );
+            return ObjectEntry {
+                key: String::new(),
+                span: value.span(),
+                value,
+                docs,
+                annotations,
+            };
+        };
+        let name = match key.kind() {
+            SyntaxKind::String => string_value(key.text()),
+            _ => key.text().to_string(),
+        };
+        let value = self.value_child(node, key.span().end);
+        ObjectEntry {
+            key: normalize_identifier(&name),
+            span: self.span(key.span().union(value.span())),
+            value,
+            docs,
+            annotations,
+        }
+    }
+
+    fn annotations(&self, node: SyntaxNode<'_>) -> Vec<Annotation> {
+        node.child_nodes()
+            .filter(|child| child.kind() == SyntaxKind::Annotation)
+            .filter_map(|annotation| {
+                let name = significant_tokens(annotation).nth(1)?;
+                Some(Annotation {
+                    name: normalize_identifier(name.text()),
+                    args: annotation
+                        .child_nodes()
+                        .map(|arg| self.value(arg))
+                        .collect(),
+                    span: self.span(annotation.span()),
+                })
+            })
+            .collect()
+    }
+
+    // The `##` lines a node starts with.
+    fn docs(&self, node: SyntaxNode<'_>) -> Vec<DocComment> {
+        node.children()
+            .map_while(|child| match child {
+                SyntaxElement::Token(token) if token.kind().is_trivia() => Some(token),
+                _ => None,
+            })
+            .filter(|token| token.kind() == SyntaxKind::DocComment)
+            .map(|token| {
+                let raw = token.text();
+                let text = raw.strip_prefix(DOC_COMMENT_PREFIX).unwrap_or(raw);
+                let text = text.strip_prefix(' ').unwrap_or(text);
+                DocComment::new(text.trim_end(), self.span(token.span()))
+            })
+            .collect()
+    }
+}
+
+// The node's own tokens that are not trivia, such as a key or a keyword.
+fn significant_tokens<'t>(node: SyntaxNode<'t>) -> impl Iterator<Item = SyntaxToken<'t>> {
+    node.child_tokens()
+        .filter(|token| !token.kind().is_trivia())
+}
@@ -0,0 +1,8 @@
+pub(crate) mod builder;
+pub(crate) mod green;
+pub mod kind;
+pub(crate) mod lower;
+pub mod tree;
+
+pub use self::kind::SyntaxKind;
+pub use self::tree::{SyntaxElement, SyntaxNode, SyntaxToken, SyntaxTree};
@@ -0,0 +1,283 @@
+use std::{
+    fmt,
+    sync::{Arc, OnceLock},
+};
+
+use crate::{
+    ast::{AstNode, FileId, Span},
+    error::{CoreError, Warning},
+    limits::Limits,
+    parser::Parser,
+    version::LanguageVersion,
+};
+
+use super::{
+    green::{GreenChild, GreenNode, GreenToken},
+    kind::SyntaxKind,
+    lower,
+};
+
+// Tokens and nodes are views into a `SyntaxTree` that know where in the
+// source they are; the tree itself only stores widths.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct SyntaxToken<'t> {
+    green: &'t GreenToken,
+    offset: usize,
+}
+
+impl<'t> SyntaxToken<'t> {
+    pub fn kind(&self) -> SyntaxKind {
+        self.green.kind()
+    }
+
+    pub fn span(&self) -> Span {
+        Span::new(self.offset, self.offset + self.green.text().len())
+    }
+
+    pub fn text(&self) -> &'t str {
+        self.green.text()
+    }
+}
+
+impl fmt::Debug for SyntaxToken<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SyntaxToken")
+            .field("kind", &self.kind())
+            .field("span", &self.span())
+            .field("text", &self.text())
+            .finish()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyntaxElement<'t> {
+    Node(SyntaxNode<'t>),
+    Token(SyntaxToken<'t>),
+}
+
+impl SyntaxElement<'_> {
+    pub fn kind(&self) -> SyntaxKind {
+        match self {
+            SyntaxElement::Node(node) => node.kind(),
+            SyntaxElement::Token(token) => token.kind(),
+        }
+    }
+
+    pub fn span(&self) -> Span {
+        match self {
+            SyntaxElement::Node(node) => node.span(),
+            SyntaxElement::Token(token) => token.span(),
+        }
+    }
+}
+
+// A node's span covers every byte of its children, including leading doc
+// comments and annotations, so it can be wider than the matching AST span.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct SyntaxNode<'t> {
+    green: &'t GreenNode,
+    offset: usize,
+}
+
+impl<'t> SyntaxNode<'t> {
+    pub(crate) fn new(green: &'t GreenNode, offset: usize) -> Self {
+        Self { green, offset }
+    }
+
+    pub fn kind(&self) -> SyntaxKind {
+        self.green.kind()
+    }
+
+    pub fn span(&self) -> Span {
+        Span::new(self.offset, self.offset + self.green.len())
+    }
+
+    pub fn text(&self) -> String {
+        self.tokens().map(|token| token.text()).collect()
+    }
+
+    pub fn children(&self) -> impl Iterator<Item = SyntaxElement<'t>> + use<'t> {
+        let (green, mut offset) = (self.green, self.offset);
+        green.children().iter().map(move |child| {
+            let element = match child {
+                GreenChild::Node(green) => SyntaxElement::Node(SyntaxNode { green, offset }),
+                GreenChild::Token(green) => SyntaxElement::Token(SyntaxToken { green, offset }),
+            };
+            offset += child.len();
+            element
+        })
+    }
+
+    pub fn child_nodes(&self) -> impl Iterator<Item = SyntaxNode<'t>> + use<'t> {
+        self.children().filter_map(|child| match child {
+            SyntaxElement::Node(node) => Some(node),
+            SyntaxElement::Token(_) => None,
+        })
+    }
+
+    pub fn child_tokens(&self) -> impl Iterator<Item = SyntaxToken<'t>> + use<'t> {
+        self.children().filter_map(|child| match child {
+            SyntaxElement::Token(token) => Some(token),
+            SyntaxElement::Node(_) => None,
+        })
+    }
+
+    // Every token under the node, in source order.
+    pub fn tokens(&self) -> impl Iterator<Item = SyntaxToken<'t>> + use<'t> {
+        let mut stack = vec![self.children()];
+        std::iter::from_fn(move || {
+            while let Some(children) = stack.last_mut() {
+                match children.next() {
+                    Some(SyntaxElement::Token(token)) => return Some(token),
+                    Some(SyntaxElement::Node(node)) => stack.push(node.children()),
+                    None => {
+                        stack.pop();
+                    }
+                }
+            }
+            None
+        })
+    }
+
+    // Returns the deepest node whose span contains `span`.
+    pub fn covering_node(&self, span: Span) -> Option<SyntaxNode<'t>> {
+        let own = self.span();
+        if span.start < own.start || span.end > own.end {
+            return None;
+        }
+        Some(
+            self.child_nodes()
+                .find_map(|child| child.covering_node(span))
+                .unwrap_or(*self),
+        )
+    }
+
+    // Whether both views are of the same stored node, as they are for a
+    // subtree an incremental reparse kept.
+    pub fn is_same(&self, other: &SyntaxNode<'_>) -> bool {
+        std::ptr::eq(self.green, other.green)
+    }
+}
+
+impl fmt::Debug for SyntaxNode<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SyntaxNode")
+            .field("kind", &self.kind())
+            .field("span", &self.span())
+            .field("children", &self.children().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+// Lossless view of a source file: every byte, including whitespace, comments,
+// and text that does not lex or parse, belongs to exactly one token. The
+// parser builds this tree; the AST of each document is derived from it.
+#[derive(Debug, Clone)]
+pub struct SyntaxTree {
+    root: Arc<GreenNode>,
+    version: LanguageVersion,
+    limits: Limits,
+    errors: Vec<CoreError>,
+    warnings: Vec<Warning>,
+    // Lexer tokens, counted against `limits.max_tokens`, and whether any
+    // identifier is not ASCII. Both let `reparse` decide locally whether the
+    // rest of the source is affected.
+    tokens: usize,
+    unicode_identifiers: bool,
+    documents: OnceLock<Vec<AstNode>>,
+}
+
+impl SyntaxTree {
+    // Never fails: whatever does not lex or parse ends up in error nodes and
+    // is reported by `errors`.
+    pub fn parse(source: &str) -> Self {
+        Self::parse_with_limits(source, Limits::default())
+    }
+
+    pub fn parse_with_limits(source: &str, limits: Limits) -> Self {
+        Parser::from_source_with_recovery_and_limits(source, limits).parse_syntax_tree()
+    }
+
+    pub(crate) fn new(
+        root: Arc<GreenNode>,
+        version: LanguageVersion,
+        limits: Limits,
+        errors: Vec<CoreError>,
+        warnings: Vec<Warning>,
+        tokens: usize,
+        unicode_identifiers: bool,
+    ) -> Self {
+        Self {
+            root,
+            version,
+            limits,
+            errors,
+            warnings,
+            tokens,
+            unicode_identifiers,
+            documents: OnceLock::new(),
+        }
+    }
+
+    pub fn version(&self) -> LanguageVersion {
+        self.version
+    }
+
+    pub fn limits(&self) -> Limits {
+        self.limits
+    }
+
+    pub(crate) fn green(&self) -> &Arc<GreenNode> {
+        &self.root
+    }
+
+    pub(crate) fn token_count(&self) -> usize {
+        self.tokens
+    }
+
+    pub(crate) fn has_unicode_identifiers(&self) -> bool {
+        self.unicode_identifiers
+    }
+
+    pub fn root(&self) -> SyntaxNode<'_> {
+        SyntaxNode::new(&self.root, 0)
+    }
+
+    pub fn len(&self) -> usize {
+        self.root.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.len() == 0
+    }
+
+    // Lexical and parse errors, ordered by position.
+    pub fn errors(&self) -> &[CoreError] {
+        &self.errors
+    }
+
+    pub fn warnings(&self) -> &[Warning] {
+        &self.warnings
+    }
+
+    // The AST of every document, derived from the tree on first use. Parts
+    // that did not parse are error nodes.
+    pub fn documents(&self) -> &[AstNode] {
+        self.documents
+            .get_or_init(|| lower::documents(self.root(), FileId::default()))
+    }
+
+    pub fn covering_node(&self, span: Span) -> Option<SyntaxNode<'_>> {
+        self.root().covering_node(span)
+    }
+}
+
+// Rebuilds the text from the tokens, which reproduces the source exactly.
+impl fmt::Display for SyntaxTree {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for token in self.root().tokens() {
+            f.write_str(token.text())?;
+        }
+        Ok(())
+    }
+}
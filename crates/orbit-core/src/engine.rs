@@ -0,0 +1,415 @@
+use std::{
+    collections::HashMap,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+};
+
+use crate::{
+    ast::{AstNode, SourceMap, Span},
+    cst::SyntaxTree,
+    error::{CoreError, ErrorCode, RuntimeError, Warning},
+    files::FileDatabase,
+    limits::Limits,
+    parser::{self, ParseReport, Parser, TextEdit},
+    runtime::{Evaluation, EvaluationOptions, EvaluationReport, Evaluator},
+    value::OrbitValue,
+};
+
+pub type Loader = dyn Fn(&Path) -> io::Result<String> + Send + Sync;
+
+#[derive(Debug, thiserror::Error)]
+pub enum EngineError {
+    #[error("failed to read {}", path.display())]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+    #[error(transparent)]
+    Core(#[from] CoreError),
+}
+
+// The embedding entry point: one value that carries everything evaluation is
+// configured with, so new settings become builder methods here instead of
+// more free functions. The crate-root functions use a default engine.
+pub struct Engine {
+    options: EvaluationOptions,
+    loader: Arc<Loader>,
+    // Every text `evaluate_file` parsed, so spans can name their file.
+    files: Mutex<FileDatabase>,
+    // Parsed files by path. A file is parsed again only when the loader
+    // returns different text for it.
+    parsed: Mutex<HashMap<PathBuf, Arc<ParsedFile>>>,
+}
+
+struct ParsedFile {
+    source_map: Arc<SourceMap>,
+    document: AstNode,
+    warnings: Vec<Warning>,
+}
+
+impl Engine {
+    pub fn new() -> Self {
+        Self {
+            options: EvaluationOptions::default(),
+            loader: Arc::new(|path: &Path| fs::read_to_string(path)),
+            files: Mutex::new(FileDatabase::new()),
+            parsed: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn with_options(mut self, options: EvaluationOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    pub fn with_profile(mut self, name: impl Into<String>) -> Self {
+        self.options = self.options.with_profile(name);
+        self
+    }
+
+    pub fn with_tag<F>(mut self, tag: impl Into<String>, parser: F) -> Self
+    where
+        F: Fn(&str, Span) -> Result<OrbitValue, RuntimeError> + Send + Sync + 'static,
+    {
+        self.options = self.options.with_tag(tag, parser);
+        self
+    }
+
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.options = self.options.with_limits(limits);
+        self
+    }
+
+    pub fn with_spread_overrides(mut self, allow: bool) -> Self {
+        self.options = self.options.with_spread_overrides(allow);
+        self
+    }
+
+    pub fn with_deny_warnings(mut self, deny: bool) -> Self {
+        self.options = self.options.with_deny_warnings(deny);
+        self
+    }
+
+    // Replaces how `load` and `evaluate_file` read a path, for hosts that
+    // keep configuration somewhere other than the file system.
+    pub fn with_loader<F>(mut self, loader: F) -> Self
+    where
+        F: Fn(&Path) -> io::Result<String> + Send + Sync + 'static,
+    {
+        self.loader = Arc::new(loader);
+        self
+    }
+
+    pub fn options(&self) -> &EvaluationOptions {
+        &self.options
+    }
+
+    pub fn limits(&self) -> Limits {
+        self.options.limits
+    }
+
+    pub fn parse(&self, source: &str) -> Result<AstNode, CoreError> {
+        let ast = Parser::from_source_with_limits(source, self.limits())?.parse_document()?;
+        Ok(ast)
+    }
+
+    // The lexer and parser warnings, located in `source`, or the error they
+    // become when warnings are denied.
+    pub fn parse_with_warnings(&self, source: &str) -> Result<(AstNode, Vec<Warning>), CoreError> {
+        let (ast, mut warnings) = Parser::from_source_with_limits(source, self.limits())?
+            .parse_document_with_warnings()?;
+        locate_warnings(&mut warnings, source);
+        self.deny(&warnings)?;
+        Ok((ast, warnings))
+    }
+
+    pub fn parse_documents(&self, source: &str) -> Result<Vec<AstNode>, CoreError> {
+        let documents =
+            Parser::from_source_with_limits(source, self.limits())?.parse_documents()?;
+        Ok(documents)
+    }
+
+    pub fn parse_with_recovery(&self, source: &str) -> ParseReport {
+        let mut report = Parser::from_source_with_recovery_and_limits(source, self.limits())
+            .parse_document_with_recovery();
+        locate_warnings(&mut report.warnings, source);
+        report
+    }
+
+    pub fn evaluate(&self, source: &str) -> Result<OrbitValue, CoreError> {
+        // Warnings are dropped here unless they are denied.
+        Ok(self.evaluate_detailed(source)?.value)
+    }
+
+    pub fn evaluate_detailed(&self, source: &str) -> Result<Evaluation, CoreError> {
+        let (ast, warnings) = Parser::from_source_with_limits(source, self.limits())?
+            .parse_document_with_warnings()?;
+        self.evaluate_parsed(&ast, &warnings, source)
+    }
+
+    pub fn evaluate_documents(&self, source: &str) -> Result<Vec<OrbitValue>, CoreError> {
+        let (documents, warnings) = Parser::from_source_with_limits(source, self.limits())?
+            .parse_documents_with_warnings()?;
+        self.evaluate_parsed_documents(&documents, warnings, source)
+    }
+
+    pub fn evaluate_ast(&self, ast: &AstNode) -> Result<OrbitValue, RuntimeError> {
+        Evaluator::evaluate_with_options(ast, &self.options)
+    }
+
+    // Like `evaluate`, but keeps going after a runtime error and reports all
+    // of them. Lexical and parse errors still fail; see `evaluate_parse_report`.
+    pub fn evaluate_report(&self, source: &str) -> Result<EvaluationReport, CoreError> {
+        let (ast, warnings) = Parser::from_source_with_limits(source, self.limits())?
+            .parse_document_with_warnings()?;
+        let mut report = Evaluator::evaluate_partial_allowing_warnings(&ast, &self.options);
+        self.add_warnings(&mut report, warnings, Some(source));
+        Ok(report)
+    }
+
+    pub fn evaluate_ast_report(&self, ast: &AstNode) -> EvaluationReport {
+        Evaluator::evaluate_partial(ast, &self.options)
+    }
+
+    // Evaluates whatever a recovering parse could read. The lex and parse
+    // errors of `report` are merged with the runtime errors, ordered by
+    // position.
+    pub fn evaluate_parse_report(&self, report: ParseReport) -> EvaluationReport {
+        self.evaluate_recovered(report, None)
+    }
+
+    fn evaluate_recovered(&self, report: ParseReport, source: Option<&str>) -> EvaluationReport {
+        let mut evaluation =
+            Evaluator::evaluate_partial_allowing_warnings(&report.document, &self.options);
+        let mut errors = report.errors;
+        errors.append(&mut evaluation.errors);
+        errors.sort_by_key(|error| error.span().start);
+        evaluation.errors = errors;
+        self.add_warnings(&mut evaluation, report.warnings, source);
+        evaluation
+    }
+
+    // Best-effort evaluation of every document in `source`, each with its own
+    // report. A single document that does not parse is evaluated as far as
+    // it can be recovered; several documents cannot be recovered, so those
+    // fail with the first error.
+    pub fn evaluate_documents_report(
+        &self,
+        source: &str,
+    ) -> Result<Vec<EvaluationReport>, CoreError> {
+        let parsed = Parser::from_source_with_limits(source, self.limits())
+            .map_err(CoreError::from)
+            .and_then(|parser| Ok(parser.parse_documents_with_warnings()?));
+        let (documents, warnings) = match parsed {
+            Ok(parsed) => parsed,
+            Err(err) => {
+                let report = self.parse_with_recovery(source);
+                if report
+                    .errors
+                    .iter()
+                    .any(|error| error.code() == ErrorCode::UnexpectedSeparator)
+                {
+                    return Err(err);
+                }
+                return Ok(vec![self.evaluate_recovered(report, Some(source))]);
+            }
+        };
+
+        let reports = documents
+            .iter()
+            .zip(by_document(&documents, warnings))
+            .map(|(document, warnings)| {
+                let mut report =
+                    Evaluator::evaluate_partial_allowing_warnings(document, &self.options);
+                self.add_warnings(&mut report, warnings, Some(source));
+                report
+            })
+            .collect();
+        Ok(reports)
+    }
+
+    pub fn parse_cst(&self, source: &str) -> SyntaxTree {
+        SyntaxTree::parse_with_limits(source, self.limits())
+    }
+
+    // Shares the untouched subtrees of `previous` only when it was parsed
+    // with the limits of this engine; otherwise `source` is parsed in full.
+    pub fn reparse(&self, previous: &SyntaxTree, source: &str, edit: &TextEdit) -> SyntaxTree {
+        if previous.limits() != self.limits() {
+            return self.parse_cst(source);
+        }
+        parser::reparse(previous, source, edit)
+    }
+
+    pub fn load(&self, path: impl AsRef<Path>) -> Result<Arc<SourceMap>, EngineError> {
+        let path = path.as_ref();
+        let source = (self.loader)(path).map_err(|source| EngineError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        Ok(Arc::new(SourceMap::new(path.display().to_string(), source)))
+    }
+
+    // Errors carry the file's source map, so they display as
+    // `path:line:column`, and every span carries the id the file got in
+    // `files()`.
+    pub fn evaluate_file(&self, path: impl AsRef<Path>) -> Result<OrbitValue, EngineError> {
+        let file = self.parse_file(path.as_ref())?;
+        let evaluation = self
+            .evaluate_parsed(&file.document, &file.warnings, file.source_map.source())
+            .map_err(|error| error.with_source(file.source_map.clone()))?;
+        Ok(evaluation.value)
+    }
+
+    // The files `evaluate_file` has read so far. A file whose text changed
+    // is added again under a new id, so earlier spans stay valid.
+    pub fn files(&self) -> FileDatabase {
+        lock(&self.files).clone()
+    }
+
+    pub fn clear_cache(&self) {
+        lock(&self.parsed).clear();
+    }
+
+    fn parse_file(&self, path: &Path) -> Result<Arc<ParsedFile>, EngineError> {
+        let source_map = self.load(path)?;
+        if let Some(file) = lock(&self.parsed).get(path)
+            && file.source_map.source() == source_map.source()
+        {
+            return Ok(file.clone());
+        }
+        let id = lock(&self.files).insert(source_map.clone());
+        let (document, warnings) =
+            Parser::from_source_with_limits(source_map.source(), self.limits())
+                .map(|parser| parser.in_file(id))
+                .map_err(|mut error| {
+                    error.span.file = id;
+                    CoreError::from(error)
+                })
+                .and_then(|parser| Ok(parser.parse_document_with_warnings()?))
+                .map_err(|error| error.with_source(source_map.clone()))?;
+        let file = Arc::new(ParsedFile {
+            source_map,
+            document,
+            warnings,
+        });
+        lock(&self.parsed).insert(path.to_path_buf(), file.clone());
+        Ok(file)
+    }
+
+    // Each document is checked against the lexer and parser warnings that
+    // fall inside it.
+    pub(crate) fn evaluate_parsed_documents(
+        &self,
+        documents: &[AstNode],
+        warnings: Vec<Warning>,
+        source: &str,
+    ) -> Result<Vec<OrbitValue>, CoreError> {
+        documents
+            .iter()
+            .zip(by_document(documents, warnings))
+            .map(|(document, warnings)| {
+                Ok(self.evaluate_parsed(document, &warnings, source)?.value)
+            })
+            .collect()
+    }
+
+    // Warnings are located in `source` before they are denied, so the error
+    // they become displays its line and column too.
+    pub(crate) fn evaluate_parsed(
+        &self,
+        ast: &AstNode,
+        warnings: &[Warning],
+        source: &str,
+    ) -> Result<Evaluation, CoreError> {
+        // A runtime error wins over the warnings of the evaluator, which
+        // may be incomplete, but not over those found while parsing.
+        let mut evaluation = match Evaluator::evaluate_allowing_warnings(ast, &self.options) {
+            Ok(evaluation) => evaluation,
+            Err(error) => {
+                let mut warnings = warnings.to_vec();
+                locate_warnings(&mut warnings, source);
+                self.deny(&warnings)?;
+                return Err(error.into());
+            }
+        };
+        evaluation.warnings.splice(0..0, warnings.iter().cloned());
+        locate_warnings(&mut evaluation.warnings, source);
+        self.deny(&evaluation.warnings)?;
+        Ok(evaluation)
+    }
+
+    pub(crate) fn deny(&self, warnings: &[Warning]) -> Result<(), CoreError> {
+        match Warning::deny_all(warnings) {
+            Some(error) if self.options.deny_warnings => Err(error.into()),
+            _ => Ok(()),
+        }
+    }
+
+    // Puts lexer and parser warnings ahead of the evaluator's, or among the
+    // errors when warnings are denied. With its `source`, every warning is
+    // located first.
+    fn add_warnings(
+        &self,
+        report: &mut EvaluationReport,
+        warnings: Vec<Warning>,
+        source: Option<&str>,
+    ) {
+        report.warnings.splice(0..0, warnings);
+        if let Some(source) = source {
+            locate_warnings(&mut report.warnings, source);
+        }
+        if self.options.deny_warnings {
+            report.deny_warnings();
+        }
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    // Neither value is ever left half-updated, so a panic elsewhere while the
+    // lock was held does not make it unusable.
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+// Splits the lexer and parser warnings of a whole source by the document
+// they occur in.
+fn by_document(documents: &[AstNode], warnings: Vec<Warning>) -> Vec<Vec<Warning>> {
+    let mut by_document = vec![Vec::new(); documents.len()];
+    for warning in warnings {
+        let index = documents
+            .iter()
+            .rposition(|document| document.span().start <= warning.span.start)
+            .unwrap_or(0);
+        by_document[index].push(warning);
+    }
+    by_document
+}
+
+// Lets warnings of an unnamed source display as `line:column`.
+fn locate_warnings(warnings: &mut Vec<Warning>, source: &str) {
+    if warnings.is_empty() {
+        return;
+    }
+    let source_map = Arc::new(SourceMap::new("", source));
+    *warnings = std::mem::take(warnings)
+        .into_iter()
+        .map(|warning| warning.with_source(source_map.clone()))
+        .collect();
+}
+
+impl Default for Engine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for Engine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Engine")
+            .field("options", &self.options)
+            .field("cached_files", &lock(&self.parsed).len())
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,393 @@
+use std::{fmt, str::FromStr};
+
+use serde::Serialize;
+
+// Stable identifiers for every error the core reports. Bindings match on
+// these instead of messages, so a code is never renumbered or reused once
+// released, while message wording may change freely. New codes may be
+// added in any release, so matches need a wildcard arm.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[non_exhaustive]
+pub enum ErrorCode {
+    #[serde(rename = "E0100")]
+    Lex,
+    #[serde(rename = "E0101")]
+    UnterminatedString,
+    #[serde(rename = "E0102")]
+    UnterminatedEscape,
+    #[serde(rename = "E0103")]
+    UnexpectedCharacter,
+    #[serde(rename = "E0104")]
+    InvalidBytes,
+    #[serde(rename = "E0105")]
+    UnsupportedVersion,
+    #[serde(rename = "E0106")]
+    SingleQuotedString,
+    #[serde(rename = "E0200")]
+    Parse,
+    #[serde(rename = "E0201")]
+    ExpectedIdentifier,
+    #[serde(rename = "E0202")]
+    MissingColon,
+    #[serde(rename = "E0203")]
+    MissingComma,
+    #[serde(rename = "E0204")]
+    UnclosedDelimiter,
+    #[serde(rename = "E0205")]
+    MisplacedProfile,
+    #[serde(rename = "E0206")]
+    UnterminatedBlock,
+    #[serde(rename = "E0207")]
+    UnexpectedToken,
+    #[serde(rename = "E0208")]
+    UnsupportedSyntax,
+    #[serde(rename = "E0209")]
+    UnexpectedSeparator,
+    #[serde(rename = "E0210")]
+    InvalidNumber,
+    #[serde(rename = "E0300")]
+    Runtime,
+    #[serde(rename = "E0301")]
+    DuplicateKey,
+    #[serde(rename = "E0302")]
+    DuplicateProfile,
+    #[serde(rename = "E0303")]
+    UnknownProfile,
+    #[serde(rename = "E0304")]
+    UnknownReference,
+    #[serde(rename = "E0305")]
+    InvalidSpread,
+    #[serde(rename = "E0306")]
+    UnknownTag,
+    #[serde(rename = "E0307")]
+    InvalidTaggedLiteral,
+    #[serde(rename = "E0308")]
+    UnparsedNode,
+    #[serde(rename = "E0401")]
+    NestingTooDeep,
+    #[serde(rename = "E0402")]
+    TooManyTokens,
+    #[serde(rename = "E0403")]
+    StringTooLong,
+    #[serde(rename = "E0404")]
+    TooManyValues,
+    #[serde(rename = "E0500")]
+    Warning,
+    #[serde(rename = "E0501")]
+    MixedScripts,
+    #[serde(rename = "E0502")]
+    ConfusableIdentifier,
+    #[serde(rename = "E0503")]
+    UnknownEscape,
+    #[serde(rename = "E0504")]
+    LossyFloat,
+    #[serde(rename = "E0505")]
+    UnsafeInteger,
+    #[serde(rename = "E0506")]
+    DeprecatedKey,
+    #[serde(rename = "E0507")]
+    EmptyBlock,
+}
+
+impl ErrorCode {
+    pub const ALL: [ErrorCode; 39] = [
+        ErrorCode::Lex,
+        ErrorCode::UnterminatedString,
+        ErrorCode::UnterminatedEscape,
+        ErrorCode::UnexpectedCharacter,
+        ErrorCode::InvalidBytes,
+        ErrorCode::UnsupportedVersion,
+        ErrorCode::SingleQuotedString,
+        ErrorCode::Parse,
+        ErrorCode::ExpectedIdentifier,
+        ErrorCode::MissingColon,
+        ErrorCode::MissingComma,
+        ErrorCode::UnclosedDelimiter,
+        ErrorCode::MisplacedProfile,
+        ErrorCode::UnterminatedBlock,
+        ErrorCode::UnexpectedToken,
+        ErrorCode::UnsupportedSyntax,
+        ErrorCode::UnexpectedSeparator,
+        ErrorCode::InvalidNumber,
+        ErrorCode::Runtime,
+        ErrorCode::DuplicateKey,
+        ErrorCode::DuplicateProfile,
+        ErrorCode::UnknownProfile,
+        ErrorCode::UnknownReference,
+        ErrorCode::InvalidSpread,
+        ErrorCode::UnknownTag,
+        ErrorCode::InvalidTaggedLiteral,
+        ErrorCode::UnparsedNode,
+        ErrorCode::NestingTooDeep,
+        ErrorCode::TooManyTokens,
+        ErrorCode::StringTooLong,
+        ErrorCode::TooManyValues,
+        ErrorCode::Warning,
+        ErrorCode::MixedScripts,
+        ErrorCode::ConfusableIdentifier,
+        ErrorCode::UnknownEscape,
+        ErrorCode::LossyFloat,
+        ErrorCode::UnsafeInteger,
+        ErrorCode::DeprecatedKey,
+        ErrorCode::EmptyBlock,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ErrorCode::Lex => "E0100",
+            ErrorCode::UnterminatedString => "E0101",
+            ErrorCode::UnterminatedEscape => "E0102",
+            ErrorCode::UnexpectedCharacter => "E0103",
+            ErrorCode::InvalidBytes => "E0104",
+            ErrorCode::UnsupportedVersion => "E0105",
+            ErrorCode::SingleQuotedString => "E0106",
+            ErrorCode::Parse => "E0200",
+            ErrorCode::ExpectedIdentifier => "E0201",
+            ErrorCode::MissingColon => "E0202",
+            ErrorCode::MissingComma => "E0203",
+            ErrorCode::UnclosedDelimiter => "E0204",
+            ErrorCode::MisplacedProfile => "E0205",
+            ErrorCode::UnterminatedBlock => "E0206",
+            ErrorCode::UnexpectedToken => "E0207",
+            ErrorCode::UnsupportedSyntax => "E0208",
+            ErrorCode::UnexpectedSeparator => "E0209",
+            ErrorCode::InvalidNumber => "E0210",
+            ErrorCode::Runtime => "E0300",
+            ErrorCode::DuplicateKey => "E0301",
+            ErrorCode::DuplicateProfile => "E0302",
+            ErrorCode::UnknownProfile => "E0303",
+            ErrorCode::UnknownReference => "E0304",
+            ErrorCode::InvalidSpread => "E0305",
+            ErrorCode::UnknownTag => "E0306",
+            ErrorCode::InvalidTaggedLiteral => "E0307",
+            ErrorCode::UnparsedNode => "E0308",
+            ErrorCode::NestingTooDeep => "E0401",
+            ErrorCode::TooManyTokens => "E0402",
+            ErrorCode::StringTooLong => "E0403",
+            ErrorCode::TooManyValues => "E0404",
+            ErrorCode::Warning => "E0500",
+            ErrorCode::MixedScripts => "E0501",
+            ErrorCode::ConfusableIdentifier => "E0502",
+            ErrorCode::UnknownEscape => "E0503",
+            ErrorCode::LossyFloat => "E0504",
+            ErrorCode::UnsafeInteger => "E0505",
+            ErrorCode::DeprecatedKey => "E0506",
+            ErrorCode::EmptyBlock => "E0507",
+        }
+    }
+
+    pub fn title(self) -> &'static str {
+        match self {
+            ErrorCode::Lex => "lexical error",
+            ErrorCode::UnterminatedString => "unterminated string",
+            ErrorCode::UnterminatedEscape => "unterminated escape",
+            ErrorCode::UnexpectedCharacter => "unexpected character",
+            ErrorCode::InvalidBytes => "invalid bytes literal",
+            ErrorCode::UnsupportedVersion => "unsupported language version",
+            ErrorCode::SingleQuotedString => "single-quoted string",
+            ErrorCode::Parse => "syntax error",
+            ErrorCode::ExpectedIdentifier => "expected identifier",
+            ErrorCode::MissingColon => "missing colon",
+            ErrorCode::MissingComma => "missing comma",
+            ErrorCode::UnclosedDelimiter => "unclosed delimiter",
+            ErrorCode::MisplacedProfile => "misplaced profile",
+            ErrorCode::UnterminatedBlock => "unterminated block",
+            ErrorCode::UnexpectedToken => "unexpected token",
+            ErrorCode::UnsupportedSyntax => "syntax not available in this version",
+            ErrorCode::UnexpectedSeparator => "unexpected document separator",
+            ErrorCode::InvalidNumber => "invalid number",
+            ErrorCode::Runtime => "evaluation error",
+            ErrorCode::DuplicateKey => "duplicate key",
+            ErrorCode::DuplicateProfile => "duplicate profile",
+            ErrorCode::UnknownProfile => "unknown profile",
+            ErrorCode::UnknownReference => "unknown reference",
+            ErrorCode::InvalidSpread => "invalid spread",
+            ErrorCode::UnknownTag => "unknown literal tag",
+            ErrorCode::InvalidTaggedLiteral => "invalid tagged literal",
+            ErrorCode::UnparsedNode => "evaluating a parse error",
+            ErrorCode::NestingTooDeep => "nesting too deep",
+            ErrorCode::TooManyTokens => "too many tokens",
+            ErrorCode::StringTooLong => "string too long",
+            ErrorCode::TooManyValues => "too many values",
+            ErrorCode::Warning => "warning",
+            ErrorCode::MixedScripts => "identifier mixes scripts",
+            ErrorCode::ConfusableIdentifier => "confusable identifier",
+            ErrorCode::UnknownEscape => "unknown escape sequence",
+            ErrorCode::LossyFloat => "float literal loses precision",
+            ErrorCode::UnsafeInteger => "integer outside the safe range",
+            ErrorCode::DeprecatedKey => "deprecated key",
+            ErrorCode::EmptyBlock => "empty block",
+        }
+    }
+
+    // Long-form text printed by `orbit explain`.
+    pub fn explanation(self) -> &'static str {
+        match self {
+            ErrorCode::Lex => "The source could not be split into tokens.",
+            ErrorCode::UnterminatedString => {
+                "A string literal was opened with `\"` but not closed on the same line. Strings cannot\n\
+                span several lines; use `\\n` for a line break.\n\n    name: \"orbit      # error\n    name: \"orbit\"    # ok"
+            }
+            ErrorCode::UnterminatedEscape => {
+                "A `\\` at the very end of the source starts an escape sequence with no character\n\
+                after it. Escape the backslash itself with `\\\\`."
+            }
+            ErrorCode::UnexpectedCharacter => {
+                "The source contains characters that cannot start any token, such as `$` or `?`\n\
+                outside a string. Quote the text if it is meant as a value.\n\n    path: $HOME      # error\n    path: \"$HOME\"    # ok"
+            }
+            ErrorCode::InvalidBytes => {
+                "The text of a `hex\"..\"` or `b64\"..\"` literal is not valid in that encoding. Hex\n\
+                literals need an even number of hex digits; base64 literals need standard padding.\n\n    key: hex\"abc\"     # error\n    key: hex\"0abc\"    # ok"
+            }
+            ErrorCode::UnsupportedVersion => {
+                "The `#!orbit <version>` pragma on the first line names a version this release does\n\
+                not know. Use one of the versions listed in the specification, or drop the pragma to\n\
+                get the latest grammar."
+            }
+            ErrorCode::SingleQuotedString => {
+                "Strings are written with double quotes; `'` does not start a string.\n\n    \
+                name: 'orbit'    # error\n    name: \"orbit\"    # ok"
+            }
+            ErrorCode::Parse => "The tokens do not form a valid document.",
+            ErrorCode::ExpectedIdentifier => {
+                "A key, block name, profile name, annotation name, or spread target is missing. Keys\n\
+                that are not identifiers must be quoted inside object literals.\n\n    : 1              # error\n    port: 1          # ok"
+            }
+            ErrorCode::MissingColon => {
+                "Entries are written `key: value`; the `:` after the key is missing.\n\n    port 80          # error\n    port: 80         # ok"
+            }
+            ErrorCode::MissingComma => {
+                "Items of a list and entries of an object literal are separated by `,`.\n\n    ports: [80 443]  # error\n    ports: [80, 443] # ok"
+            }
+            ErrorCode::UnclosedDelimiter => {
+                "A `[`, `{`, or `(` was opened but the matching closing delimiter is missing.\n\n    ports: [80, 443  # error\n    ports: [80, 443] # ok"
+            }
+            ErrorCode::MisplacedProfile => {
+                "`profile <name> { .. }` sections may only appear at the top level of a document.\n\n    server {\n        profile prod { port: 80 }   # error\n    }"
+            }
+            ErrorCode::UnterminatedBlock => {
+                "A block was opened with `name {` but never closed with `}`."
+            }
+            ErrorCode::UnexpectedToken => {
+                "A value was expected, but the token found cannot start one. Values are strings,\n\
+                numbers, booleans, lists, object literals, bytes and tagged literals.\n\n    port: ]          # error"
+            }
+            ErrorCode::UnsupportedSyntax => {
+                "The document uses syntax that was added after the language version declared by its\n\
+                `#!orbit` pragma. Raise the pragma or avoid the construct."
+            }
+            ErrorCode::UnexpectedSeparator => {
+                "A `---` line separates documents, which only `parse_documents` (and the CLI's\n\
+                `eval`) accept. Single-document entry points reject it."
+            }
+            ErrorCode::InvalidNumber => "A number literal is out of range or malformed.",
+            ErrorCode::Runtime => {
+                "The document parsed but could not be evaluated. Errors raised by code outside the\n\
+                core that do not pick a more specific code use this one."
+            }
+            ErrorCode::DuplicateKey => {
+                "The same key or block name is defined twice in one scope or object literal. Every\n\
+                key must be unique; the error points at both definitions.\n\n    server {\n        port: 80\n        port: 81     # error\n    }\n\n\
+                Keys brought in by a spread may be overridden when the host enables\n\
+                `allow_spread_overrides`."
+            }
+            ErrorCode::DuplicateProfile => {
+                "Two `profile` sections in one document share a name. Merge their bodies."
+            }
+            ErrorCode::UnknownProfile => {
+                "The host selected a profile that the document does not define."
+            }
+            ErrorCode::UnknownReference => {
+                "A spread names a key that is not defined in this scope or an enclosing one. Only\n\
+                keys defined earlier can be spread.\n\n    all: [...base]   # error unless `base` is defined above"
+            }
+            ErrorCode::InvalidSpread => {
+                "A spread was used outside a list or object literal, or it names a value of the wrong\n\
+                type: lists spread into lists and objects into object literals.\n\n    base: [1, 2]\n    obj: { ...base } # error"
+            }
+            ErrorCode::UnknownTag => {
+                "A tagged literal such as `dur\"5s\"` uses a tag that no parser is registered for.\n\
+                Register one in `EvaluationOptions::tags`."
+            }
+            ErrorCode::InvalidTaggedLiteral => {
+                "The parser registered for a literal tag rejected the literal's text."
+            }
+            ErrorCode::UnparsedNode => {
+                "Evaluation reached a placeholder left by error recovery. Fix the parse errors\n\
+                reported for the document first."
+            }
+            ErrorCode::NestingTooDeep => {
+                "Lists, object literals, and blocks are nested deeper than `Limits::max_depth`\n\
+                (128 by default). Flatten the document or raise the limit."
+            }
+            ErrorCode::TooManyTokens => {
+                "The source has more tokens than `Limits::max_tokens` (1,000,000 by default).\n\
+                Split the document or raise the limit."
+            }
+            ErrorCode::StringTooLong => {
+                "A string or bytes literal is longer than `Limits::max_string_length` bytes\n\
+                (1 MiB by default)."
+            }
+            ErrorCode::TooManyValues => {
+                "Evaluation would produce more values than `Limits::max_output_nodes`\n\
+                (1,000,000 by default). Spreads copy the values they name, so spreading\n\
+                the same key repeatedly can multiply the size of a small document.\n\n    \
+                a: [1, 2]\n    b: [...a, ...a]\n    c: [...b, ...b]  # 8 values, doubling at every step"
+            }
+            ErrorCode::Warning => {
+                "Something in the document is valid but probably not what was meant. Warnings\n\
+                raised by code outside the core that do not pick a more specific code use this\n\
+                one. Like every E05xx code it is only an error when warnings are denied."
+            }
+            ErrorCode::MixedScripts => {
+                "An identifier mixes letters from several scripts, such as Latin and Cyrillic,\n\
+                which usually means a look-alike character was typed or pasted by accident."
+            }
+            ErrorCode::ConfusableIdentifier => {
+                "Two different identifiers render alike, for example `host` and `hоst` with a\n\
+                Cyrillic `о`. Rename one of them."
+            }
+            ErrorCode::UnknownEscape => {
+                "A string uses an escape other than `\\\"`, `\\\\`, `\\n`, `\\r`, or `\\t`. The\n\
+                backslash is dropped and the character kept.\n\n    path: \"C:\\data\"     # warning: `\\d` is `d`\n    path: \"C:\\\\data\"    # ok"
+            }
+            ErrorCode::LossyFloat => {
+                "A float literal has more significant digits than a 64-bit float can hold, so the\n\
+                evaluated value differs from what was written. Quote it if every digit matters."
+            }
+            ErrorCode::UnsafeInteger => {
+                "An integer is larger in magnitude than 2^53 - 1. JavaScript and many JSON parsers\n\
+                read numbers as 64-bit floats and would silently round it. Quote it if it is an\n\
+                identifier rather than a quantity."
+            }
+            ErrorCode::DeprecatedKey => {
+                "The document sets a key marked `@deprecated`. The annotation's argument, if any,\n\
+                says what to use instead."
+            }
+            ErrorCode::EmptyBlock => {
+                "A block has no entries and evaluates to an empty object, which is often a\n\
+                leftover from editing. Write `name: {}` if the empty object is intended."
+            }
+        }
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ErrorCode {
+    type Err = ();
+
+    // Accepts `E0301`, `e0301`, and the bare number `0301`.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let digits = value.strip_prefix(['E', 'e']).unwrap_or(value);
+        Self::ALL
+            .into_iter()
+            .find(|code| code.as_str()[1..] == *digits)
+            .ok_or(())
+    }
+}
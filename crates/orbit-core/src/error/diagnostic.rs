@@ -0,0 +1,473 @@
+use std::{fmt::Write, sync::Arc};
+
+use serde::Serialize;
+
+use crate::{
+    ast::{FileId, SourceMap, Span},
+    files::FileDatabase,
+};
+
+use super::{CoreError, ErrorCode, LexError, ParseError, RuntimeError, Suggestion, Warning};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl Severity {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Label {
+    pub span: Span,
+    pub message: String,
+}
+
+impl Label {
+    pub fn new(span: Span, message: impl Into<String>) -> Self {
+        Self {
+            span,
+            message: message.into(),
+        }
+    }
+}
+
+// Everything needed to print an error the way rustc does: the message, the
+// span it is about, other spans that explain it, and trailing notes.
+#[derive(Debug, Clone, Serialize)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<ErrorCode>,
+    pub message: String,
+    pub primary: Label,
+    pub secondary: Vec<Label>,
+    pub notes: Vec<String>,
+    pub suggestions: Vec<Suggestion>,
+    pub help: Option<String>,
+    #[serde(skip)]
+    source_map: Option<Arc<SourceMap>>,
+}
+
+impl Diagnostic {
+    pub fn error(message: impl Into<String>, span: Span) -> Self {
+        Self::new(Severity::Error, message, span)
+    }
+
+    pub fn warning(message: impl Into<String>, span: Span) -> Self {
+        Self::new(Severity::Warning, message, span)
+    }
+
+    fn new(severity: Severity, message: impl Into<String>, span: Span) -> Self {
+        Self {
+            severity,
+            code: None,
+            message: message.into(),
+            primary: Label::new(span, ""),
+            secondary: Vec::new(),
+            notes: Vec::new(),
+            suggestions: Vec::new(),
+            help: None,
+            source_map: None,
+        }
+    }
+
+    pub fn with_code(mut self, code: ErrorCode) -> Self {
+        self.code = Some(code);
+        self
+    }
+
+    pub fn with_primary_message(mut self, message: impl Into<String>) -> Self {
+        self.primary.message = message.into();
+        self
+    }
+
+    pub fn with_label(mut self, span: Span, message: impl Into<String>) -> Self {
+        self.secondary.push(Label::new(span, message));
+        self
+    }
+
+    pub fn with_note(mut self, note: impl Into<String>) -> Self {
+        self.notes.push(note.into());
+        self
+    }
+
+    pub fn with_suggestion(mut self, suggestion: Suggestion) -> Self {
+        self.suggestions.push(suggestion);
+        self
+    }
+
+    pub fn with_help(mut self, help: impl Into<String>) -> Self {
+        self.help = Some(help.into());
+        self
+    }
+
+    // The source used for spans that no `FileDatabase` passed to the renderer
+    // knows about, such as those of the plain `parse` entry points.
+    pub fn with_source(mut self, source_map: Arc<SourceMap>) -> Self {
+        self.source_map = Some(source_map);
+        self
+    }
+
+    pub fn source_map(&self) -> Option<&SourceMap> {
+        self.source_map.as_deref()
+    }
+}
+
+impl From<&LexError> for Diagnostic {
+    fn from(error: &LexError) -> Self {
+        Diagnostic {
+            code: Some(error.code),
+            suggestions: error.suggestions.clone(),
+            source_map: error.source_map.clone(),
+            ..Diagnostic::error(&error.message, error.span)
+        }
+    }
+}
+
+impl From<&ParseError> for Diagnostic {
+    fn from(error: &ParseError) -> Self {
+        // A single expected kind is already spelled out by the message.
+        let notes = match error.expected.as_slice() {
+            [] | [_] => Vec::new(),
+            expected => {
+                let kinds: Vec<_> = expected.iter().map(|kind| kind.describe()).collect();
+                vec![format!("expected one of {}", kinds.join(", "))]
+            }
+        };
+        Diagnostic {
+            code: Some(error.code),
+            notes,
+            suggestions: error.suggestions.clone(),
+            source_map: error.source_map.clone(),
+            ..Diagnostic::error(&error.message, error.span)
+        }
+    }
+}
+
+impl From<&RuntimeError> for Diagnostic {
+    fn from(error: &RuntimeError) -> Self {
+        Diagnostic {
+            secondary: error.labels.clone(),
+            code: Some(error.code),
+            source_map: error.source_map.clone(),
+            ..Diagnostic::error(&error.message, error.span)
+        }
+    }
+}
+
+impl From<&CoreError> for Diagnostic {
+    fn from(error: &CoreError) -> Self {
+        match error {
+            CoreError::Lex(error) => error.into(),
+            CoreError::Parse(error) => error.into(),
+            CoreError::Runtime(error) => error.into(),
+        }
+    }
+}
+
+impl From<&Warning> for Diagnostic {
+    fn from(warning: &Warning) -> Self {
+        Diagnostic {
+            source_map: warning.source_map.clone(),
+            ..Diagnostic::warning(&warning.message, warning.span).with_code(warning.code)
+        }
+    }
+}
+
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "\x1b[1m";
+const RED: &str = "\x1b[1;31m";
+const YELLOW: &str = "\x1b[1;33m";
+const BLUE: &str = "\x1b[1;34m";
+const TAB_WIDTH: usize = 4;
+
+// Turns diagnostics into text: the message, a `-->` location, the source
+// lines with `^` under the primary span and `-` under secondary ones, and
+// any notes. Colors are plain ANSI escapes.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Renderer {
+    color: bool,
+}
+
+impl Renderer {
+    pub fn plain() -> Self {
+        Self { color: false }
+    }
+
+    pub fn colored() -> Self {
+        Self { color: true }
+    }
+
+    pub fn render(&self, diagnostic: &Diagnostic) -> String {
+        self.render_with(diagnostic, |_| None)
+    }
+
+    // Resolves spans through `files` first, so labels may point into other
+    // files than the primary one.
+    pub fn render_in(&self, diagnostic: &Diagnostic, files: &FileDatabase) -> String {
+        self.render_with(diagnostic, |file| files.get(file).map(Arc::as_ref))
+    }
+
+    fn render_with<'a>(
+        &self,
+        diagnostic: &'a Diagnostic,
+        lookup: impl Fn(FileId) -> Option<&'a SourceMap>,
+    ) -> String {
+        let resolve = |span: Span| {
+            lookup(span.file).or_else(|| {
+                diagnostic
+                    .source_map()
+                    .filter(|_| span.file == diagnostic.primary.span.file)
+            })
+        };
+
+        // Labels grouped by the source they point into, primary source first.
+        let mut groups: Vec<(&SourceMap, Vec<(&Label, bool)>)> = Vec::new();
+        let labels = std::iter::once((&diagnostic.primary, true))
+            .chain(diagnostic.secondary.iter().map(|label| (label, false)));
+        for (label, primary) in labels {
+            let Some(source_map) = resolve(label.span) else {
+                continue;
+            };
+            match groups
+                .iter_mut()
+                .find(|(known, _)| std::ptr::eq(*known, source_map))
+            {
+                Some((_, group)) => group.push((label, primary)),
+                None => groups.push((source_map, vec![(label, primary)])),
+            }
+        }
+        let width = groups
+            .iter()
+            .flat_map(|(source_map, labels)| {
+                labels
+                    .iter()
+                    .map(|(label, _)| source_map.line_col(label.span.start).line)
+            })
+            .max()
+            .map_or(0, |line| line.to_string().len());
+        let pad = " ".repeat(width);
+        let severity = match diagnostic.severity {
+            Severity::Error => RED,
+            Severity::Warning => YELLOW,
+        };
+
+        let mut out = String::new();
+        let code = diagnostic
+            .code
+            .map(|code| format!("[{code}]"))
+            .unwrap_or_default();
+        let _ = writeln!(
+            out,
+            "{}{}{code}{}: {}{}{}",
+            self.paint(severity),
+            diagnostic.severity.as_str(),
+            self.paint(RESET),
+            self.paint(BOLD),
+            diagnostic.message,
+            self.paint(RESET),
+        );
+        for (index, (source_map, labels)) in groups.iter().enumerate() {
+            let arrow = if index == 0 { "-->" } else { ":::" };
+            let _ = writeln!(
+                out,
+                "{pad}{}{arrow}{} {}",
+                self.paint(BLUE),
+                self.paint(RESET),
+                source_map.location(labels[0].0.span),
+            );
+            let _ = writeln!(out, "{pad} {}|{}", self.paint(BLUE), self.paint(RESET));
+            let mut lines: Vec<usize> = labels
+                .iter()
+                .map(|(label, _)| source_map.line_col(label.span.start).line)
+                .collect();
+            lines.sort_unstable();
+            lines.dedup();
+            let mut previous = None;
+            for line in lines {
+                // A single skipped line is shown; longer gaps are elided.
+                match previous {
+                    Some(previous) if line == previous + 2 => {
+                        self.write_line(&mut out, source_map, previous + 1, width);
+                    }
+                    Some(previous) if line > previous + 2 => {
+                        let _ = writeln!(out, "{}...{}", self.paint(BLUE), self.paint(RESET));
+                    }
+                    _ => {}
+                }
+                previous = Some(line);
+                let text = self.write_line(&mut out, source_map, line, width);
+                let start = source_map.line_index().line_start(line).unwrap_or(0);
+                let mut marks: Vec<Mark> = labels
+                    .iter()
+                    .filter(|(label, _)| source_map.line_col(label.span.start).line == line)
+                    .map(|(label, primary)| {
+                        let from = label.span.start.clamp(start, start + text.len()) - start;
+                        let to = label.span.end.clamp(start + from, start + text.len()) - start;
+                        let (from, to) = (floor_boundary(text, from), floor_boundary(text, to));
+                        Mark {
+                            offset: expand_tabs(&text[..from]).chars().count(),
+                            length: expand_tabs(&text[from..to]).chars().count().max(1),
+                            symbol: if *primary { '^' } else { '-' },
+                            color: if *primary { severity } else { BLUE },
+                            message: &label.message,
+                        }
+                    })
+                    .collect();
+                marks.sort_by_key(|mark| mark.offset);
+                self.write_marks(&mut out, &pad, &marks);
+            }
+        }
+        let helps = diagnostic
+            .suggestions
+            .iter()
+            .map(|suggestion| &suggestion.message)
+            .chain(&diagnostic.help);
+        if !groups.is_empty() && (!diagnostic.notes.is_empty() || helps.clone().next().is_some()) {
+            let _ = writeln!(out, "{pad} {}|{}", self.paint(BLUE), self.paint(RESET));
+        }
+        let trailer = diagnostic
+            .notes
+            .iter()
+            .map(|note| ("note", note))
+            .chain(helps.map(|help| ("help", help)));
+        for (kind, text) in trailer {
+            let _ = writeln!(
+                out,
+                "{pad} {}={} {}{kind}{}: {text}",
+                self.paint(BLUE),
+                self.paint(RESET),
+                self.paint(BOLD),
+                self.paint(RESET),
+            );
+        }
+        out
+    }
+
+    // Underlines every label on one source line. The rightmost label's
+    // message follows its underline; the others hang below on `|` stems.
+    fn write_marks(&self, out: &mut String, pad: &str, marks: &[Mark]) {
+        let Some(last) = marks.last() else {
+            return;
+        };
+        let mut cells = Vec::new();
+        for mark in marks {
+            let underline = mark.symbol.to_string().repeat(mark.length);
+            place(&mut cells, mark.offset, &underline, mark.color);
+        }
+        if !last.message.is_empty() {
+            let end = last.offset + last.length;
+            place(&mut cells, end + 1, last.message, last.color);
+        }
+        self.write_cells(out, pad, &cells);
+
+        let hanging: Vec<&Mark> = marks[..marks.len() - 1]
+            .iter()
+            .filter(|mark| !mark.message.is_empty())
+            .collect();
+        if hanging.is_empty() {
+            return;
+        }
+        let stems = |count: usize| {
+            let mut cells = Vec::new();
+            for mark in &hanging[..count] {
+                place(&mut cells, mark.offset, "|", mark.color);
+            }
+            cells
+        };
+        self.write_cells(out, pad, &stems(hanging.len()));
+        for (index, mark) in hanging.iter().enumerate().rev() {
+            let mut cells = stems(index);
+            place(&mut cells, mark.offset, mark.message, mark.color);
+            self.write_cells(out, pad, &cells);
+        }
+    }
+
+    fn write_cells(&self, out: &mut String, pad: &str, cells: &[(char, &'static str)]) {
+        let _ = write!(out, "{pad} {}|{} ", self.paint(BLUE), self.paint(RESET));
+        let mut current = "";
+        for &(ch, color) in cells {
+            let color = if ch == ' ' { "" } else { color };
+            if color != current {
+                if !current.is_empty() {
+                    out.push_str(self.paint(RESET));
+                }
+                out.push_str(self.paint(color));
+                current = color;
+            }
+            out.push(ch);
+        }
+        if !current.is_empty() {
+            out.push_str(self.paint(RESET));
+        }
+        out.push('\n');
+    }
+
+    fn write_line<'s>(
+        &self,
+        out: &mut String,
+        source_map: &'s SourceMap,
+        line: usize,
+        width: usize,
+    ) -> &'s str {
+        let text = line_text(source_map, line);
+        let _ = writeln!(
+            out,
+            "{}{line:>width$} |{} {}",
+            self.paint(BLUE),
+            self.paint(RESET),
+            expand_tabs(text),
+        );
+        text
+    }
+
+    fn paint(&self, code: &'static str) -> &'static str {
+        if self.color { code } else { "" }
+    }
+}
+
+struct Mark<'a> {
+    offset: usize,
+    length: usize,
+    symbol: char,
+    color: &'static str,
+    message: &'a str,
+}
+
+fn place(cells: &mut Vec<(char, &'static str)>, column: usize, text: &str, color: &'static str) {
+    for (at, ch) in text.chars().enumerate() {
+        if cells.len() <= column + at {
+            cells.resize(column + at + 1, (' ', ""));
+        }
+        cells[column + at] = (ch, color);
+    }
+}
+
+fn line_text(source_map: &SourceMap, line: usize) -> &str {
+    let source = source_map.source();
+    let start = source_map.line_index().line_start(line).unwrap_or(0);
+    let end = source[start..]
+        .find(['\n', '\r'])
+        .map_or(source.len(), |at| start + at);
+    &source[start..end]
+}
+
+fn floor_boundary(text: &str, mut offset: usize) -> usize {
+    while !text.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    offset
+}
+
+fn expand_tabs(text: &str) -> String {
+    text.replace('\t', &" ".repeat(TAB_WIDTH))
+}
@@ -1,18 +1,56 @@
-use crate::ast::Span;
+use std::{fmt, sync::Arc};
+
+use crate::ast::{SourceMap, Span};
 use serde::Serialize;
 
-#[derive(Debug, thiserror::Error, Serialize)]
-#[error("lex error at byte range {span:?}: {message}")]
+use super::{ErrorCode, Suggestion};
+
+#[derive(Debug, Clone, Serialize)]
 pub struct LexError {
+    pub code: ErrorCode,
     pub message: String,
     pub span: Span,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub suggestions: Vec<Suggestion>,
+    #[serde(skip)]
+    pub(super) source_map: Option<Arc<SourceMap>>,
 }
 
 impl LexError {
     pub fn new(message: impl Into<String>, span: Span) -> Self {
         Self {
+            code: ErrorCode::Lex,
             message: message.into(),
             span,
+            suggestions: Vec::new(),
+            source_map: None,
         }
     }
+
+    pub fn with_code(mut self, code: ErrorCode) -> Self {
+        self.code = code;
+        self
+    }
+
+    pub fn with_suggestion(mut self, suggestion: Suggestion) -> Self {
+        self.suggestions.push(suggestion);
+        self
+    }
+
+    pub fn with_source(mut self, source_map: Arc<SourceMap>) -> Self {
+        self.source_map = Some(source_map);
+        self
+    }
+
+    pub fn source_map(&self) -> Option<&SourceMap> {
+        self.source_map.as_deref()
+    }
 }
+
+impl fmt::Display for LexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        super::write_located(f, "lex", &self.message, self.span, self.source_map())
+    }
+}
+
+impl std::error::Error for LexError {}
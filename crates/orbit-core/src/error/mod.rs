@@ -1,12 +1,27 @@
+use std::{fmt, sync::Arc};
+
+use serde::Serialize;
+
+use crate::ast::{SourceMap, Span};
+
+pub mod code;
+pub mod diagnostic;
 pub mod lex_error;
 pub mod parse_error;
 pub mod runtime_error;
+pub mod suggestion;
+pub mod warning;
 
+pub use self::code::ErrorCode;
+pub use self::diagnostic::{Diagnostic, Label, Renderer, Severity};
 pub use self::lex_error::LexError;
 pub use self::parse_error::ParseError;
 pub use self::runtime_error::RuntimeError;
+pub use self::suggestion::Suggestion;
+pub use self::warning::Warning;
 
-#[derive(Debug, thiserror::Error)]
+#[derive(Debug, Clone, thiserror::Error, Serialize)]
+#[serde(tag = "kind")]
 pub enum CoreError {
     #[error(transparent)]
     Lex(#[from] LexError),
@@ -15,3 +30,69 @@ pub enum CoreError {
     #[error(transparent)]
     Runtime(#[from] RuntimeError),
 }
+
+impl CoreError {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            CoreError::Lex(err) => err.code,
+            CoreError::Parse(err) => err.code,
+            CoreError::Runtime(err) => err.code,
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            CoreError::Lex(err) => &err.message,
+            CoreError::Parse(err) => &err.message,
+            CoreError::Runtime(err) => &err.message,
+        }
+    }
+
+    pub fn suggestions(&self) -> &[Suggestion] {
+        match self {
+            CoreError::Lex(err) => &err.suggestions,
+            CoreError::Parse(err) => &err.suggestions,
+            CoreError::Runtime(_) => &[],
+        }
+    }
+
+    pub fn span(&self) -> Span {
+        match self {
+            CoreError::Lex(err) => err.span,
+            CoreError::Parse(err) => err.span,
+            CoreError::Runtime(err) => err.span,
+        }
+    }
+
+    pub fn with_source(self, source_map: Arc<SourceMap>) -> Self {
+        match self {
+            CoreError::Lex(err) => CoreError::Lex(err.with_source(source_map)),
+            CoreError::Parse(err) => CoreError::Parse(err.with_source(source_map)),
+            CoreError::Runtime(err) => CoreError::Runtime(err.with_source(source_map)),
+        }
+    }
+}
+
+// Errors print their byte range until a source map is attached; from then on
+// they lead with `name:line:column` like compiler diagnostics. Sources
+// without a name print as `error at line:column`, like warnings.
+fn write_located(
+    f: &mut fmt::Formatter<'_>,
+    kind: &str,
+    message: &str,
+    span: Span,
+    source_map: Option<&SourceMap>,
+) -> fmt::Result {
+    match source_map {
+        Some(source_map) if source_map.name().is_empty() => {
+            let position = source_map.line_col(span.start);
+            write!(
+                f,
+                "{kind} error at {}:{}: {message}",
+                position.line, position.column
+            )
+        }
+        Some(source_map) => write!(f, "{}: {kind} error: {message}", source_map.location(span)),
+        None => write!(f, "{kind} error at byte range {span:?}: {message}"),
+    }
+}
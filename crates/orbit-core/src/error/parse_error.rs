@@ -1,18 +1,68 @@
-use crate::ast::Span;
+use std::{fmt, sync::Arc};
+
+use crate::{
+    ast::{SourceMap, Span},
+    cst::SyntaxKind,
+};
 use serde::Serialize;
 
-#[derive(Debug, thiserror::Error, Serialize)]
-#[error("parse error at byte range {span:?}: {message}")]
+use super::{ErrorCode, Suggestion};
+
+#[derive(Debug, Clone, Serialize)]
 pub struct ParseError {
+    pub code: ErrorCode,
     pub message: String,
     pub span: Span,
+    // The token kinds that would have been accepted where the error occurred.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub expected: Vec<SyntaxKind>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub suggestions: Vec<Suggestion>,
+    #[serde(skip)]
+    pub(super) source_map: Option<Arc<SourceMap>>,
 }
 
 impl ParseError {
     pub fn new(message: impl Into<String>, span: Span) -> Self {
         Self {
+            code: ErrorCode::Parse,
             message: message.into(),
             span,
+            expected: Vec::new(),
+            suggestions: Vec::new(),
+            source_map: None,
         }
     }
+
+    pub fn with_code(mut self, code: ErrorCode) -> Self {
+        self.code = code;
+        self
+    }
+
+    pub fn with_expected(mut self, expected: impl IntoIterator<Item = SyntaxKind>) -> Self {
+        self.expected.extend(expected);
+        self
+    }
+
+    pub fn with_suggestion(mut self, suggestion: Suggestion) -> Self {
+        self.suggestions.push(suggestion);
+        self
+    }
+
+    pub fn with_source(mut self, source_map: Arc<SourceMap>) -> Self {
+        self.source_map = Some(source_map);
+        self
+    }
+
+    pub fn source_map(&self) -> Option<&SourceMap> {
+        self.source_map.as_deref()
+    }
 }
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        super::write_located(f, "parse", &self.message, self.span, self.source_map())
+    }
+}
+
+impl std::error::Error for ParseError {}
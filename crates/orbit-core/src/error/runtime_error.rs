@@ -1,18 +1,58 @@
-use crate::ast::Span;
+use std::{fmt, sync::Arc};
+
+use crate::ast::{SourceMap, Span};
 use serde::Serialize;
 
-#[derive(Debug, thiserror::Error, Serialize)]
-#[error("runtime error at byte range {span:?}: {message}")]
+use super::{ErrorCode, Label};
+
+#[derive(Debug, Clone, Serialize)]
 pub struct RuntimeError {
+    pub code: ErrorCode,
     pub message: String,
     pub span: Span,
+    // Other places the error refers to, such as the first definition of a
+    // duplicated key.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub labels: Vec<Label>,
+    #[serde(skip)]
+    pub(super) source_map: Option<Arc<SourceMap>>,
 }
 
 impl RuntimeError {
     pub fn new(message: impl Into<String>, span: Span) -> Self {
         Self {
+            code: ErrorCode::Runtime,
             message: message.into(),
             span,
+            labels: Vec::new(),
+            source_map: None,
         }
     }
+
+    pub fn with_label(mut self, span: Span, message: impl Into<String>) -> Self {
+        self.labels.push(Label::new(span, message));
+        self
+    }
+
+    pub fn with_code(mut self, code: ErrorCode) -> Self {
+        self.code = code;
+        self
+    }
+
+    pub fn with_source(mut self, source_map: Arc<SourceMap>) -> Self {
+        self.source_map = Some(source_map);
+        self
+    }
+
+    pub fn source_map(&self) -> Option<&SourceMap> {
+        self.source_map.as_deref()
+    }
 }
+
+impl fmt::Display for RuntimeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        super::write_located(f, "runtime", &self.message, self.span, self.source_map())
+    }
+}
+
+impl std::error::Error for RuntimeError {}
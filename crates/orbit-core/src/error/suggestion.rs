@@ -0,0 +1,28 @@
+use serde::Serialize;
+
+use crate::ast::Span;
+
+// A machine-applicable fix: replace the text under `span` with `replacement`.
+// An empty span inserts, an empty replacement deletes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Suggestion {
+    pub message: String,
+    pub span: Span,
+    pub replacement: String,
+}
+
+impl Suggestion {
+    pub fn new(message: impl Into<String>, span: Span, replacement: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            span,
+            replacement: replacement.into(),
+        }
+    }
+
+    pub fn apply(&self, source: &str) -> String {
+        let mut fixed = source.to_string();
+        fixed.replace_range(self.span.start..self.span.end, &self.replacement);
+        fixed
+    }
+}
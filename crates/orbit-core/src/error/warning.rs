@@ -0,0 +1,87 @@
+use std::{fmt, sync::Arc};
+
+use crate::ast::{SourceMap, Span};
+use serde::Serialize;
+
+use super::{ErrorCode, RuntimeError};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Warning {
+    pub code: ErrorCode,
+    pub message: String,
+    pub span: Span,
+    #[serde(skip)]
+    pub(super) source_map: Option<Arc<SourceMap>>,
+}
+
+impl Warning {
+    pub fn new(message: impl Into<String>, span: Span) -> Self {
+        Self {
+            code: ErrorCode::Warning,
+            message: message.into(),
+            span,
+            source_map: None,
+        }
+    }
+
+    pub fn with_code(mut self, code: ErrorCode) -> Self {
+        self.code = code;
+        self
+    }
+
+    pub fn with_source(mut self, source_map: Arc<SourceMap>) -> Self {
+        self.source_map = Some(source_map);
+        self
+    }
+
+    pub fn source_map(&self) -> Option<&SourceMap> {
+        self.source_map.as_deref()
+    }
+
+    // Used when warnings are denied. Whichever stage emitted the warning, it
+    // is reported once a document is evaluated, hence a runtime error.
+    pub fn into_error(self) -> RuntimeError {
+        let error = RuntimeError::new(self.message, self.span).with_code(self.code);
+        match self.source_map {
+            Some(source_map) => error.with_source(source_map),
+            None => error,
+        }
+    }
+
+    // Denies every warning when only one error can be returned: the first
+    // becomes the error and the others its labels.
+    pub(crate) fn deny_all(warnings: &[Warning]) -> Option<RuntimeError> {
+        let (first, rest) = warnings.split_first()?;
+        Some(
+            rest.iter()
+                .fold(first.clone().into_error(), |error, warning| {
+                    error.with_label(warning.span, warning.message.clone())
+                }),
+        )
+    }
+}
+
+// Sources without a name print as `warning at line:column`.
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.source_map() {
+            Some(source_map) if source_map.name().is_empty() => {
+                let position = source_map.line_col(self.span.start);
+                write!(
+                    f,
+                    "warning at {}:{}: {}",
+                    position.line, position.column, self.message
+                )
+            }
+            Some(source_map) => write!(
+                f,
+                "{}: warning: {}",
+                source_map.location(self.span),
+                self.message
+            ),
+            None => write!(f, "warning at byte range {:?}: {}", self.span, self.message),
+        }
+    }
+}
+
+impl std::error::Error for Warning {}
@@ -0,0 +1,148 @@
+use std::sync::Arc;
+
+use crate::{
+    ast::{AstNode, FileId, SourceMap, Span},
+    engine::Engine,
+    error::CoreError,
+    limits::Limits,
+    parser::{ParseReport, Parser},
+    runtime::EvaluationOptions,
+    value::OrbitValue,
+};
+
+// Owns the sources of a configuration split over several files. Ids start at
+// 1, so the anonymous id carried by spans from the plain `parse` entry points
+// never resolves to a registered file.
+#[derive(Debug, Clone, Default)]
+pub struct FileDatabase {
+    files: Vec<Arc<SourceMap>>,
+}
+
+impl FileDatabase {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, path: impl Into<String>, source: impl Into<String>) -> FileId {
+        self.insert(Arc::new(SourceMap::new(path, source)))
+    }
+
+    pub(crate) fn insert(&mut self, source_map: Arc<SourceMap>) -> FileId {
+        self.files.push(source_map);
+        FileId::new(self.files.len() as u32)
+    }
+
+    pub fn get(&self, file: FileId) -> Option<&Arc<SourceMap>> {
+        (file.raw() as usize)
+            .checked_sub(1)
+            .and_then(|index| self.files.get(index))
+    }
+
+    pub fn path(&self, file: FileId) -> Option<&str> {
+        self.get(file).map(|source_map| source_map.name())
+    }
+
+    pub fn source(&self, file: FileId) -> Option<&str> {
+        self.get(file).map(|source_map| source_map.source())
+    }
+
+    pub fn files(&self) -> impl Iterator<Item = FileId> + '_ {
+        (1..=self.files.len()).map(|raw| FileId::new(raw as u32))
+    }
+
+    pub fn location(&self, span: Span) -> Option<String> {
+        self.get(span.file)
+            .map(|source_map| source_map.location(span))
+    }
+
+    pub fn snippet(&self, span: Span) -> Option<&str> {
+        self.get(span.file)
+            .map(|source_map| source_map.snippet(span))
+    }
+
+    // Attaches the source the error's span points into, so it displays as
+    // `path:line:column`.
+    pub fn locate(&self, error: CoreError) -> CoreError {
+        match self.get(error.span().file) {
+            Some(source_map) => error.with_source(source_map.clone()),
+            None => error,
+        }
+    }
+
+    // The methods below return `None` when `file` was not added to this
+    // database.
+    pub fn parse(&self, file: FileId) -> Option<Result<AstNode, CoreError>> {
+        let parser = self.parser(file, Limits::default())?;
+        Some(
+            parser
+                .and_then(|parser| Ok(parser.parse_document()?))
+                .map_err(|error| self.locate(error)),
+        )
+    }
+
+    pub fn parse_with_recovery(&self, file: FileId) -> Option<ParseReport> {
+        let mut report = Parser::from_source_with_recovery(self.source(file)?)
+            .in_file(file)
+            .parse_document_with_recovery();
+        report.errors = report
+            .errors
+            .into_iter()
+            .map(|error| self.locate(error))
+            .collect();
+        let source_map = self.get(file)?;
+        report.warnings = report
+            .warnings
+            .into_iter()
+            .map(|warning| warning.with_source(source_map.clone()))
+            .collect();
+        Some(report)
+    }
+
+    pub fn evaluate(
+        &self,
+        file: FileId,
+        options: &EvaluationOptions,
+    ) -> Option<Result<OrbitValue, CoreError>> {
+        let source = self.source(file)?;
+        let parser = self.parser(file, options.limits)?;
+        let engine = Engine::new().with_options(options.clone());
+        Some(
+            parser
+                .and_then(|parser| {
+                    let (document, warnings) = parser.parse_document_with_warnings()?;
+                    Ok(engine.evaluate_parsed(&document, &warnings, source)?.value)
+                })
+                .map_err(|error| self.locate(error)),
+        )
+    }
+
+    pub fn evaluate_documents(
+        &self,
+        file: FileId,
+        options: &EvaluationOptions,
+    ) -> Option<Result<Vec<OrbitValue>, CoreError>> {
+        let source = self.source(file)?;
+        let parser = self.parser(file, options.limits)?;
+        let engine = Engine::new().with_options(options.clone());
+        Some(
+            parser
+                .and_then(|parser| {
+                    let (documents, warnings) = parser.parse_documents_with_warnings()?;
+                    engine.evaluate_parsed_documents(&documents, warnings, source)
+                })
+                .map_err(|error| self.locate(error)),
+        )
+    }
+
+    fn parser(&self, file: FileId, limits: Limits) -> Option<Result<Parser<'_>, CoreError>> {
+        let parser = Parser::from_source_with_limits(self.source(file)?, limits);
+        Some(
+            parser
+                .map(|parser| parser.in_file(file))
+                .map_err(|mut error| {
+                    error.span.file = file;
+                    error.into()
+                }),
+        )
+    }
+}
@@ -1,5 +1,9 @@
 mod scanner;
 pub mod token;
+pub mod unicode;
 
-pub use self::scanner::{Lexer, lex};
+pub use self::scanner::{
+    BASE64_TAG, DOC_COMMENT_PREFIX, DOCUMENT_SEPARATOR, HEX_TAG, Lexer, is_identifier, lex,
+    string_value,
+};
 pub use self::token::{Token, TokenKind};
@@ -1,77 +1,303 @@
 use crate::{
     ast::Span,
-    error::LexError,
-    lexer::token::{Token, TokenKind},
+    error::{ErrorCode, LexError, Suggestion, Warning},
+    lexer::{
+        token::{Token, TokenKind},
+        unicode::{ConfusableDetector, is_unicode_ident_part, is_unicode_ident_start},
+    },
+    limits::Limits,
+    value::bytes::{from_base64, from_hex},
+    version::LanguageVersion,
 };
 
+pub const DOC_COMMENT_PREFIX: &str = "##";
+pub const DOCUMENT_SEPARATOR: &str = "---";
+pub const BASE64_TAG: &str = "b64";
+pub const HEX_TAG: &str = "hex";
+
 pub struct Lexer<'a> {
     source: &'a str,
     offset: usize,
+    version: LanguageVersion,
+    limits: Limits,
+    warnings: Vec<Warning>,
+    confusables: Option<ConfusableDetector>,
+    // For each open delimiter, whether its items are separated by commas
+    // (lists, arguments, object literals) rather than line breaks (blocks).
+    // Only used to suggest a fix for a stray `;`.
+    delimiters: Vec<bool>,
+    value_position: bool,
 }
 
 impl<'a> Lexer<'a> {
     pub fn new(source: &'a str) -> Self {
-        Self { source, offset: 0 }
+        Self::with_version(source, LanguageVersion::LATEST)
+    }
+
+    pub fn with_version(source: &'a str, version: LanguageVersion) -> Self {
+        // Confusable detection is only needed once non-ASCII identifiers are
+        // possible, which keeps plain ASCII sources on the fast path.
+        let confusables = (version >= LanguageVersion::V0_3 && !source.is_ascii())
+            .then(ConfusableDetector::default);
+        Self {
+            source,
+            offset: 0,
+            version,
+            limits: Limits::default(),
+            warnings: Vec::new(),
+            confusables,
+            delimiters: Vec::new(),
+            value_position: false,
+        }
+    }
+
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    pub fn tokenize(self) -> Result<Vec<Token<'a>>, LexError> {
+        Ok(self.tokenize_with_warnings()?.0)
+    }
+
+    pub fn tokenize_with_warnings(self) -> Result<(Vec<Token<'a>>, Vec<Warning>), LexError> {
+        let (tokens, errors, warnings) = self.tokenize_with_errors();
+        match errors.into_iter().next() {
+            Some(error) => Err(error),
+            None => Ok((tokens, warnings)),
+        }
     }
 
-    pub fn tokenize(mut self) -> Result<Vec<Token<'a>>, LexError> {
+    // Only stops early at the token limit: the text behind each lexical error
+    // becomes a single `TokenKind::Error` token so a recovering parser can
+    // step over it.
+    pub fn tokenize_with_errors(mut self) -> (Vec<Token<'a>>, Vec<LexError>, Vec<Warning>) {
         let mut tokens = Vec::new();
+        let mut errors = Vec::new();
         while let Some(ch) = self.peek_char() {
-            match ch {
-                ' ' | '\t' | '\x0c' => {
-                    self.advance_char();
+            let start = self.offset;
+            if tokens.len() >= self.limits.max_tokens {
+                errors.push(
+                    LexError::new(
+                        format!(
+                            "source exceeds the limit of {} tokens",
+                            self.limits.max_tokens
+                        ),
+                        Span::new(start, start),
+                    )
+                    .with_code(ErrorCode::TooManyTokens),
+                );
+                break;
+            }
+            match self.lex_token(ch) {
+                Ok(Some(token)) => {
+                    self.track_delimiters(&token.kind);
+                    tokens.push(token);
                 }
-                '\r' => {
-                    let start = self.offset;
-                    self.advance_char();
-                    if self.peek_char() == Some('\n') {
-                        self.advance_char();
-                    }
+                Ok(None) => {}
+                Err(error) => {
                     let span = Span::new(start, self.offset);
                     tokens.push(Token {
-                        kind: TokenKind::Newline,
+                        kind: TokenKind::Error(&self.source[start..self.offset]),
                         span,
                     });
+                    errors.push(error);
                 }
-                '\n' => {
-                    let start = self.offset;
+            }
+        }
+
+        tokens.push(Token {
+            kind: TokenKind::Eof,
+            span: Span::new(self.offset, self.offset),
+        });
+
+        (tokens, errors, self.warnings)
+    }
+
+    // Lexes the token starting with `ch`; whitespace yields `None`. On error
+    // the offending text has been consumed.
+    fn lex_token(&mut self, ch: char) -> Result<Option<Token<'a>>, LexError> {
+        let token = match ch {
+            ' ' | '\t' | '\x0c' => {
+                self.advance_char();
+                return Ok(None);
+            }
+            '\r' => {
+                let start = self.offset;
+                self.advance_char();
+                if self.peek_char() == Some('\n') {
                     self.advance_char();
-                    let span = Span::new(start, self.offset);
-                    tokens.push(Token {
-                        kind: TokenKind::Newline,
-                        span,
-                    });
                 }
-                '#' => tokens.push(self.lex_comment()?),
-                '{' => tokens.push(self.symbol(TokenKind::LBrace)),
-                '}' => tokens.push(self.symbol(TokenKind::RBrace)),
-                '[' => tokens.push(self.symbol(TokenKind::LBracket)),
-                ']' => tokens.push(self.symbol(TokenKind::RBracket)),
-                ':' => tokens.push(self.symbol(TokenKind::Colon)),
-                ',' => tokens.push(self.symbol(TokenKind::Comma)),
-                '"' => tokens.push(self.lex_string()?),
-                c if is_ident_start(c) => tokens.push(self.lex_ident_or_bool()?),
-                c if c.is_ascii_digit()
-                    || (c == '-' && self.peek_next_char().is_some_and(|n| n.is_ascii_digit())) =>
+                Token {
+                    kind: TokenKind::Newline,
+                    span: Span::new(start, self.offset),
+                }
+            }
+            '\n' => self.symbol(TokenKind::Newline),
+            '#' => self.lex_comment()?,
+            '{' => self.symbol(TokenKind::LBrace),
+            '}' => self.symbol(TokenKind::RBrace),
+            '[' => self.symbol(TokenKind::LBracket),
+            ']' => self.symbol(TokenKind::RBracket),
+            '(' => self.symbol(TokenKind::LParen),
+            ')' => self.symbol(TokenKind::RParen),
+            '@' => self.symbol(TokenKind::At),
+            ':' => self.symbol(TokenKind::Colon),
+            ',' => self.symbol(TokenKind::Comma),
+            '.' if self.source[self.offset..].starts_with("...") => {
+                self.fixed("...", TokenKind::Ellipsis)
+            }
+            '-' if self.at_document_separator() => {
+                self.fixed(DOCUMENT_SEPARATOR, TokenKind::DocumentSeparator)
+            }
+            '"' => self.lex_string()?,
+            '\'' if self.single_quoted_len().is_some() => return Err(self.lex_single_quoted()),
+            c if self.starts_ident(c) => self.lex_ident_or_bool()?,
+            c if c.is_ascii_digit()
+                || (c == '-' && self.peek_next_char().is_some_and(|n| n.is_ascii_digit())) =>
+            {
+                self.lex_number()?
+            }
+            _ => {
+                // A run of unexpected characters is reported once.
+                let start = self.offset;
+                self.advance_char();
+                while self
+                    .peek_char()
+                    .is_some_and(|next| !self.starts_token(next))
                 {
-                    tokens.push(self.lex_number()?)
+                    self.advance_char();
                 }
-                other => {
-                    let span = Span::new(self.offset, self.offset + other.len_utf8());
-                    return Err(LexError::new(
-                        format!("unexpected character '{other}'"),
+                let span = Span::new(start, self.offset);
+                let text = &self.source[start..self.offset];
+                let message = if text.chars().count() == 1 {
+                    format!("unexpected character '{text}'")
+                } else {
+                    format!("unexpected characters '{text}'")
+                };
+                let error = LexError::new(message, span).with_code(ErrorCode::UnexpectedCharacter);
+                return Err(match text {
+                    "=" => error.with_suggestion(Suggestion::new(
+                        "use ':' to separate a key from its value",
                         span,
-                    ));
-                }
+                        ":",
+                    )),
+                    ";" => error.with_suggestion(self.semicolon_fix(span)),
+                    _ => error,
+                });
             }
+        };
+        Ok(Some(token))
+    }
+
+    fn starts_ident(&self, ch: char) -> bool {
+        is_ident_start(ch) || (self.unicode_identifiers() && is_unicode_ident_start(ch))
+    }
+
+    fn starts_token(&self, ch: char) -> bool {
+        matches!(
+            ch,
+            ' ' | '\t'
+                | '\x0c'
+                | '\r'
+                | '\n'
+                | '#'
+                | '{'
+                | '}'
+                | '['
+                | ']'
+                | '('
+                | ')'
+                | '@'
+                | ':'
+                | ','
+                | '.'
+                | '-'
+                | '"'
+        ) || ch.is_ascii_digit()
+            || self.starts_ident(ch)
+    }
+
+    fn unicode_identifiers(&self) -> bool {
+        self.version >= LanguageVersion::V0_3
+    }
+
+    fn track_delimiters(&mut self, kind: &TokenKind<'a>) {
+        match kind {
+            TokenKind::LBracket | TokenKind::LParen => self.delimiters.push(true),
+            TokenKind::LBrace => self.delimiters.push(self.value_position),
+            TokenKind::RBracket | TokenKind::RParen | TokenKind::RBrace => {
+                self.delimiters.pop();
+            }
+            _ => {}
         }
+        if !matches!(
+            kind,
+            TokenKind::Newline | TokenKind::Comment(_) | TokenKind::DocComment(_)
+        ) {
+            self.value_position = matches!(
+                kind,
+                TokenKind::Colon | TokenKind::Comma | TokenKind::LBracket | TokenKind::LParen
+            );
+        }
+    }
 
-        tokens.push(Token {
-            kind: TokenKind::Eof,
-            span: Span::new(self.offset, self.offset),
-        });
+    // Inside a list or object literal `;` stands for `,`; elsewhere it ends
+    // an entry, which a line break already does.
+    fn semicolon_fix(&self, span: Span) -> Suggestion {
+        if self.delimiters.last() == Some(&true) {
+            return Suggestion::new("use ',' to separate items", span, ",");
+        }
+        let rest = self.source[span.end..]
+            .split(['\n', '\r'])
+            .next()
+            .unwrap_or_default()
+            .trim_start();
+        if rest.is_empty() || rest.starts_with('#') {
+            return Suggestion::new("remove the ';'; entries end at the line break", span, "");
+        }
+        let line_start = self.source[..span.start]
+            .rfind(['\n', '\r'])
+            .map_or(0, |at| at + 1);
+        let indent: String = self.source[line_start..]
+            .chars()
+            .take_while(|ch| matches!(ch, ' ' | '\t'))
+            .collect();
+        let end = self.source.len()
+            - self.source[span.end..]
+                .trim_start_matches([' ', '\t'])
+                .len();
+        Suggestion::new(
+            "put the next entry on its own line",
+            Span::new(span.start, end),
+            format!("\n{indent}"),
+        )
+    }
+
+    // Length of the text between a `'` at the current offset and its closing
+    // quote on the same line.
+    fn single_quoted_len(&self) -> Option<usize> {
+        let rest = &self.source[self.offset + 1..];
+        rest.find(['\'', '\n', '\r'])
+            .filter(|&at| rest[at..].starts_with('\''))
+    }
 
-        Ok(tokens)
+    fn lex_single_quoted(&mut self) -> LexError {
+        let start = self.offset;
+        let len = self.single_quoted_len().unwrap_or_default();
+        let contents = &self.source[start + 1..start + 1 + len];
+        self.offset = start + len + 2;
+        let span = Span::new(start, self.offset);
+        LexError::new("strings are written with double quotes", span)
+            .with_code(ErrorCode::SingleQuotedString)
+            .with_suggestion(Suggestion::new(
+                "use double quotes",
+                span,
+                format!(
+                    "\"{}\"",
+                    contents.replace('\\', "\\\\").replace('"', "\\\"")
+                ),
+            ))
     }
 
     fn symbol(&mut self, kind: TokenKind<'a>) -> Token<'a> {
@@ -81,6 +307,32 @@ impl<'a> Lexer<'a> {
         Token { kind, span }
     }
 
+    // A separator is a line of its own: `---` at the start of a line,
+    // followed by nothing but whitespace.
+    fn at_document_separator(&self) -> bool {
+        let line_start = self.source[..self.offset]
+            .chars()
+            .next_back()
+            .is_none_or(|ch| ch == '\n' || ch == '\r');
+        let Some(rest) = self.source[self.offset..].strip_prefix(DOCUMENT_SEPARATOR) else {
+            return false;
+        };
+        line_start
+            && rest
+                .chars()
+                .take_while(|ch| *ch != '\n' && *ch != '\r')
+                .all(|ch| matches!(ch, ' ' | '\t' | '\x0c'))
+    }
+
+    fn fixed(&mut self, lexeme: &str, kind: TokenKind<'a>) -> Token<'a> {
+        let start = self.offset;
+        self.offset += lexeme.len();
+        Token {
+            kind,
+            span: Span::new(start, self.offset),
+        }
+    }
+
     fn lex_comment(&mut self) -> Result<Token<'a>, LexError> {
         let start = self.offset;
         self.advance_char();
@@ -92,59 +344,110 @@ impl<'a> Lexer<'a> {
         }
         let span = Span::new(start, self.offset);
         let lexeme = &self.source[start..self.offset];
+        // Before 0.2 a `##` line is an ordinary comment.
+        let kind =
+            if lexeme.starts_with(DOC_COMMENT_PREFIX) && self.version >= LanguageVersion::V0_2 {
+                TokenKind::DocComment(lexeme)
+            } else {
+                TokenKind::Comment(lexeme)
+            };
+        Ok(Token { kind, span })
+    }
+
+    fn lex_string(&mut self) -> Result<Token<'a>, LexError> {
+        let start = self.offset;
+        let value = self.lex_string_contents(start)?;
+        Ok(Token {
+            kind: TokenKind::String(value),
+            span: Span::new(start, self.offset),
+        })
+    }
+
+    fn lex_bytes(&mut self, start: usize, encoding: &str) -> Result<Token<'a>, LexError> {
+        let contents = self.lex_string_contents(start)?;
+        let span = Span::new(start, self.offset);
+        let bytes = match encoding {
+            BASE64_TAG => from_base64(&contents),
+            _ => from_hex(&contents),
+        }
+        .ok_or_else(|| {
+            LexError::new(format!("invalid {encoding} bytes literal"), span)
+                .with_code(ErrorCode::InvalidBytes)
+        })?;
         Ok(Token {
-            kind: TokenKind::Comment(lexeme),
+            kind: TokenKind::Bytes(bytes),
             span,
         })
     }
 
-    fn lex_string(&mut self) -> Result<Token<'a>, LexError> {
-        let start = self.offset;
+    // Consumes a quoted string starting at the opening quote and returns its
+    // unescaped contents. `start` is only used for error spans.
+    fn lex_string_contents(&mut self, start: usize) -> Result<String, LexError> {
         self.advance_char();
         let mut value = String::new();
+        let mut too_long = false;
         while let Some(ch) = self.advance_char() {
+            // Past the limit the text is only scanned for the closing quote,
+            // so an oversized literal never holds more than the limit.
+            if value.len() > self.limits.max_string_length {
+                too_long = true;
+                value.clear();
+            }
             match ch {
-                '"' => {
-                    let span = Span::new(start, self.offset);
-                    return Ok(Token {
-                        kind: TokenKind::String(value),
-                        span,
-                    });
+                '"' if too_long || value.len() > self.limits.max_string_length => {
+                    return Err(LexError::new(
+                        format!(
+                            "string literal exceeds the limit of {} bytes",
+                            self.limits.max_string_length
+                        ),
+                        Span::new(start, self.offset),
+                    )
+                    .with_code(ErrorCode::StringTooLong));
                 }
+                '"' => return Ok(value),
                 '\\' => {
                     let escaped = self.advance_char().ok_or_else(|| {
                         LexError::new("unterminated string escape", Span::new(start, self.offset))
+                            .with_code(ErrorCode::UnterminatedEscape)
                     })?;
-                    value.push(match escaped {
-                        '"' => '"',
-                        '\\' => '\\',
-                        'n' => '\n',
-                        'r' => '\r',
-                        't' => '\t',
-                        other => other,
+                    value.push(match unescape(escaped) {
+                        Some(ch) => ch,
+                        None => {
+                            self.warnings.push(
+                                Warning::new(
+                                    format!("unknown escape sequence '\\{escaped}'"),
+                                    Span::new(self.offset - 1 - escaped.len_utf8(), self.offset),
+                                )
+                                .with_code(ErrorCode::UnknownEscape),
+                            );
+                            escaped
+                        }
                     });
                 }
+                // The line break is left for the next token.
                 '\n' | '\r' => {
+                    self.offset -= 1;
                     return Err(LexError::new(
                         "unterminated string literal",
                         Span::new(start, self.offset),
-                    ));
+                    )
+                    .with_code(ErrorCode::UnterminatedString));
                 }
                 other => value.push(other),
             }
         }
 
-        Err(LexError::new(
-            "unterminated string literal",
-            Span::new(start, self.offset),
-        ))
+        Err(
+            LexError::new("unterminated string literal", Span::new(start, self.offset))
+                .with_code(ErrorCode::UnterminatedString),
+        )
     }
 
     fn lex_ident_or_bool(&mut self) -> Result<Token<'a>, LexError> {
         let start = self.offset;
         self.advance_char();
         while let Some(ch) = self.peek_char() {
-            if is_ident_part(ch) {
+            if is_ident_part(ch) || (self.unicode_identifiers() && is_unicode_ident_part(ch)) {
                 self.advance_char();
             } else {
                 break;
@@ -152,6 +455,19 @@ impl<'a> Lexer<'a> {
         }
         let span = Span::new(start, self.offset);
         let lexeme = &self.source[start..self.offset];
+        if self.peek_char() == Some('"') {
+            match lexeme {
+                BASE64_TAG | HEX_TAG => return self.lex_bytes(start, lexeme),
+                "true" | "false" => {}
+                tag => {
+                    let value = self.lex_string_contents(start)?;
+                    return Ok(Token {
+                        kind: TokenKind::Tagged(tag, value),
+                        span: Span::new(start, self.offset),
+                    });
+                }
+            }
+        }
         match lexeme {
             "true" => Ok(Token {
                 kind: TokenKind::Bool(true),
@@ -161,10 +477,15 @@ impl<'a> Lexer<'a> {
                 kind: TokenKind::Bool(false),
                 span,
             }),
-            _ => Ok(Token {
-                kind: TokenKind::Ident(lexeme),
-                span,
-            }),
+            _ => {
+                if let Some(confusables) = &mut self.confusables {
+                    confusables.check(lexeme, span, &mut self.warnings);
+                }
+                Ok(Token {
+                    kind: TokenKind::Ident(lexeme),
+                    span,
+                })
+            }
         }
     }
 
@@ -223,6 +544,49 @@ fn is_ident_part(ch: char) -> bool {
     is_ident_start(ch) || ch.is_ascii_digit() || ch == '.' || ch == '-'
 }
 
+pub fn is_identifier(value: &str, version: LanguageVersion) -> bool {
+    let unicode = version >= LanguageVersion::V0_3;
+    let mut chars = value.chars();
+    chars
+        .next()
+        .is_some_and(|ch| is_ident_start(ch) || (unicode && is_unicode_ident_start(ch)))
+        && chars.all(|ch| is_ident_part(ch) || (unicode && is_unicode_ident_part(ch)))
+        && !matches!(value, "true" | "false")
+}
+
+// The character `\\ch` stands for, if it is a known escape.
+fn unescape(ch: char) -> Option<char> {
+    match ch {
+        '"' => Some('"'),
+        '\\' => Some('\\'),
+        'n' => Some('\n'),
+        'r' => Some('\r'),
+        't' => Some('\t'),
+        _ => None,
+    }
+}
+
+// Reads back the contents of a quoted literal the lexer accepted, such as
+// `"a\\tb"` or the quoted part of a tagged literal. An unknown escape stands
+// for the escaped character, as it does when lexing.
+pub fn string_value(quoted: &str) -> String {
+    let contents = quoted.strip_prefix('"').unwrap_or(quoted);
+    let contents = contents.strip_suffix('"').unwrap_or(contents);
+    let mut value = String::with_capacity(contents.len());
+    let mut chars = contents.chars();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\\' => value.extend(
+                chars
+                    .next()
+                    .map(|escaped| unescape(escaped).unwrap_or(escaped)),
+            ),
+            other => value.push(other),
+        }
+    }
+    value
+}
+
 pub fn lex<'a>(source: &'a str) -> Result<Vec<Token<'a>>, LexError> {
     Lexer::new(source).tokenize()
 }
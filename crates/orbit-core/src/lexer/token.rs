@@ -8,7 +8,10 @@ pub struct Token<'a> {
 
 impl<'a> Token<'a> {
     pub fn is_trivia(&self) -> bool {
-        matches!(self.kind, TokenKind::Newline | TokenKind::Comment(_))
+        matches!(
+            self.kind,
+            TokenKind::Newline | TokenKind::Comment(_) | TokenKind::DocComment(_)
+        )
     }
 }
 
@@ -16,16 +19,26 @@ impl<'a> Token<'a> {
 pub enum TokenKind<'a> {
     Ident(&'a str),
     String(String),
+    Bytes(Vec<u8>),
+    Tagged(&'a str, String),
     Number(&'a str),
     Bool(bool),
     LBrace,
     RBrace,
     LBracket,
     RBracket,
+    LParen,
+    RParen,
+    At,
     Colon,
     Comma,
+    Ellipsis,
+    DocumentSeparator,
     Newline,
     Comment(&'a str),
+    DocComment(&'a str),
+    // Text the lexer could not read; the error itself is reported separately.
+    Error(&'a str),
     Eof,
 }
 
@@ -34,16 +47,25 @@ impl<'a> TokenKind<'a> {
         match self {
             TokenKind::Ident(_) => "identifier",
             TokenKind::String(_) => "string",
+            TokenKind::Bytes(_) => "bytes",
+            TokenKind::Tagged(..) => "tagged literal",
             TokenKind::Number(_) => "number",
             TokenKind::Bool(_) => "boolean",
             TokenKind::LBrace => "{",
             TokenKind::RBrace => "}",
             TokenKind::LBracket => "[",
             TokenKind::RBracket => "]",
+            TokenKind::LParen => "(",
+            TokenKind::RParen => ")",
+            TokenKind::At => "@",
             TokenKind::Colon => ":",
             TokenKind::Comma => ",",
+            TokenKind::Ellipsis => "...",
+            TokenKind::DocumentSeparator => "---",
             TokenKind::Newline => "newline",
             TokenKind::Comment(_) => "comment",
+            TokenKind::DocComment(_) => "doc comment",
+            TokenKind::Error(_) => "invalid token",
             TokenKind::Eof => "end of file",
         }
     }
@@ -0,0 +1,70 @@
+use std::collections::HashMap;
+
+use unicode_normalization::{UnicodeNormalization, is_nfc};
+use unicode_security::{MixedScript, skeleton};
+
+use crate::{
+    ast::Span,
+    error::{ErrorCode, Warning},
+};
+
+pub fn is_unicode_ident_start(ch: char) -> bool {
+    unicode_ident::is_xid_start(ch)
+}
+
+pub fn is_unicode_ident_part(ch: char) -> bool {
+    unicode_ident::is_xid_continue(ch) || ch == '.' || ch == '-'
+}
+
+// Identifiers, quoted keys, and tag names are compared in NFC so that
+// precomposed and decomposed spellings of the same name refer to the same
+// key or tag.
+pub fn normalize_identifier(ident: &str) -> String {
+    if ident.is_ascii() || is_nfc(ident) {
+        ident.to_string()
+    } else {
+        ident.nfc().collect()
+    }
+}
+
+// Tracks the confusable skeleton (UTS #39) of every identifier in a source so
+// that two distinct names which render alike are reported.
+#[derive(Default)]
+pub struct ConfusableDetector {
+    skeletons: HashMap<String, String>,
+}
+
+impl ConfusableDetector {
+    pub fn check(&mut self, ident: &str, span: Span, warnings: &mut Vec<Warning>) {
+        let normalized = normalize_identifier(ident);
+        if !normalized.is_ascii() && !normalized.as_str().is_single_script() {
+            warnings.push(
+                Warning::new(
+                    format!("identifier '{normalized}' mixes characters from several scripts"),
+                    span,
+                )
+                .with_code(ErrorCode::MixedScripts),
+            );
+        }
+        let key: String = skeleton(&normalized).collect();
+        match self.skeletons.get(&key) {
+            // Pure ASCII names that share a skeleton (`rn` and `m`) are left
+            // alone; only collisions involving non-ASCII spellings are suspicious.
+            Some(existing)
+                if *existing != normalized && !(existing.is_ascii() && normalized.is_ascii()) =>
+            {
+                warnings.push(
+                    Warning::new(
+                        format!("identifier '{normalized}' is confusable with '{existing}'"),
+                        span,
+                    )
+                    .with_code(ErrorCode::ConfusableIdentifier),
+                );
+            }
+            Some(_) => {}
+            None => {
+                self.skeletons.insert(key, normalized);
+            }
+        }
+    }
+}
@@ -1,37 +1,108 @@
 pub mod ast;
+pub mod cst;
+pub mod engine;
 pub mod error;
+pub mod files;
 pub mod lexer;
+pub mod limits;
 pub mod parser;
 pub mod runtime;
 pub mod serializer;
 pub mod value;
+pub mod version;
 
-pub use crate::ast::{AstNode, ObjectEntry, Span, ValueNode};
-pub use crate::error::{CoreError, LexError, ParseError, RuntimeError};
+pub use crate::ast::{
+    Annotation, AstNode, DocComment, FileId, LineCol, LineIndex, ObjectEntry, SourceMap, Span,
+    ValueNode,
+};
+pub use crate::cst::{SyntaxElement, SyntaxKind, SyntaxNode, SyntaxToken, SyntaxTree};
+pub use crate::engine::{Engine, EngineError};
+pub use crate::error::{
+    CoreError, Diagnostic, ErrorCode, Label, LexError, ParseError, Renderer, RuntimeError,
+    Severity, Suggestion, Warning,
+};
+pub use crate::files::FileDatabase;
 pub use crate::lexer::{Token, TokenKind};
-pub use crate::parser::{ParseReport, Parser};
-pub use crate::runtime::Evaluator;
-pub use crate::value::{OrbitNumber, OrbitValue};
+pub use crate::limits::Limits;
+pub use crate::parser::{ParseReport, Parser, TextEdit};
+pub use crate::runtime::{Evaluation, EvaluationOptions, EvaluationReport, Evaluator, TagRegistry};
+pub use crate::value::{OrbitNumber, OrbitValue, Secret};
+pub use crate::version::LanguageVersion;
 
 pub fn parse(source: &str) -> Result<AstNode, CoreError> {
-    let parser = Parser::from_source(source)?;
-    let ast = parser.parse_document()?;
-    Ok(ast)
+    Engine::new().parse(source)
+}
+
+pub fn parse_documents(source: &str) -> Result<Vec<AstNode>, CoreError> {
+    Engine::new().parse_documents(source)
+}
+
+// Parses `source` after `edit` was applied to the text `previous` came from,
+// sharing the subtrees of `previous` the edit did not touch.
+pub fn reparse(previous: &SyntaxTree, source: &str, edit: &TextEdit) -> SyntaxTree {
+    parser::reparse(previous, source, edit)
+}
+
+pub fn parse_cst(source: &str) -> SyntaxTree {
+    Engine::new().parse_cst(source)
 }
 
 pub fn evaluate(source: &str) -> Result<OrbitValue, CoreError> {
-    let ast = parse(source)?;
-    let value = Evaluator::evaluate(&ast)?;
-    Ok(value)
+    Engine::new().evaluate(source)
+}
+
+pub fn evaluate_with_options(
+    source: &str,
+    options: &EvaluationOptions,
+) -> Result<OrbitValue, CoreError> {
+    Engine::new().with_options(options.clone()).evaluate(source)
+}
+
+pub fn evaluate_detailed(
+    source: &str,
+    options: &EvaluationOptions,
+) -> Result<Evaluation, CoreError> {
+    Engine::new()
+        .with_options(options.clone())
+        .evaluate_detailed(source)
+}
+
+pub fn evaluate_documents(
+    source: &str,
+    options: &EvaluationOptions,
+) -> Result<Vec<OrbitValue>, CoreError> {
+    Engine::new()
+        .with_options(options.clone())
+        .evaluate_documents(source)
 }
 
 pub fn evaluate_ast(ast: &AstNode) -> Result<OrbitValue, RuntimeError> {
-    Evaluator::evaluate(ast)
+    Engine::new().evaluate_ast(ast)
+}
+
+pub fn evaluate_report(
+    source: &str,
+    options: &EvaluationOptions,
+) -> Result<EvaluationReport, CoreError> {
+    Engine::new()
+        .with_options(options.clone())
+        .evaluate_report(source)
+}
+
+pub fn evaluate_ast_report(ast: &AstNode, options: &EvaluationOptions) -> EvaluationReport {
+    Engine::new()
+        .with_options(options.clone())
+        .evaluate_ast_report(ast)
+}
+
+pub fn parse_with_recovery(source: &str) -> ParseReport {
+    Engine::new().parse_with_recovery(source)
 }
 
-pub fn parse_with_recovery(source: &str) -> Result<ParseReport, CoreError> {
-    let parser = Parser::from_source(source)?;
-    Ok(parser.parse_document_with_recovery())
+pub fn evaluate_parse_report(report: ParseReport, options: &EvaluationOptions) -> EvaluationReport {
+    Engine::new()
+        .with_options(options.clone())
+        .evaluate_parse_report(report)
 }
 
 #[cfg(target_arch = "wasm32")]
@@ -0,0 +1,53 @@
+// Bounds on the work a single source may cause, so that hosts evaluating
+// untrusted documents get an error instead of a stack overflow or an
+// unbounded allocation. The defaults are far above what hand-written
+// configuration needs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Limits {
+    // Lists, object literals, blocks, and profiles nested inside each other.
+    pub max_depth: usize,
+    pub max_tokens: usize,
+    // In bytes, after escapes are resolved.
+    pub max_string_length: usize,
+    // Values produced by evaluation, counting everything a spread copies.
+    pub max_output_nodes: usize,
+}
+
+impl Limits {
+    pub const DEFAULT: Limits = Limits {
+        max_depth: 128,
+        max_tokens: 1_000_000,
+        max_string_length: 1 << 20,
+        max_output_nodes: 1_000_000,
+    };
+
+    pub fn new() -> Self {
+        Self::DEFAULT
+    }
+
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    pub fn with_max_tokens(mut self, max_tokens: usize) -> Self {
+        self.max_tokens = max_tokens;
+        self
+    }
+
+    pub fn with_max_string_length(mut self, max_string_length: usize) -> Self {
+        self.max_string_length = max_string_length;
+        self
+    }
+
+    pub fn with_max_output_nodes(mut self, max_output_nodes: usize) -> Self {
+        self.max_output_nodes = max_output_nodes;
+        self
+    }
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
@@ -1,210 +1,867 @@
+use std::{collections::HashMap, sync::Arc};
+
 use crate::{
-    ast::{AstNode, ObjectEntry, Span, ValueNode},
-    error::{LexError, ParseError},
-    lexer::{Token, TokenKind, lex},
+    ast::{FileId, Span},
+    cst::{SyntaxKind, SyntaxNode, SyntaxTree, builder, green::GreenNode, lower},
+    error::{CoreError, ErrorCode, LexError, ParseError, Suggestion, Warning},
+    lexer::{Lexer, Token, TokenKind, unicode::normalize_identifier},
+    limits::Limits,
     value::number::OrbitNumber,
+    version::LanguageVersion,
 };
 
 use serde::Serialize;
 
-use super::grammar::{Document, document};
+use super::{
+    event::Event,
+    grammar::{Document, PROFILE_KEYWORD},
+};
+
+// Token kinds that can begin a value, reported when one is missing.
+const VALUE_STARTS: [SyntaxKind; 7] = [
+    SyntaxKind::String,
+    SyntaxKind::Bytes,
+    SyntaxKind::Tagged,
+    SyntaxKind::Number,
+    SyntaxKind::Bool,
+    SyntaxKind::LBracket,
+    SyntaxKind::LBrace,
+];
 
 #[derive(Debug, Serialize)]
 pub struct ParseReport {
     pub document: Document,
-    pub errors: Vec<ParseError>,
+    pub errors: Vec<CoreError>,
+    pub warnings: Vec<Warning>,
 }
 
+// The position of a node's `Start` event; its kind is set once the node is
+// complete, when it is known.
+struct Marker(usize);
+
 pub struct Parser<'a> {
+    source: &'a str,
     tokens: Vec<Token<'a>>,
     index: usize,
     last_consumed: Option<usize>,
+    version: LanguageVersion,
+    warnings: Vec<Warning>,
+    // Set by `parse_document_with_recovery`: errors are collected here and
+    // parsing continues with error nodes instead of bailing out.
+    recovering: bool,
+    errors: Vec<ParseError>,
+    lex_errors: Vec<LexError>,
+    open_lists: usize,
+    limits: Limits,
+    // Lists, objects, and block bodies currently being parsed; bounded by
+    // `limits.max_depth` so deeply nested input cannot exhaust the stack.
+    depth: usize,
+    events: Vec<Event>,
+    // Entries an unterminated block handed back to its parent, by start
+    // index, with the index after them and their errors. The parent parses
+    // them again, which would take exponential time for nested blocks.
+    handed_back: HashMap<usize, (Vec<Event>, usize, Vec<ParseError>)>,
+    file: FileId,
 }
 
 impl<'a> Parser<'a> {
     pub fn from_source(source: &'a str) -> Result<Self, LexError> {
-        let tokens = lex(source)?;
-        Ok(Self {
+        Self::from_source_with_limits(source, Limits::default())
+    }
+
+    pub fn from_source_with_limits(source: &'a str, limits: Limits) -> Result<Self, LexError> {
+        let version = LanguageVersion::from_pragma(source)
+            .map_err(|message| pragma_error(source, message))?
+            .unwrap_or(LanguageVersion::LATEST);
+        Self::lex(source, version, limits)
+    }
+
+    pub fn with_version(source: &'a str, version: LanguageVersion) -> Result<Self, LexError> {
+        Self::lex(source, version, Limits::default())
+    }
+
+    pub(crate) fn with_version_and_limits(
+        source: &'a str,
+        version: LanguageVersion,
+        limits: Limits,
+    ) -> Result<Self, LexError> {
+        Self::lex(source, version, limits)
+    }
+
+    fn lex(source: &'a str, version: LanguageVersion, limits: Limits) -> Result<Self, LexError> {
+        let (tokens, warnings) = Lexer::with_version(source, version)
+            .with_limits(limits)
+            .tokenize_with_warnings()?;
+        Ok(Self::new(
+            source,
+            tokens,
+            version,
+            limits,
+            warnings,
+            Vec::new(),
+        ))
+    }
+
+    pub fn from_source_with_recovery(source: &'a str) -> Self {
+        Self::from_source_with_recovery_and_limits(source, Limits::default())
+    }
+
+    // Never fails: lexical errors become error tokens and an unreadable
+    // pragma falls back to the latest version. Both are reported by
+    // `parse_document_with_recovery` along with the parse errors.
+    pub fn from_source_with_recovery_and_limits(source: &'a str, limits: Limits) -> Self {
+        let mut lex_errors = Vec::new();
+        let version = match LanguageVersion::from_pragma(source) {
+            Ok(version) => version.unwrap_or(LanguageVersion::LATEST),
+            Err(message) => {
+                lex_errors.push(pragma_error(source, message));
+                LanguageVersion::LATEST
+            }
+        };
+        let (tokens, errors, warnings) = Lexer::with_version(source, version)
+            .with_limits(limits)
+            .tokenize_with_errors();
+        lex_errors.extend(errors);
+        Self::new(source, tokens, version, limits, warnings, lex_errors)
+    }
+
+    fn new(
+        source: &'a str,
+        tokens: Vec<Token<'a>>,
+        version: LanguageVersion,
+        limits: Limits,
+        warnings: Vec<Warning>,
+        lex_errors: Vec<LexError>,
+    ) -> Self {
+        Self {
+            source,
             tokens,
             index: 0,
             last_consumed: None,
-        })
+            version,
+            warnings,
+            recovering: false,
+            errors: Vec::new(),
+            lex_errors,
+            open_lists: 0,
+            limits,
+            depth: 0,
+            events: Vec::new(),
+            handed_back: HashMap::new(),
+            file: FileId::default(),
+        }
+    }
+
+    // Tags every span the parser hands out (tokens, nodes, errors, and
+    // warnings) with `file`.
+    pub fn in_file(mut self, file: FileId) -> Self {
+        self.file = file;
+        let spans = self
+            .tokens
+            .iter_mut()
+            .map(|token| &mut token.span)
+            .chain(self.lex_errors.iter_mut().map(|error| &mut error.span))
+            .chain(self.warnings.iter_mut().map(|warning| &mut warning.span));
+        for span in spans {
+            span.file = file;
+        }
+        self
+    }
+
+    pub fn version(&self) -> LanguageVersion {
+        self.version
+    }
+
+    pub fn warnings(&self) -> &[Warning] {
+        &self.warnings
     }
 
-    pub fn parse_document(mut self) -> Result<Document, ParseError> {
-        let span_start = self.tokens.first().map(|t| t.span.start).unwrap_or(0);
-        let mut body = Vec::new();
+    pub fn parse_document(self) -> Result<Document, ParseError> {
+        Ok(self.parse_document_with_warnings()?.0)
+    }
+
+    // `warnings` before parsing only has the lexer's; these also include the
+    // ones raised while parsing, such as number literals that lose precision.
+    pub fn parse_document_with_warnings(mut self) -> Result<(Document, Vec<Warning>), ParseError> {
+        let document = self.start();
         while !self.is_at_end() {
-            body.push(self.parse_entry_or_block()?);
+            if self.at_separator() {
+                return Err(separator_error(self.peek().span));
+            }
+            self.parse_entry_or_block()?;
         }
-        let span_end = self.tokens.last().map(|t| t.span.end).unwrap_or(span_start);
-        Ok(document(body, Span::new(span_start, span_end)))
+        self.complete(document, SyntaxKind::Document);
+        let root = self.build();
+        let document = lower::document(SyntaxNode::new(&root, 0), self.file);
+        Ok((document, self.warnings))
+    }
+
+    // A leading and a trailing separator are both optional, so `---` may
+    // frame every document the way generated YAML streams often do.
+    pub fn parse_documents(self) -> Result<Vec<Document>, ParseError> {
+        Ok(self.parse_documents_with_warnings()?.0)
+    }
+
+    pub fn parse_documents_with_warnings(
+        mut self,
+    ) -> Result<(Vec<Document>, Vec<Warning>), ParseError> {
+        self.parse_document_list()?;
+        let root = self.build();
+        let documents = lower::documents(SyntaxNode::new(&root, 0), self.file);
+        Ok((documents, self.warnings))
     }
 
     pub fn parse_document_with_recovery(mut self) -> ParseReport {
-        let span_start = self.tokens.first().map(|t| t.span.start).unwrap_or(0);
-        let mut body = Vec::new();
-        let mut errors = Vec::new();
+        self.recovering = true;
+        let document = self.start();
         while !self.is_at_end() {
-            match self.parse_entry_or_block() {
-                Ok(node) => body.push(node),
-                Err(err) => {
-                    errors.push(err);
-                    self.synchronize();
-                }
+            if self.at_separator() {
+                let span = self.advance().span;
+                self.errors.push(separator_error(span));
+                continue;
             }
+            self.parse_statement();
         }
-        let span_end = self.tokens.last().map(|t| t.span.end).unwrap_or(span_start);
+        self.complete(document, SyntaxKind::Document);
+        let root = self.build();
+        let document = lower::document(SyntaxNode::new(&root, 0), self.file);
+        let (errors, warnings) = self.into_diagnostics();
         ParseReport {
-            document: document(body, Span::new(span_start, span_end)),
+            document,
             errors,
+            warnings,
+        }
+    }
+
+    // The lossless tree of every document. Like `parse_document_with_recovery`
+    // it never fails; what does not parse becomes an error node.
+    pub(crate) fn parse_syntax_tree(mut self) -> SyntaxTree {
+        self.recovering = true;
+        if let Err(error) = self.parse_document_list() {
+            self.errors.push(error);
+        }
+        self.into_syntax_tree()
+    }
+
+    // Parses a stretch of a larger source that an incremental reparse
+    // replaces, as statements of a body nested `depth` levels deep. Only a
+    // clean parse is of use there, so any error gives `None`.
+    pub(crate) fn parse_region(mut self, depth: usize) -> Option<SyntaxTree> {
+        self.depth = depth;
+        let document = self.start();
+        while !self.is_at_end() {
+            if self.at_separator() {
+                return None;
+            }
+            self.parse_entry_or_block().ok()?;
+        }
+        self.complete(document, SyntaxKind::Document);
+        Some(self.into_syntax_tree())
+    }
+
+    fn into_syntax_tree(self) -> SyntaxTree {
+        let root = self.build();
+        let (version, limits) = (self.version, self.limits);
+        // The end of input does not count against the token limit.
+        let tokens = self.tokens.len().saturating_sub(1);
+        let unicode_identifiers = self
+            .tokens
+            .iter()
+            .any(|token| matches!(token.kind, TokenKind::Ident(name) if !name.is_ascii()));
+        let (errors, warnings) = self.into_diagnostics();
+        SyntaxTree::new(
+            root,
+            version,
+            limits,
+            errors,
+            warnings,
+            tokens,
+            unicode_identifiers,
+        )
+    }
+
+    fn parse_document_list(&mut self) -> Result<(), ParseError> {
+        self.consume_separator()?;
+        loop {
+            let document = self.start();
+            while !self.is_at_end() && !self.at_separator() {
+                if self.recovering {
+                    self.parse_statement();
+                } else {
+                    self.parse_entry_or_block()?;
+                }
+            }
+            self.complete(document, SyntaxKind::Document);
+            if !self.consume_separator()? || self.is_at_end() {
+                return Ok(());
+            }
         }
     }
 
-    fn parse_entry_or_block(&mut self) -> Result<AstNode, ParseError> {
+    fn build(&self) -> Arc<GreenNode> {
+        builder::build(self.source, &self.tokens, &self.events)
+    }
+
+    fn into_diagnostics(self) -> (Vec<CoreError>, Vec<Warning>) {
+        let mut errors: Vec<CoreError> = self.lex_errors.into_iter().map(Into::into).collect();
+        errors.extend(self.errors.into_iter().map(CoreError::from));
+        errors.sort_by_key(|error| error.span().start);
+        let mut warnings = self.warnings;
+        warnings.sort_by_key(|warning| warning.span.start);
+        (errors, warnings)
+    }
+
+    // Only used while recovering: an entry that cannot be parsed at all
+    // becomes an error node holding the skipped tokens.
+    fn parse_statement(&mut self) {
+        if let Some((events, end, errors)) = self.handed_back.remove(&self.index) {
+            self.errors.extend(errors);
+            self.events.extend(events);
+            self.rewind(end);
+            return;
+        }
+        if self.current_is(|kind| matches!(kind, TokenKind::Error(_))) {
+            let node = self.start();
+            self.skip_lex_errors();
+            self.complete(node, SyntaxKind::Error);
+            return;
+        }
+        let (start, checkpoint) = (self.index, self.events.len());
+        if let Err(err) = self.parse_entry_or_block() {
+            self.errors.push(err);
+            self.error_node(start, checkpoint, |parser| parser.synchronize(start));
+        }
+    }
+
+    fn parse_entry_or_block(&mut self) -> Result<(), ParseError> {
+        let docs = self.take_docs();
+        let statement = self.start_at(docs);
+        let annotations = self.parse_annotations()?;
         let (ident, ident_span) = self.consume_ident("expected identifier")?;
-        if self.matches(|kind| matches!(kind, TokenKind::LBrace)) {
-            self.parse_block(ident, ident_span)
+        if ident == PROFILE_KEYWORD && self.current_is(|kind| matches!(kind, TokenKind::Ident(_))) {
+            self.require_version(LanguageVersion::V0_2, "profile sections", ident_span)?;
+            if let Some(span) = annotations {
+                self.recover(ParseError::new(
+                    "annotations are not allowed on profile sections",
+                    span,
+                ))?;
+            }
+            self.parse_profile(statement, ident_span)
+        } else if self.matches(|kind| matches!(kind, TokenKind::LBrace)) {
+            self.nested(ident_span, |parser| parser.parse_block_body(ident_span))?;
+            self.complete(statement, SyntaxKind::Block);
+            Ok(())
         } else {
-            self.expect(
-                |kind| matches!(kind, TokenKind::Colon),
+            self.parse_separated_value(
                 "expected ':' after identifier",
+                &[SyntaxKind::Colon, SyntaxKind::LBrace],
             )?;
-            let value = self.parse_value()?;
-            let span = ident_span.union(value.span());
-            Ok(AstNode::Entry {
-                key: ident,
-                value,
-                span,
-            })
+            self.complete(statement, SyntaxKind::Entry);
+            Ok(())
         }
     }
 
-    fn parse_block(&mut self, name: String, name_span: Span) -> Result<AstNode, ParseError> {
-        let mut body = Vec::new();
+    fn parse_profile(&mut self, statement: Marker, keyword_span: Span) -> Result<(), ParseError> {
+        self.consume_ident("expected profile name")?;
+        self.expect(
+            SyntaxKind::LBrace,
+            ErrorCode::Parse,
+            "expected '{' after profile name",
+        )?;
+        let top_level = self.depth == 0;
+        self.nested(keyword_span, |parser| parser.parse_block_body(keyword_span))?;
+        if top_level {
+            self.complete(statement, SyntaxKind::Profile);
+            return Ok(());
+        }
+        let end = self.previous().map_or(keyword_span, |token| token.span);
+        self.recover(
+            ParseError::new(
+                "profile sections are only allowed at the top level",
+                keyword_span.union(end),
+            )
+            .with_code(ErrorCode::MisplacedProfile),
+        )?;
+        self.complete(statement, SyntaxKind::Error);
+        Ok(())
+    }
+
+    fn parse_block_body(&mut self, open_span: Span) -> Result<(), ParseError> {
+        let indent = self
+            .last_consumed
+            .map_or(0, |index| self.line_indent(index));
+        // Where each statement starts: its token, error count, and event.
+        let mut starts: Vec<(usize, usize, usize)> = Vec::new();
         while !self.current_is(|kind| matches!(kind, TokenKind::RBrace)) {
-            if self.is_at_end() {
-                return Err(ParseError::new("unterminated block", name_span));
+            // A recovering parse of several documents ends the block at the
+            // next separator rather than reading the next document into it.
+            if self.is_at_end() || (self.recovering && self.at_separator()) {
+                self.recover(
+                    ParseError::new("unterminated block", open_span)
+                        .with_code(ErrorCode::UnterminatedBlock)
+                        .with_expected([SyntaxKind::RBrace]),
+                )?;
+                // The block most likely ends before the first entry that is not
+                // indented past it; hand that entry back to the enclosing scope.
+                if let Some(position) = starts.iter().position(|&(index, ..)| {
+                    self.starts_line(index) && self.line_indent(index) <= indent
+                }) {
+                    let (index, ..) = starts[position];
+                    let error = self.errors.pop();
+                    let mut end = self.index;
+                    for &(start, errors, events) in starts[position..].iter().rev() {
+                        let errors = self.errors.split_off(errors);
+                        let events = self.events.split_off(events);
+                        self.handed_back.insert(start, (events, end, errors));
+                        end = start;
+                    }
+                    self.errors.extend(error);
+                    self.rewind(index);
+                }
+                return Ok(());
+            }
+            starts.push((self.index, self.errors.len(), self.events.len()));
+            if self.recovering {
+                self.parse_statement();
+            } else {
+                self.parse_entry_or_block()?;
             }
-            body.push(self.parse_entry_or_block()?);
         }
-        let closing = self.expect(
-            |kind| matches!(kind, TokenKind::RBrace),
+        self.expect(
+            SyntaxKind::RBrace,
+            ErrorCode::UnclosedDelimiter,
             "expected '}' to close block",
         )?;
-        let span = name_span.union(closing.span);
-        Ok(AstNode::Block { name, body, span })
-    }
-
-    fn parse_value(&mut self) -> Result<ValueNode, ParseError> {
-        match self.peek().kind.clone() {
-            TokenKind::String(value) => {
-                let token = self.advance().clone();
-                Ok(ValueNode::String {
-                    value,
-                    span: token.span,
-                })
+        Ok(())
+    }
+
+    fn parse_value(&mut self) -> Result<(), ParseError> {
+        if self.current_is(|kind| matches!(kind, TokenKind::Error(_))) {
+            let node = self.start();
+            self.skip_lex_errors();
+            self.complete(node, SyntaxKind::Error);
+            return Ok(());
+        }
+        let span = self.peek().span;
+        match self.peek().kind {
+            TokenKind::String(_) | TokenKind::Bool(_) => {
+                self.literal();
+                Ok(())
             }
-            TokenKind::Number(raw) => {
-                let token = self.advance().clone();
-                let number = parse_number_literal(raw, token.span)?;
-                Ok(ValueNode::Number {
-                    value: number,
-                    span: token.span,
-                })
+            TokenKind::Bytes(_) => {
+                self.literal();
+                self.require_version(LanguageVersion::V0_2, "bytes literals", span)
             }
-            TokenKind::Bool(value) => {
-                let token = self.advance().clone();
-                Ok(ValueNode::Bool {
-                    value,
-                    span: token.span,
-                })
+            TokenKind::Tagged(..) => {
+                self.literal();
+                self.require_version(LanguageVersion::V0_2, "tagged literals", span)
             }
-            TokenKind::LBracket => self.parse_list(),
-            TokenKind::LBrace => self.parse_object(),
-            other => Err(ParseError::new(
-                format!("unexpected token {} while parsing value", other.describe()),
-                self.peek().span,
-            )),
+            TokenKind::Number(raw) => match parse_number_literal(raw, span) {
+                Ok(number) => {
+                    self.warnings.extend(number_warning(raw, number, span));
+                    self.literal();
+                    Ok(())
+                }
+                Err(err) => {
+                    self.recover(err)?;
+                    let node = self.start();
+                    self.advance();
+                    self.complete(node, SyntaxKind::Error);
+                    Ok(())
+                }
+            },
+            TokenKind::LBracket => self.nested(span, |parser| {
+                parser.open_lists += 1;
+                let list = parser.parse_list();
+                parser.open_lists -= 1;
+                list
+            }),
+            TokenKind::LBrace => self.nested(span, Self::parse_object),
+            _ => self.stray_token(span),
+        }
+    }
+
+    fn literal(&mut self) {
+        let node = self.start();
+        self.advance();
+        self.complete(node, SyntaxKind::Literal);
+    }
+
+    fn stray_token(&mut self, span: Span) -> Result<(), ParseError> {
+        let other = &self.tokens[self.index].kind;
+        // A stray token is swallowed unless it could close an enclosing
+        // construct or start the next line's entry.
+        let leave = matches!(
+            other,
+            TokenKind::RBrace | TokenKind::Comma | TokenKind::DocumentSeparator | TokenKind::Eof
+        ) || (matches!(other, TokenKind::RBracket) && self.open_lists > 0);
+        let error = ParseError::new(
+            format!("unexpected token {} while parsing value", other.describe()),
+            span,
+        )
+        .with_code(ErrorCode::UnexpectedToken)
+        .with_expected(VALUE_STARTS);
+        self.recover(error)?;
+        let node = self.start();
+        if !leave && !self.starts_line(self.index) {
+            self.advance();
         }
+        self.complete(node, SyntaxKind::Error);
+        Ok(())
     }
 
-    fn parse_list(&mut self) -> Result<ValueNode, ParseError> {
-        let open = self.advance().clone();
-        let mut items = Vec::new();
-        if self.matches(|kind| matches!(kind, TokenKind::RBracket)) {
-            let close_span = self.previous().unwrap().span;
-            return Ok(ValueNode::List {
-                items,
-                span: open.span.union(close_span),
-            });
+    // After a key: the separator, then the value. A recovering parse reports
+    // a missing separator once and reads the value anyway when one follows.
+    fn parse_separated_value(
+        &mut self,
+        message: &str,
+        expected: &[SyntaxKind],
+    ) -> Result<(), ParseError> {
+        if self.matches(|kind| matches!(kind, TokenKind::Colon)) {
+            return self.parse_value();
+        }
+        // `key = value` was already reported by the lexer with a fix.
+        if self.matches(|kind| matches!(kind, TokenKind::Error("="))) {
+            return self.parse_value();
+        }
+        let span = self.peek().span;
+        let mut error = ParseError::new(message, span)
+            .with_code(ErrorCode::MissingColon)
+            .with_expected(expected.iter().copied());
+        if self.starts_value() {
+            error = error.with_suggestion(self.insert_after_previous("add ':' after the key", ":"));
         }
+        self.recover(error)?;
+        if self.starts_value() {
+            self.parse_value()
+        } else {
+            self.missing_value();
+            Ok(())
+        }
+    }
+
+    // An empty error node, which sits right after the previous token.
+    fn missing_value(&mut self) {
+        let node = self.start();
+        self.complete(node, SyntaxKind::Error);
+    }
+
+    fn parse_list(&mut self) -> Result<(), ParseError> {
+        let list = self.start();
+        self.advance();
+        if !self.matches(|kind| matches!(kind, TokenKind::RBracket)) {
+            self.parse_list_items()?;
+        }
+        self.complete(list, SyntaxKind::List);
+        Ok(())
+    }
+
+    fn parse_list_items(&mut self) -> Result<(), ParseError> {
         loop {
-            let value = self.parse_value()?;
-            items.push(value);
-            if self.matches(|kind| matches!(kind, TokenKind::Comma)) {
+            if self.recovering && self.ends_container() {
+                self.unclosed_list();
+                return Ok(());
+            }
+            if self.current_is(|kind| matches!(kind, TokenKind::Ellipsis)) {
+                self.parse_spread()?;
+            } else {
+                self.parse_value()?;
+            }
+            if self.matches_separator() {
                 if self.matches(|kind| matches!(kind, TokenKind::RBracket)) {
-                    let close_span = self.previous().unwrap().span;
-                    let span = open.span.union(close_span);
-                    return Ok(ValueNode::List { items, span });
+                    return Ok(());
                 }
                 continue;
             }
-            let close = self.expect(
-                |kind| matches!(kind, TokenKind::RBracket),
+            if !self.current_is(|kind| matches!(kind, TokenKind::RBracket)) {
+                if self.starts_value()
+                    || self.current_is(|kind| matches!(kind, TokenKind::Ellipsis))
+                {
+                    let error = self.missing_comma(SyntaxKind::RBracket);
+                    self.recover(error)?;
+                    continue;
+                }
+                if self.recovering {
+                    self.unclosed_list();
+                    return Ok(());
+                }
+            }
+            self.expect(
+                SyntaxKind::RBracket,
+                ErrorCode::UnclosedDelimiter,
                 "expected ']' to close list",
             )?;
-            let span = open.span.union(close.span);
-            return Ok(ValueNode::List { items, span });
+            return Ok(());
         }
     }
 
-    fn parse_object(&mut self) -> Result<ValueNode, ParseError> {
-        let open = self.advance().clone();
-        let mut entries = Vec::new();
-        if self.matches(|kind| matches!(kind, TokenKind::RBrace)) {
-            let close_span = self.previous().unwrap().span;
-            return Ok(ValueNode::Object {
-                entries,
-                span: open.span.union(close_span),
-            });
+    fn unclosed_list(&mut self) {
+        let span = self.peek().span;
+        self.errors.push(
+            ParseError::new("expected ']' to close list", span)
+                .with_code(ErrorCode::UnclosedDelimiter)
+                .with_expected([SyntaxKind::Comma, SyntaxKind::RBracket]),
+        );
+    }
+
+    fn parse_object(&mut self) -> Result<(), ParseError> {
+        let open_index = self.index;
+        let object = self.start();
+        self.advance();
+        if !self.matches(|kind| matches!(kind, TokenKind::RBrace)) {
+            self.parse_object_entries(open_index)?;
         }
+        self.complete(object, SyntaxKind::Object);
+        Ok(())
+    }
+
+    fn parse_object_entries(&mut self, open_index: usize) -> Result<(), ParseError> {
         loop {
-            let (key, key_span) = self.consume_ident("expected key inside object")?;
-            self.expect(
-                |kind| matches!(kind, TokenKind::Colon),
-                "expected ':' after key in object",
-            )?;
-            let value = self.parse_value()?;
-            let entry_span = key_span.union(value.span());
-            entries.push(ObjectEntry {
-                key,
-                value,
-                span: entry_span,
-            });
-
-            if self.matches(|kind| matches!(kind, TokenKind::Comma)) {
+            if self.current_is(|kind| matches!(kind, TokenKind::Ellipsis)) {
+                let docs = self.take_docs();
+                let entry = self.start_at(docs);
+                self.parse_spread()?;
+                self.complete(entry, SyntaxKind::ObjectEntry);
+            } else if self.recovering {
+                let (start, checkpoint) = (self.index, self.events.len());
+                if let Err(err) = self.parse_object_entry() {
+                    self.errors.push(err);
+                    self.error_node(start, checkpoint, Self::skip_object_entry);
+                }
+            } else {
+                self.parse_object_entry()?;
+            }
+
+            if self.matches_separator() {
                 if self.matches(|kind| matches!(kind, TokenKind::RBrace)) {
-                    let close_span = self.previous().unwrap().span;
-                    let span = open.span.union(close_span);
-                    return Ok(ValueNode::Object { entries, span });
+                    return Ok(());
                 }
                 continue;
             }
 
-            let close = self.expect(
-                |kind| matches!(kind, TokenKind::RBrace),
+            // Without the comma a key on a deeper-indented line is most likely
+            // the next entry, while one at or left of the line that opened the
+            // object (or a `}` left of it) belongs to the enclosing scope.
+            if self.recovering {
+                let indent = self.line_indent(open_index);
+                let next = self.index;
+                let outdented = self.starts_line(next) && self.line_indent(next) <= indent;
+                let closes = self.current_is(|kind| matches!(kind, TokenKind::RBrace))
+                    && !(self.starts_line(next) && self.line_indent(next) < indent);
+                if !closes {
+                    if self.starts_key() && !outdented {
+                        let error = self.missing_comma(SyntaxKind::RBrace);
+                        self.errors.push(error);
+                        continue;
+                    }
+                    let span = self.peek().span;
+                    self.errors.push(
+                        ParseError::new("expected '}' to close object", span)
+                            .with_code(ErrorCode::UnclosedDelimiter)
+                            .with_expected([SyntaxKind::Comma, SyntaxKind::RBrace]),
+                    );
+                    return Ok(());
+                }
+            }
+
+            if self.starts_key() {
+                return Err(self.missing_comma(SyntaxKind::RBrace));
+            }
+            self.expect(
+                SyntaxKind::RBrace,
+                ErrorCode::UnclosedDelimiter,
                 "expected '}' to close object",
             )?;
-            let span = open.span.union(close.span);
-            return Ok(ValueNode::Object { entries, span });
+            return Ok(());
+        }
+    }
+
+    fn parse_object_entry(&mut self) -> Result<(), ParseError> {
+        let docs = self.take_docs();
+        let entry = self.start_at(docs);
+        self.parse_annotations()?;
+        if self.current_is(|kind| matches!(kind, TokenKind::String(_))) {
+            let key_span = self.advance().span;
+            self.require_version(LanguageVersion::V0_2, "quoted object keys", key_span)?;
+        } else {
+            self.consume_ident("expected key inside object")?;
+        }
+        self.parse_separated_value("expected ':' after key in object", &[SyntaxKind::Colon])?;
+        self.complete(entry, SyntaxKind::ObjectEntry);
+        Ok(())
+    }
+
+    // Returns the span of the first annotation, if there is one.
+    fn parse_annotations(&mut self) -> Result<Option<Span>, ParseError> {
+        let mut first = None;
+        while self.current_is(|kind| matches!(kind, TokenKind::At)) {
+            let annotation = self.start();
+            let at_span = self.advance().span;
+            self.require_version(LanguageVersion::V0_2, "annotations", at_span)?;
+            self.consume_ident("expected annotation name after '@'")?;
+            if self.matches(|kind| matches!(kind, TokenKind::LParen)) {
+                while !self.current_is(|kind| matches!(kind, TokenKind::RParen)) {
+                    self.parse_value()?;
+                    if !self.matches(|kind| matches!(kind, TokenKind::Comma)) {
+                        break;
+                    }
+                }
+                self.expect(
+                    SyntaxKind::RParen,
+                    ErrorCode::UnclosedDelimiter,
+                    "expected ')' to close annotation arguments",
+                )?;
+            }
+            let end = self.previous().map_or(at_span, |token| token.span);
+            first.get_or_insert(at_span.union(end));
+            self.complete(annotation, SyntaxKind::Annotation);
+        }
+        Ok(first)
+    }
+
+    fn parse_spread(&mut self) -> Result<(), ParseError> {
+        let spread = self.start();
+        let ellipsis_span = self.advance().span;
+        self.require_version(LanguageVersion::V0_2, "spread syntax", ellipsis_span)?;
+        self.consume_ident("expected identifier after '...'")?;
+        self.complete(spread, SyntaxKind::Spread);
+        Ok(())
+    }
+
+    // Finds the `##` lines directly above the current token and returns the
+    // first one; plain `#` lines between them are skipped. A comment that
+    // trails other tokens on its line belongs to nothing and stops the scan.
+    fn take_docs(&mut self) -> Option<usize> {
+        self.skip_trivia();
+        let mut first = None;
+        let mut index = self.index;
+        while index > 0 {
+            index -= 1;
+            match self.tokens[index].kind {
+                TokenKind::Newline => {}
+                TokenKind::Comment(_) | TokenKind::DocComment(_) => {
+                    if !self.starts_line(index) {
+                        break;
+                    }
+                    if matches!(self.tokens[index].kind, TokenKind::DocComment(_)) {
+                        first = Some(index);
+                    }
+                }
+                _ => break,
+            }
+        }
+        first
+    }
+
+    fn consume_separator(&mut self) -> Result<bool, ParseError> {
+        if !self.at_separator() {
+            return Ok(false);
+        }
+        let span = self.advance().span;
+        self.require_version(LanguageVersion::V0_2, "document separators", span)?;
+        Ok(true)
+    }
+
+    fn at_separator(&mut self) -> bool {
+        self.current_is(|kind| matches!(kind, TokenKind::DocumentSeparator))
+    }
+
+    fn start(&mut self) -> Marker {
+        self.start_at(None)
+    }
+
+    fn start_at(&mut self, first: Option<usize>) -> Marker {
+        self.events.push(Event::Start {
+            kind: SyntaxKind::Error,
+            first,
+        });
+        Marker(self.events.len() - 1)
+    }
+
+    fn complete(&mut self, marker: Marker, kind: SyntaxKind) {
+        if let Event::Start { kind: slot, .. } = &mut self.events[marker.0] {
+            *slot = kind;
+        }
+        self.events.push(Event::Finish);
+    }
+
+    // Replaces the events since `checkpoint` with one flat error node that
+    // holds the tokens consumed since `start` and whatever `skip` steps over.
+    fn error_node(&mut self, start: usize, checkpoint: usize, skip: impl FnOnce(&mut Self)) {
+        self.events.truncate(checkpoint);
+        let node = self.start();
+        let consumed = (start..self.index).filter(|&index| !self.tokens[index].is_trivia());
+        self.events.extend(consumed.map(Event::Token));
+        skip(self);
+        if self.events.len() == node.0 + 1 {
+            self.events.pop();
+        } else {
+            self.complete(node, SyntaxKind::Error);
+        }
+    }
+
+    fn require_version(
+        &mut self,
+        required: LanguageVersion,
+        feature: &str,
+        span: Span,
+    ) -> Result<(), ParseError> {
+        if self.version >= required {
+            Ok(())
+        } else {
+            self.recover(
+                ParseError::new(
+                    format!(
+                        "orbit {} does not support {feature} (added in {required})",
+                        self.version
+                    ),
+                    span,
+                )
+                .with_code(ErrorCode::UnsupportedSyntax),
+            )
+        }
+    }
+
+    // Nesting errors are not recovered where they occur; the enclosing
+    // statement or object entry is skipped instead.
+    fn nested<T>(
+        &mut self,
+        span: Span,
+        parse: impl FnOnce(&mut Self) -> Result<T, ParseError>,
+    ) -> Result<T, ParseError> {
+        if self.depth >= self.limits.max_depth {
+            return Err(ParseError::new(
+                format!(
+                    "nesting exceeds the limit of {} levels",
+                    self.limits.max_depth
+                ),
+                span,
+            )
+            .with_code(ErrorCode::NestingTooDeep));
+        }
+        self.depth += 1;
+        let result = parse(self);
+        self.depth -= 1;
+        result
+    }
+
+    fn span(&self, start: usize, end: usize) -> Span {
+        Span::new(start, end).in_file(self.file)
+    }
+
+    fn recover(&mut self, error: ParseError) -> Result<(), ParseError> {
+        if self.recovering {
+            self.errors.push(error);
+            Ok(())
+        } else {
+            Err(error)
         }
     }
 
     fn consume_ident(&mut self, message: &str) -> Result<(String, Span), ParseError> {
-        let token = self.expect(|kind| matches!(kind, TokenKind::Ident(_)), message)?;
+        let token = self.expect(SyntaxKind::Ident, ErrorCode::ExpectedIdentifier, message)?;
         if let TokenKind::Ident(raw) = token.kind {
-            Ok((raw.to_string(), token.span))
+            Ok((normalize_identifier(raw), token.span))
         } else {
             unreachable!()
         }
@@ -222,14 +879,18 @@ impl<'a> Parser<'a> {
         }
     }
 
-    fn expect<F>(&mut self, predicate: F, message: &str) -> Result<&Token<'a>, ParseError>
-    where
-        F: Fn(&TokenKind<'a>) -> bool,
-    {
-        if predicate(&self.peek().kind) {
+    fn expect(
+        &mut self,
+        expected: SyntaxKind,
+        code: ErrorCode,
+        message: &str,
+    ) -> Result<&Token<'a>, ParseError> {
+        if SyntaxKind::from_token(&self.peek().kind) == Some(expected) {
             Ok(self.advance())
         } else {
-            Err(ParseError::new(message, self.peek().span))
+            Err(ParseError::new(message, self.peek().span)
+                .with_code(code)
+                .with_expected([expected]))
         }
     }
 
@@ -240,17 +901,26 @@ impl<'a> Parser<'a> {
         predicate(&self.peek().kind)
     }
 
+    // The end of input is never consumed, so it has no event of its own.
     fn advance(&mut self) -> &Token<'a> {
         self.skip_trivia();
         let idx = self.index;
         self.last_consumed = Some(idx);
         if self.index < self.tokens.len() - 1 {
+            self.events.push(Event::Token(idx));
             self.index += 1;
             self.skip_trivia();
         }
         &self.tokens[idx]
     }
 
+    fn rewind(&mut self, index: usize) {
+        self.index = index;
+        self.last_consumed = self.tokens[..index]
+            .iter()
+            .rposition(|token| !token.is_trivia());
+    }
+
     fn peek(&mut self) -> &Token<'a> {
         self.skip_trivia();
         &self.tokens[self.index]
@@ -273,30 +943,218 @@ impl<'a> Parser<'a> {
         matches!(self.peek().kind, TokenKind::Eof)
     }
 
-    fn synchronize(&mut self) {
-        if matches!(self.peek().kind, TokenKind::Eof) {
-            return;
+    // A `,` between items, or a `;` typed in its place, which the lexer has
+    // already reported with a fix. Other lexical errors are stepped over.
+    fn matches_separator(&mut self) -> bool {
+        if self.matches(|kind| matches!(kind, TokenKind::Error(";"))) {
+            return true;
         }
-        self.advance();
+        self.skip_lex_errors();
+        self.matches(|kind| matches!(kind, TokenKind::Comma))
+    }
+
+    fn missing_comma(&mut self, close: SyntaxKind) -> ParseError {
+        let (message, help) = match close {
+            SyntaxKind::RBracket => (
+                "expected ',' between list items",
+                "add ',' between the items",
+            ),
+            _ => (
+                "expected ',' between object entries",
+                "add ',' between the entries",
+            ),
+        };
+        ParseError::new(message, self.peek().span)
+            .with_code(ErrorCode::MissingComma)
+            .with_expected([SyntaxKind::Comma, close])
+            .with_suggestion(self.insert_after_previous(help, ","))
+    }
+
+    fn insert_after_previous(&self, message: &str, text: &str) -> Suggestion {
+        let end = self.previous().map_or(0, |token| token.span.end);
+        Suggestion::new(message, self.span(end, end), text)
+    }
+
+    // Error tokens were already reported by the lexer, so they are stepped
+    // over without adding a parse error of their own.
+    fn skip_lex_errors(&mut self) {
+        while self.current_is(|kind| matches!(kind, TokenKind::Error(_))) {
+            self.advance();
+        }
+    }
+
+    fn starts_value(&mut self) -> bool {
+        self.current_is(|kind| {
+            matches!(
+                kind,
+                TokenKind::String(_)
+                    | TokenKind::Bytes(_)
+                    | TokenKind::Tagged(..)
+                    | TokenKind::Number(_)
+                    | TokenKind::Bool(_)
+                    | TokenKind::LBracket
+                    | TokenKind::LBrace
+            )
+        })
+    }
+
+    fn starts_key(&mut self) -> bool {
+        self.current_is(|kind| {
+            matches!(
+                kind,
+                TokenKind::Ident(_) | TokenKind::String(_) | TokenKind::Ellipsis | TokenKind::At
+            )
+        })
+    }
+
+    // Tokens that can only mean a list item is missing its closing bracket.
+    fn ends_container(&mut self) -> bool {
+        self.current_is(|kind| {
+            matches!(
+                kind,
+                TokenKind::Ident(_)
+                    | TokenKind::At
+                    | TokenKind::RBrace
+                    | TokenKind::RParen
+                    | TokenKind::DocumentSeparator
+                    | TokenKind::Eof
+            )
+        })
+    }
+
+    fn starts_line(&self, index: usize) -> bool {
+        index == 0 || matches!(self.tokens[index - 1].kind, TokenKind::Newline)
+    }
+
+    // Width of the leading whitespace on the line holding token `index`.
+    fn line_indent(&self, index: usize) -> usize {
+        let first = (0..=index)
+            .rev()
+            .find(|&at| self.starts_line(at))
+            .unwrap_or(0);
+        let line_start = first
+            .checked_sub(1)
+            .map_or(0, |newline| self.tokens[newline].span.end);
+        self.tokens[first].span.start - line_start
+    }
+
+    // Skips to the next line that starts with an identifier or annotation, or
+    // to a `}` that closes an enclosing block.
+    fn synchronize(&mut self, start: usize) {
+        if self.index == start && !self.is_at_end() {
+            self.advance();
+        }
+        let mut depth = 0usize;
         while !self.is_at_end() {
-            match self.peek().kind {
-                TokenKind::Ident(_) | TokenKind::RBrace => break,
-                _ => {
-                    self.advance();
+            let line_start = self.starts_line(self.index);
+            match self.tokens[self.index].kind {
+                TokenKind::Ident(_) | TokenKind::At if depth == 0 && line_start => break,
+                TokenKind::RBrace if depth == 0 => break,
+                TokenKind::DocumentSeparator => break,
+                TokenKind::LBrace | TokenKind::LBracket | TokenKind::LParen => depth += 1,
+                TokenKind::RBrace | TokenKind::RBracket | TokenKind::RParen => {
+                    depth = depth.saturating_sub(1)
+                }
+                _ => {}
+            }
+            self.advance();
+        }
+    }
+
+    // Inside an object literal a broken entry is skipped up to the next
+    // comma or the closing brace.
+    fn skip_object_entry(&mut self) {
+        let mut depth = 0usize;
+        while !self.is_at_end() {
+            let line_start = self.starts_line(self.index);
+            match self.tokens[self.index].kind {
+                TokenKind::Comma | TokenKind::RBrace if depth == 0 => break,
+                TokenKind::Ident(_) if depth == 0 && line_start => break,
+                TokenKind::LBrace | TokenKind::LBracket | TokenKind::LParen => depth += 1,
+                TokenKind::RBrace | TokenKind::RBracket | TokenKind::RParen => {
+                    depth = depth.saturating_sub(1)
                 }
+                _ => {}
             }
+            self.advance();
         }
     }
 }
 
-fn parse_number_literal(raw: &str, span: Span) -> Result<OrbitNumber, ParseError> {
+fn separator_error(span: Span) -> ParseError {
+    ParseError::new(
+        "unexpected document separator; use parse_documents for multi-document sources",
+        span,
+    )
+    .with_code(ErrorCode::UnexpectedSeparator)
+}
+
+fn pragma_error(source: &str, message: String) -> LexError {
+    let pragma_len = source.lines().next().map(str::len).unwrap_or(0);
+    LexError::new(message, Span::new(0, pragma_len)).with_code(ErrorCode::UnsupportedVersion)
+}
+
+pub(crate) fn parse_number_literal(raw: &str, span: Span) -> Result<OrbitNumber, ParseError> {
     if raw.contains(['.', 'e', 'E']) {
-        raw.parse::<f64>()
-            .map(OrbitNumber::Float)
-            .map_err(|_| ParseError::new("invalid float literal", span))
+        raw.parse::<f64>().map(OrbitNumber::Float).map_err(|_| {
+            ParseError::new("invalid float literal", span).with_code(ErrorCode::InvalidNumber)
+        })
     } else {
-        raw.parse::<i64>()
-            .map(OrbitNumber::Integer)
-            .map_err(|_| ParseError::new("invalid integer literal", span))
+        raw.parse::<i64>().map(OrbitNumber::Integer).map_err(|_| {
+            ParseError::new("invalid integer literal", span).with_code(ErrorCode::InvalidNumber)
+        })
+    }
+}
+
+// Largest integer a JavaScript number (an f64) holds exactly.
+const MAX_SAFE_INTEGER: u64 = (1 << 53) - 1;
+
+fn number_warning(raw: &str, number: OrbitNumber, span: Span) -> Option<Warning> {
+    match number {
+        OrbitNumber::Integer(value) if value.unsigned_abs() > MAX_SAFE_INTEGER => Some(
+            Warning::new(
+                format!(
+                    "integer {raw} is outside JavaScript's safe integer range \
+                     (±{MAX_SAFE_INTEGER}) and may be rounded by JSON consumers"
+                ),
+                span,
+            )
+            .with_code(ErrorCode::UnsafeInteger),
+        ),
+        OrbitNumber::Float(value) if is_lossy_float(raw, value) => Some(
+            Warning::new(
+                format!(
+                    "float literal {raw} cannot be represented exactly; it evaluates to {value}"
+                ),
+                span,
+            )
+            .with_code(ErrorCode::LossyFloat),
+        ),
+        _ => None,
     }
 }
+
+// Compares the significant digits of the literal with those of the shortest
+// decimal that reads back as the same f64. `0.1` is not exact in binary
+// either, but it round-trips, so only digits that are actually dropped count.
+// The lexer never produces an exponent, so those literals are not checked.
+fn is_lossy_float(raw: &str, value: f64) -> bool {
+    if raw.contains(['e', 'E']) {
+        return false;
+    }
+    let Some((int, frac)) = raw.trim_start_matches('-').split_once('.') else {
+        return false;
+    };
+    let digits = format!("{int}{frac}");
+    let Some(first) = digits.find(|digit| digit != '0') else {
+        return false;
+    };
+    let exponent = int.len() as i64 - 1 - first as i64;
+    let digits = digits[first..].trim_end_matches('0');
+
+    let shortest = format!("{:e}", value.abs());
+    let Some((mantissa, shortest_exponent)) = shortest.split_once('e') else {
+        return true;
+    };
+    mantissa.replace('.', "") != digits || shortest_exponent.parse() != Ok(exponent)
+}
@@ -0,0 +1,16 @@
+use crate::cst::SyntaxKind;
+
+// What the parser produces: node boundaries and the lexer tokens it consumed
+// between them, by index, in source order. `cst::builder` turns them into the
+// syntax tree and fills in the trivia the parser stepped over.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Event {
+    // `first` is the token the node starts at when that comes before the
+    // first token it consumes, as a leading doc comment does.
+    Start {
+        kind: SyntaxKind,
+        first: Option<usize>,
+    },
+    Token(usize),
+    Finish,
+}
@@ -2,6 +2,8 @@ use crate::ast::{AstNode, Span};
 
 pub type Document = AstNode;
 
+pub const PROFILE_KEYWORD: &str = "profile";
+
 pub fn document(body: Vec<AstNode>, span: Span) -> AstNode {
     AstNode::Document { body, span }
 }
@@ -0,0 +1,470 @@
+use std::sync::Arc;
+
+use crate::{
+    ast::Span,
+    cst::{
+        SyntaxKind, SyntaxTree,
+        green::{GreenChild, GreenNode},
+    },
+    error::Warning,
+    lexer::DOC_COMMENT_PREFIX,
+};
+
+use super::Parser;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextEdit {
+    pub range: Span,
+    pub replacement: String,
+}
+
+impl TextEdit {
+    pub fn new(range: Span, replacement: impl Into<String>) -> Self {
+        Self {
+            range,
+            replacement: replacement.into(),
+        }
+    }
+
+    pub fn apply(&self, source: &str) -> String {
+        let mut edited = source.to_string();
+        edited.replace_range(self.range.start..self.range.end, &self.replacement);
+        edited
+    }
+
+    fn delta(&self) -> isize {
+        self.replacement.len() as isize - self.range.len() as isize
+    }
+}
+
+// Re-parses `source`, the text after `edit` was applied to the text of
+// `previous`, under the limits `previous` was parsed with. Only the
+// statements around the edit, inside the innermost block or profile body
+// holding it, are lexed and parsed again; every other subtree is shared with
+// `previous`. Stored nodes only know their width, so nothing after the edit
+// is shifted. The result always equals a full parse: when `previous` has
+// errors, the edit touches the pragma line, or the stretch around it does not
+// parse cleanly on its own, the whole source is parsed instead.
+pub fn reparse(previous: &SyntaxTree, source: &str, edit: &TextEdit) -> SyntaxTree {
+    try_reparse(previous, source, edit)
+        .unwrap_or_else(|| SyntaxTree::parse_with_limits(source, previous.limits()))
+}
+
+fn try_reparse(previous: &SyntaxTree, source: &str, edit: &TextEdit) -> Option<SyntaxTree> {
+    let range = edit.range;
+    // Confusable identifiers are checked against every other identifier of
+    // the source, so a region cannot be judged on its own.
+    if !previous.errors().is_empty()
+        || previous.has_unicode_identifiers()
+        || range.start > range.end
+        || range.end > previous.len()
+        || previous.len().checked_add_signed(edit.delta()) != Some(source.len())
+    {
+        return None;
+    }
+    // The pragma on the first line selects the grammar for the whole file.
+    if source.find('\n').is_none_or(|end| range.start <= end) {
+        return None;
+    }
+
+    let root = previous.green();
+    let area = Area::around(root, range)?;
+    let (document_index, document) = area.document(root)?;
+    let mut path = vec![(root.as_ref(), document_index)];
+    let mut node = document;
+    let mut offset = area.start + width(&root.children()[area.first..document_index]);
+    // Descend into the innermost block or profile whose braces hold the edit.
+    'descend: loop {
+        let mut child_offset = offset;
+        for (index, child) in node.children().iter().enumerate() {
+            if let GreenChild::Node(child) = child
+                && let Some((open, close)) = braces(child, child_offset)
+                && open <= range.start
+                && range.end <= close
+            {
+                path.push((node, index));
+                node = child.as_ref();
+                offset = child_offset;
+                continue 'descend;
+            }
+            child_offset += child.len();
+        }
+        break;
+    }
+
+    let nested = path.len() > 1;
+    let body = if nested {
+        let (open, close) = brace_indexes(node)?;
+        let start = offset + width(&node.children()[..=open]);
+        Body {
+            children: node.children()[open + 1..close].to_vec(),
+            start,
+            end: start + width(&node.children()[open + 1..close]),
+        }
+    } else {
+        let children = root.children();
+        let mut flat = children[area.first..document_index].to_vec();
+        flat.extend(document.children().iter().cloned());
+        flat.extend(children[document_index + 1..area.last].iter().cloned());
+        Body {
+            children: flat,
+            start: area.start,
+            end: area.end,
+        }
+    };
+
+    let context = Context {
+        previous,
+        source,
+        edit,
+        separator_follows: !nested && area.last < root.children().len(),
+    };
+    let children = context.reparse_body(&body, path.len() - 1)?;
+    let (tokens, warnings) = (children.tokens, children.warnings);
+
+    // Rebuild the nodes on the path to the body; everything else is shared.
+    let new_root = if nested {
+        let (open, close) = brace_indexes(node)?;
+        let mut rebuilt = node.children()[..=open].to_vec();
+        rebuilt.extend(children.children);
+        rebuilt.extend(node.children()[close..].iter().cloned());
+        let mut replacement = Arc::new(GreenNode::new(node.kind(), rebuilt));
+        for &(parent, index) in path.iter().rev() {
+            let mut siblings = parent.children().to_vec();
+            siblings[index] = GreenChild::Node(replacement);
+            replacement = Arc::new(GreenNode::new(parent.kind(), siblings));
+        }
+        replacement
+    } else {
+        // Trivia before the first statement of a document and after its last
+        // one belongs to the root, as it does in a full parse.
+        let flat = children.children;
+        let first = flat.iter().position(is_node);
+        let last = flat.iter().rposition(is_node);
+        let (lead, statements, trail) = match (first, last) {
+            (Some(first), Some(last)) => (&flat[..first], &flat[first..=last], &flat[last + 1..]),
+            _ => (&flat[..0], &flat[..0], &flat[..]),
+        };
+        let children = root.children();
+        let mut rebuilt = children[..area.first].to_vec();
+        rebuilt.extend(lead.iter().cloned());
+        rebuilt.push(GreenChild::Node(Arc::new(GreenNode::new(
+            SyntaxKind::Document,
+            statements.to_vec(),
+        ))));
+        rebuilt.extend(trail.iter().cloned());
+        rebuilt.extend(children[area.last..].iter().cloned());
+        Arc::new(GreenNode::new(root.kind(), rebuilt))
+    };
+
+    Some(SyntaxTree::new(
+        new_root,
+        previous.version(),
+        previous.limits(),
+        Vec::new(),
+        warnings,
+        tokens,
+        false,
+    ))
+}
+
+// The root's children between two document separators, with the byte range
+// they cover.
+struct Area {
+    first: usize,
+    last: usize,
+    start: usize,
+    end: usize,
+}
+
+impl Area {
+    // An edit reaching into a separator, or starting right behind one where
+    // it would extend the `---`, is not contained in any area.
+    fn around(root: &GreenNode, range: Span) -> Option<Self> {
+        let (mut first, mut start, mut offset) = (0, 0, 0);
+        for (index, child) in root.children().iter().enumerate() {
+            if child_kind(child) == SyntaxKind::DocumentSeparator {
+                if range.end <= offset {
+                    return Self::checked(first, index, start, offset, range);
+                }
+                first = index + 1;
+                start = offset + child.len();
+            }
+            offset += child.len();
+        }
+        Self::checked(first, root.children().len(), start, offset, range)
+    }
+
+    fn checked(first: usize, last: usize, start: usize, end: usize, range: Span) -> Option<Self> {
+        if range.start < start || (first > 0 && range.start == start) {
+            return None;
+        }
+        Some(Self {
+            first,
+            last,
+            start,
+            end,
+        })
+    }
+
+    fn document<'g>(&self, root: &'g GreenNode) -> Option<(usize, &'g GreenNode)> {
+        root.children()[self.first..self.last]
+            .iter()
+            .enumerate()
+            .find_map(|(index, child)| match child {
+                GreenChild::Node(node) if node.kind() == SyntaxKind::Document => {
+                    Some((self.first + index, node.as_ref()))
+                }
+                _ => None,
+            })
+    }
+}
+
+// The statements of a body and the trivia between them, starting at byte
+// `start` of the old text: the inside of a block's braces, or a whole
+// document area with the trivia around the document.
+struct Body {
+    children: Vec<GreenChild>,
+    start: usize,
+    end: usize,
+}
+
+struct Reparsed {
+    children: Vec<GreenChild>,
+    tokens: usize,
+    warnings: Vec<Warning>,
+}
+
+struct Context<'a> {
+    previous: &'a SyntaxTree,
+    source: &'a str,
+    edit: &'a TextEdit,
+    separator_follows: bool,
+}
+
+impl Context<'_> {
+    // Positions are in the old text unless they are called new.
+    fn reparse_body(&self, body: &Body, depth: usize) -> Option<Reparsed> {
+        let range = self.edit.range;
+        let delta = self.edit.delta();
+        let mut positions = Vec::with_capacity(body.children.len());
+        let mut offset = body.start;
+        for child in &body.children {
+            positions.push(offset);
+            offset += child.len();
+        }
+        let span = |index: usize| {
+            Span::new(
+                positions[index],
+                positions[index] + body.children[index].len(),
+            )
+        };
+        let statements: Vec<usize> = (0..body.children.len())
+            .filter(|&index| is_node(&body.children[index]))
+            .collect();
+
+        // The statement before the edit and the one after it are parsed again
+        // too: the edit may change where one ends or which doc comments
+        // attach to the other.
+        let before = statements
+            .iter()
+            .copied()
+            .rfind(|&index| span(index).end < range.start);
+        let (first, start) = before.map_or((0, body.start), |index| (index, positions[index]));
+        // Comments and strings never cross a line break, so ending the region
+        // at one guarantees the lexer splits the full text there too.
+        let new_end = shift(body.end, delta);
+        let line_end = |end: usize| {
+            let new = shift(end, delta);
+            self.source[new..new_end]
+                .find('\n')
+                .map_or(body.end, |at| shift(new + at + 1, -delta))
+        };
+        let mut end = statements
+            .iter()
+            .find(|&&index| span(index).start > range.end)
+            .map_or(body.end, |&index| line_end(span(index).end));
+        // Statements sharing the last line may run past it; take them in whole.
+        while let Some(&index) = statements.iter().find(|&&index| {
+            let span = span(index);
+            span.start > range.end && span.start < end && span.end > end
+        }) {
+            end = line_end(span(index).end);
+        }
+        let last = positions
+            .iter()
+            .position(|&position| position >= end)
+            .unwrap_or(body.children.len());
+        let covered = last.checked_sub(1).map_or(start, |index| span(index).end);
+        if covered != end || start > range.start || end < range.end {
+            return None;
+        }
+
+        let new_end = shift(end, delta);
+        let region = &self.source[start..new_end];
+        let line_break = |text: &str| text.ends_with(['\n', '\r']);
+        // A doc comment opening the region is only attached by the region
+        // parser when it also starts its line in the full text.
+        let before_region = self.source[..start].trim_end_matches([' ', '\t', '\x0c']);
+        if region
+            .trim_start_matches([' ', '\t', '\x0c'])
+            .starts_with(DOC_COMMENT_PREFIX)
+            && !(before_region.is_empty() || line_break(before_region))
+        {
+            return None;
+        }
+        // A separator only counts at the start of a line.
+        if self.separator_follows && end == body.end && !line_break(&self.source[..new_end]) {
+            return None;
+        }
+
+        let part = Parser::with_version_and_limits(
+            region,
+            self.previous.version(),
+            self.previous.limits(),
+        )
+        .ok()?
+        .parse_region(depth)?;
+        if part.has_unicode_identifiers() {
+            return None;
+        }
+        let root = part.green();
+        // A region that stops before a closing brace must not end inside a
+        // comment, which in the full text would swallow the brace.
+        let ends_in_comment = root
+            .children()
+            .iter()
+            .rev()
+            .find(|child| child_kind(child) != SyntaxKind::Whitespace)
+            .is_some_and(|child| {
+                matches!(
+                    child_kind(child),
+                    SyntaxKind::Comment | SyntaxKind::DocComment
+                )
+            });
+        if ends_in_comment && !line_break(region) && new_end != self.source.len() {
+            return None;
+        }
+        // A trailing doc comment belongs to the first reused statement, which
+        // the region parser never sees.
+        let trailing_docs = root
+            .children()
+            .iter()
+            .rev()
+            .take_while(|child| !is_node(child))
+            .any(|child| child_kind(child) == SyntaxKind::DocComment);
+        if trailing_docs && body.children[last..].iter().any(is_node) {
+            return None;
+        }
+
+        let tokens = (self.previous.token_count() + part.token_count())
+            .checked_sub(count_tokens(&body.children[first..last]))?;
+        if tokens >= self.previous.limits().max_tokens {
+            return None;
+        }
+        let warnings = self.warnings(start, end, &part)?;
+
+        let mut children = body.children[..first].to_vec();
+        for child in root.children() {
+            match child {
+                GreenChild::Node(node) if node.kind() == SyntaxKind::Document => {
+                    children.extend(node.children().iter().cloned());
+                }
+                child => children.push(child.clone()),
+            }
+        }
+        children.extend(body.children[last..].iter().cloned());
+        Some(Reparsed {
+            children,
+            tokens,
+            warnings,
+        })
+    }
+
+    // The old warnings outside the region, moved with the text, and the
+    // region's own. Both lists are ordered by position.
+    fn warnings(&self, start: usize, end: usize, part: &SyntaxTree) -> Option<Vec<Warning>> {
+        let delta = self.edit.delta();
+        let moved = |warning: &Warning, delta: isize| {
+            let mut warning = warning.clone();
+            warning.span.start = shift(warning.span.start, delta);
+            warning.span.end = shift(warning.span.end, delta);
+            warning
+        };
+        let mut before = Vec::new();
+        let mut after = Vec::new();
+        for warning in self.previous.warnings() {
+            let span = warning.span;
+            if span.end <= start {
+                before.push(warning.clone());
+            } else if span.start >= end {
+                after.push(moved(warning, delta));
+            } else if span.start < start || span.end > end {
+                return None;
+            }
+        }
+        before.extend(
+            part.warnings()
+                .iter()
+                .map(|warning| moved(warning, start as isize)),
+        );
+        before.extend(after);
+        Some(before)
+    }
+}
+
+// The offsets just inside the braces of a block or profile that starts at
+// `offset`.
+fn braces(node: &GreenNode, offset: usize) -> Option<(usize, usize)> {
+    if !matches!(node.kind(), SyntaxKind::Block | SyntaxKind::Profile) {
+        return None;
+    }
+    let (open, close) = brace_indexes(node)?;
+    let children = node.children();
+    Some((
+        offset + width(&children[..=open]),
+        offset + width(&children[..close]),
+    ))
+}
+
+fn brace_indexes(node: &GreenNode) -> Option<(usize, usize)> {
+    let children = node.children();
+    let open = children
+        .iter()
+        .position(|child| child_kind(child) == SyntaxKind::LBrace)?;
+    let close = children
+        .iter()
+        .rposition(|child| child_kind(child) == SyntaxKind::RBrace)?;
+    (open < close).then_some((open, close))
+}
+
+fn child_kind(child: &GreenChild) -> SyntaxKind {
+    match child {
+        GreenChild::Node(node) => node.kind(),
+        GreenChild::Token(token) => token.kind(),
+    }
+}
+
+fn is_node(child: &GreenChild) -> bool {
+    matches!(child, GreenChild::Node(_))
+}
+
+fn width(children: &[GreenChild]) -> usize {
+    children.iter().map(GreenChild::len).sum()
+}
+
+// Lexer tokens under `children`; whitespace tokens only fill the gaps
+// between them.
+fn count_tokens(children: &[GreenChild]) -> usize {
+    children
+        .iter()
+        .map(|child| match child {
+            GreenChild::Node(node) => count_tokens(node.children()),
+            GreenChild::Token(token) => usize::from(token.kind() != SyntaxKind::Whitespace),
+        })
+        .sum()
+}
+
+fn shift(offset: usize, delta: isize) -> usize {
+    offset.saturating_add_signed(delta)
+}
@@ -1,4 +1,9 @@
 mod driver;
+mod event;
 pub mod grammar;
+mod incremental;
 
+pub(crate) use self::driver::parse_number_literal;
 pub use self::driver::{ParseReport, Parser};
+pub(crate) use self::event::Event;
+pub use self::incremental::{TextEdit, reparse};
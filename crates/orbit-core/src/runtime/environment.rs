@@ -1,16 +1,18 @@
 use indexmap::IndexMap;
 
-use crate::value::OrbitValue;
+use crate::{ast::Span, value::OrbitValue};
 
 #[derive(Debug, Default, Clone)]
 pub struct Environment {
     values: IndexMap<String, OrbitValue>,
+    spans: IndexMap<String, Span>,
 }
 
 impl Environment {
     pub fn new() -> Self {
         Self {
             values: IndexMap::new(),
+            spans: IndexMap::new(),
         }
     }
 
@@ -18,6 +20,17 @@ impl Environment {
         self.values.insert(key, value)
     }
 
+    // Like `insert`, but remembers where `key` was defined. When the key is
+    // already taken, nothing changes and the earlier definition is returned.
+    pub fn define(&mut self, key: String, value: OrbitValue, span: Span) -> Result<(), Span> {
+        if self.values.contains_key(&key) {
+            return Err(self.spans.get(&key).copied().unwrap_or(span));
+        }
+        self.spans.insert(key.clone(), span);
+        self.values.insert(key, value);
+        Ok(())
+    }
+
     pub fn get(&self, key: &str) -> Option<&OrbitValue> {
         self.values.get(key)
     }
@@ -0,0 +1,65 @@
+use indexmap::IndexMap;
+
+use crate::{
+    ast::Annotation,
+    error::{CoreError, Warning},
+    value::OrbitValue,
+};
+
+pub type ValuePath = Vec<String>;
+
+#[derive(Debug, Clone)]
+pub struct Evaluation {
+    pub value: OrbitValue,
+    pub annotations: IndexMap<ValuePath, Vec<Annotation>>,
+    pub warnings: Vec<Warning>,
+}
+
+impl Evaluation {
+    pub fn annotations_at(&self, path: &[&str]) -> &[Annotation] {
+        let key: ValuePath = path.iter().map(|part| part.to_string()).collect();
+        self.annotations
+            .get(&key)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    pub fn has_annotation(&self, path: &[&str], name: &str) -> bool {
+        self.annotations_at(path)
+            .iter()
+            .any(|annotation| annotation.name == name)
+    }
+}
+
+// The result of a best-effort evaluation: whatever could be evaluated, plus
+// every error found on the way.
+#[derive(Debug)]
+pub struct EvaluationReport {
+    pub value: OrbitValue,
+    pub annotations: IndexMap<ValuePath, Vec<Annotation>>,
+    // Values left out of `value` because they failed to parse or evaluate.
+    // An entry broken beyond telling its key, and a spread that could not be
+    // resolved, mark the enclosing block, list, or object (or the document,
+    // as the empty path) instead.
+    pub broken: Vec<ValuePath>,
+    pub errors: Vec<CoreError>,
+    pub warnings: Vec<Warning>,
+}
+
+impl EvaluationReport {
+    pub fn is_broken(&self, path: &[&str]) -> bool {
+        self.broken
+            .iter()
+            .any(|broken| broken.iter().eq(path.iter().copied()))
+    }
+
+    // Moves every warning into `errors`, keeping them ordered by position.
+    pub(crate) fn deny_warnings(&mut self) {
+        let denied = self
+            .warnings
+            .drain(..)
+            .map(|warning| CoreError::from(warning.into_error()));
+        self.errors.extend(denied);
+        self.errors.sort_by_key(|error| error.span().start);
+    }
+}
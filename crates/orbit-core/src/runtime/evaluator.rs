@@ -1,92 +1,608 @@
+use std::collections::{HashMap, HashSet};
+
 use indexmap::IndexMap;
 
 use crate::{
-    ast::{AstNode, ObjectEntry, ValueNode},
-    error::RuntimeError,
-    value::OrbitValue,
+    ast::{Annotation, AstNode, ObjectEntry, Span, ValueNode},
+    error::{CoreError, ErrorCode, RuntimeError, Warning},
+    value::{OrbitValue, Secret},
+};
+
+use super::{
+    environment::Environment,
+    evaluation::{Evaluation, EvaluationReport, ValuePath},
+    options::EvaluationOptions,
 };
 
-use super::environment::Environment;
+pub const DEPRECATED_ANNOTATION: &str = "deprecated";
+pub const SECRET_ANNOTATION: &str = "secret";
 
-pub struct Evaluator;
+pub struct Evaluator<'a> {
+    options: &'a EvaluationOptions,
+    scopes: Vec<Environment>,
+    path: ValuePath,
+    annotations: IndexMap<ValuePath, Vec<Annotation>>,
+    warnings: Vec<Warning>,
+    // Checked against `options.limits`: the nesting level being evaluated
+    // and the number of values produced so far.
+    depth: usize,
+    values: usize,
+    // Set by `evaluate_partial`: errors are collected here and the values
+    // they affect are left out instead of ending the evaluation.
+    recovering: bool,
+    errors: Vec<RuntimeError>,
+    broken: Vec<ValuePath>,
+}
 
-impl Evaluator {
+impl<'a> Evaluator<'a> {
     pub fn evaluate(ast: &AstNode) -> Result<OrbitValue, RuntimeError> {
+        Evaluator::evaluate_with_options(ast, &EvaluationOptions::default())
+    }
+
+    pub fn evaluate_with_options(
+        ast: &AstNode,
+        options: &'a EvaluationOptions,
+    ) -> Result<OrbitValue, RuntimeError> {
+        Ok(Evaluator::evaluate_detailed(ast, options)?.value)
+    }
+
+    pub fn evaluate_detailed(
+        ast: &AstNode,
+        options: &'a EvaluationOptions,
+    ) -> Result<Evaluation, RuntimeError> {
+        let evaluation = Evaluator::evaluate_allowing_warnings(ast, options)?;
+        if options.deny_warnings
+            && let Some(error) = Warning::deny_all(&evaluation.warnings)
+        {
+            return Err(error);
+        }
+        Ok(evaluation)
+    }
+
+    // `evaluate_detailed` without denying warnings, for callers that add
+    // warnings of their own before denying them together.
+    pub(crate) fn evaluate_allowing_warnings(
+        ast: &AstNode,
+        options: &'a EvaluationOptions,
+    ) -> Result<Evaluation, RuntimeError> {
+        let mut evaluator = Evaluator::new(options, false);
+        let value = evaluator.evaluate_root(ast)?;
+        Ok(Evaluation {
+            value,
+            annotations: evaluator.annotations,
+            warnings: evaluator.warnings,
+        })
+    }
+
+    // Never fails: error nodes left by a recovering parse and values that
+    // fail to evaluate are left out and listed in `broken`. Only a limit
+    // error ends the evaluation early, with an empty document.
+    pub fn evaluate_partial(ast: &AstNode, options: &'a EvaluationOptions) -> EvaluationReport {
+        let mut report = Evaluator::evaluate_partial_allowing_warnings(ast, options);
+        if options.deny_warnings {
+            report.deny_warnings();
+        }
+        report
+    }
+
+    // `evaluate_partial` without denying warnings, like
+    // `evaluate_allowing_warnings`.
+    pub(crate) fn evaluate_partial_allowing_warnings(
+        ast: &AstNode,
+        options: &'a EvaluationOptions,
+    ) -> EvaluationReport {
+        let mut evaluator = Evaluator::new(options, true);
+        let value = evaluator.evaluate_root(ast).unwrap_or_else(|error| {
+            evaluator.errors.push(error);
+            evaluator.broken.push(Vec::new());
+            OrbitValue::Object(IndexMap::new())
+        });
+        EvaluationReport {
+            value,
+            annotations: evaluator.annotations,
+            broken: evaluator.broken,
+            errors: evaluator.errors.into_iter().map(CoreError::from).collect(),
+            warnings: evaluator.warnings,
+        }
+    }
+
+    fn new(options: &'a EvaluationOptions, recovering: bool) -> Self {
+        Self {
+            options,
+            scopes: Vec::new(),
+            path: Vec::new(),
+            annotations: IndexMap::new(),
+            warnings: Vec::new(),
+            depth: 0,
+            values: 0,
+            recovering,
+            errors: Vec::new(),
+            broken: Vec::new(),
+        }
+    }
+
+    fn evaluate_root(&mut self, ast: &AstNode) -> Result<OrbitValue, RuntimeError> {
         match ast {
-            AstNode::Document { body, .. } => Self::evaluate_nodes(body),
-            node => Self::evaluate_nodes(std::slice::from_ref(node)),
+            AstNode::Document { body, span } => self.evaluate_document(body, *span),
+            node => self.evaluate_nodes(std::slice::from_ref(node)),
         }
     }
 
-    fn evaluate_nodes(nodes: &[AstNode]) -> Result<OrbitValue, RuntimeError> {
-        let mut env = Environment::new();
+    fn evaluate_document(
+        &mut self,
+        body: &[AstNode],
+        span: Span,
+    ) -> Result<OrbitValue, RuntimeError> {
+        let mut profiles = IndexMap::new();
+        for node in body {
+            if let AstNode::Profile {
+                name, body, span, ..
+            } = node
+            {
+                if let Some(&(_, first)) = profiles.get(name.as_str()) {
+                    self.recover(
+                        RuntimeError::new(format!("duplicate profile '{name}'"), *span)
+                            .with_code(ErrorCode::DuplicateProfile)
+                            .with_label(first, "first defined here"),
+                    )?;
+                    continue;
+                }
+                profiles.insert(name.as_str(), (body, *span));
+            }
+        }
+
+        let base = body
+            .iter()
+            .filter(|node| !matches!(node, AstNode::Profile { .. }));
+        let env = self.evaluate_scope(base)?;
+
+        // Profiles are applied in the order the host selected them, so later
+        // profiles win over earlier ones and all of them win over the base.
+        // Every profile sees the base document (not other profiles) as its
+        // enclosing scope.
+        let (env, overlays) = self.scoped(env, |evaluator| {
+            let mut overlays = Vec::with_capacity(evaluator.options.profiles.len());
+            for name in &evaluator.options.profiles {
+                let Some((profile_body, _)) = profiles.get(name.as_str()) else {
+                    evaluator.recover(
+                        RuntimeError::new(format!("unknown profile '{name}'"), span)
+                            .with_code(ErrorCode::UnknownProfile),
+                    )?;
+                    continue;
+                };
+                overlays.push(evaluator.evaluate_scope(profile_body.iter())?);
+            }
+            Ok(overlays)
+        });
+        let mut value = env.into_value();
+        for overlay in overlays? {
+            if let (OrbitValue::Object(target), OrbitValue::Object(overlay)) =
+                (&mut value, overlay.into_value())
+            {
+                merge_objects(target, overlay);
+            }
+        }
+        Ok(value)
+    }
+
+    fn evaluate_nodes<'n>(
+        &mut self,
+        nodes: impl IntoIterator<Item = &'n AstNode>,
+    ) -> Result<OrbitValue, RuntimeError> {
+        Ok(self.evaluate_scope(nodes)?.into_value())
+    }
+
+    fn evaluate_scope<'n>(
+        &mut self,
+        nodes: impl IntoIterator<Item = &'n AstNode>,
+    ) -> Result<Environment, RuntimeError> {
+        let (scope, result) = self.scoped(Environment::new(), |evaluator| {
+            evaluator.define_nodes(nodes)
+        });
+        result.map(|()| scope)
+    }
+
+    // Runs `evaluate` with `scope` as the innermost scope. The scope, and any
+    // path segments pushed meanwhile, are popped again on every exit,
+    // including an early error return.
+    fn scoped<T>(
+        &mut self,
+        scope: Environment,
+        evaluate: impl FnOnce(&mut Self) -> Result<T, RuntimeError>,
+    ) -> (Environment, Result<T, RuntimeError>) {
+        let path_len = self.path.len();
+        self.scopes.push(scope);
+        let result = evaluate(self);
+        self.path.truncate(path_len);
+        let scope = self.scopes.pop().unwrap_or_default();
+        (scope, result)
+    }
+
+    fn define_nodes<'n>(
+        &mut self,
+        nodes: impl IntoIterator<Item = &'n AstNode>,
+    ) -> Result<(), RuntimeError> {
         for node in nodes {
             match node {
-                AstNode::Entry { key, value, span } => {
-                    let evaluated = Self::evaluate_value(value)?;
-                    if env.insert(key.clone(), evaluated).is_some() {
-                        return Err(RuntimeError::new(format!("duplicate key '{key}'"), *span));
+                AstNode::Entry {
+                    key,
+                    value,
+                    span,
+                    annotations,
+                    ..
+                } => {
+                    self.path.push(key.clone());
+                    self.record_annotations(key, annotations);
+                    let evaluated = self.evaluate_value(value);
+                    let evaluated = self.recover_value(evaluated)?;
+                    self.path.pop();
+                    let Some(evaluated) = evaluated else {
+                        continue;
+                    };
+                    let evaluated = seal_secret(annotations, evaluated);
+                    if let Err(first) = self.current_scope().define(key.clone(), evaluated, *span) {
+                        self.recover(duplicate(format!("duplicate key '{key}'"), *span, first))?;
                     }
                 }
-                AstNode::Block { name, body, span } => {
-                    let nested = Self::evaluate_nodes(body)?;
-                    if env.insert(name.clone(), nested).is_some() {
-                        return Err(RuntimeError::new(
-                            format!("duplicate block '{name}'"),
-                            *span,
-                        ));
+                AstNode::Block {
+                    name,
+                    body,
+                    span,
+                    annotations,
+                    ..
+                } => {
+                    self.path.push(name.clone());
+                    self.record_annotations(name, annotations);
+                    self.produce(1, *span)?;
+                    if body.is_empty() {
+                        self.warnings.push(
+                            Warning::new(format!("block '{name}' is empty"), *span)
+                                .with_code(ErrorCode::EmptyBlock),
+                        );
+                    }
+                    let nested = self.nested(*span, |evaluator| evaluator.evaluate_nodes(body))?;
+                    let nested = seal_secret(annotations, nested);
+                    self.path.pop();
+                    if let Err(first) = self.current_scope().define(name.clone(), nested, *span) {
+                        self.recover(duplicate(format!("duplicate block '{name}'"), *span, first))?;
                     }
                 }
-                AstNode::Document { body, .. } => {
-                    let nested = Self::evaluate_nodes(body)?;
+                AstNode::Profile { span, .. } => {
+                    self.recover(
+                        RuntimeError::new(
+                            "profile sections are only allowed at the top level",
+                            *span,
+                        )
+                        .with_code(ErrorCode::MisplacedProfile),
+                    )?;
+                }
+                AstNode::Error { span } => {
+                    let error =
+                        RuntimeError::new("cannot evaluate a node that failed to parse", *span)
+                            .with_code(ErrorCode::UnparsedNode);
+                    self.recover_value::<()>(Err(error))?;
+                }
+                AstNode::Document { body, span } => {
+                    let nested = self.nested(*span, |evaluator| evaluator.evaluate_nodes(body))?;
                     if let OrbitValue::Object(map) = nested {
                         for (key, value) in map {
-                            if env.insert(key.clone(), value).is_some() {
-                                return Err(RuntimeError::new(
+                            if let Err(first) =
+                                self.current_scope().define(key.clone(), value, node.span())
+                            {
+                                self.recover(duplicate(
                                     format!("duplicate key '{key}'"),
                                     node.span(),
-                                ));
+                                    first,
+                                ))?;
                             }
                         }
                     }
                 }
             }
         }
-        Ok(env.into_value())
+        Ok(())
     }
 
-    fn evaluate_value(value: &ValueNode) -> Result<OrbitValue, RuntimeError> {
+    fn evaluate_value(&mut self, value: &ValueNode) -> Result<OrbitValue, RuntimeError> {
+        self.produce(1, value.span())?;
         match value {
             ValueNode::String { value, .. } => Ok(OrbitValue::String(value.clone())),
+            ValueNode::Bytes { value, .. } => Ok(OrbitValue::Bytes(value.clone())),
+            ValueNode::Tagged { tag, value, span } => {
+                let parser = self.options.tags.get(tag).ok_or_else(|| {
+                    RuntimeError::new(format!("unknown literal tag '{tag}'"), *span)
+                        .with_code(ErrorCode::UnknownTag)
+                })?;
+                // Host parsers that do not choose a code get the generic one
+                // for rejected literals.
+                parser(value, *span).map_err(|error| match error.code {
+                    ErrorCode::Runtime => error.with_code(ErrorCode::InvalidTaggedLiteral),
+                    _ => error,
+                })
+            }
             ValueNode::Number { value, .. } => Ok(OrbitValue::Number(*value)),
             ValueNode::Bool { value, .. } => Ok(OrbitValue::Bool(*value)),
-            ValueNode::List { items, .. } => {
-                let mut evaluated = Vec::with_capacity(items.len());
-                for item in items {
-                    evaluated.push(Self::evaluate_value(item)?);
-                }
-                Ok(OrbitValue::List(evaluated))
-            }
-            ValueNode::Object { entries, .. } => {
-                let map = Self::evaluate_object_entries(entries)?;
-                Ok(OrbitValue::Object(map))
+            ValueNode::List { items, span } => self
+                .nested(*span, |evaluator| evaluator.evaluate_list(items))
+                .map(OrbitValue::List),
+            ValueNode::Object { entries, span } => self
+                .nested(*span, |evaluator| {
+                    evaluator.evaluate_object_entries(entries)
+                })
+                .map(OrbitValue::Object),
+            ValueNode::Spread { span, .. } => Err(RuntimeError::new(
+                "spread is only allowed inside lists and object literals",
+                *span,
+            )
+            .with_code(ErrorCode::InvalidSpread)),
+            ValueNode::Error { span } => Err(RuntimeError::new(
+                "cannot evaluate a value that failed to parse",
+                *span,
+            )
+            .with_code(ErrorCode::UnparsedNode)),
+        }
+    }
+
+    fn evaluate_list(&mut self, items: &[ValueNode]) -> Result<Vec<OrbitValue>, RuntimeError> {
+        let mut evaluated = Vec::with_capacity(items.len());
+        for item in items {
+            if let ValueNode::Spread { name, span } = item {
+                let values = match self.resolve(name, *span) {
+                    Ok(OrbitValue::List(values)) => Ok(values.clone()),
+                    Ok(other) => Err(RuntimeError::new(
+                        format!("cannot spread {} '{name}' into a list", other.type_name()),
+                        *span,
+                    )
+                    .with_code(ErrorCode::InvalidSpread)),
+                    Err(error) => Err(error),
+                };
+                // A spread that cannot be resolved contributes nothing.
+                let Some(values) = self.recover_value(values)? else {
+                    continue;
+                };
+                self.produce(values.iter().map(value_count).sum(), *span)?;
+                evaluated.extend(values);
+            } else {
+                // A broken item is kept as a null, so later items keep
+                // their index.
+                self.path.push(evaluated.len().to_string());
+                let value = self.evaluate_value(item);
+                evaluated.push(self.recover_value(value)?.unwrap_or(OrbitValue::Null));
+                self.path.pop();
             }
         }
+        Ok(evaluated)
     }
 
     fn evaluate_object_entries(
+        &mut self,
         entries: &[ObjectEntry],
     ) -> Result<IndexMap<String, OrbitValue>, RuntimeError> {
         let mut map = IndexMap::new();
+        let mut spread_keys = HashSet::new();
+        let mut definitions = HashMap::new();
         for entry in entries {
-            let value = Self::evaluate_value(&entry.value)?;
-            if map.insert(entry.key.clone(), value).is_some() {
-                return Err(RuntimeError::new(
+            if let ValueNode::Spread { name, span } = &entry.value {
+                let source = match self.resolve(name, *span) {
+                    Ok(OrbitValue::Object(source)) => Ok(source.clone()),
+                    Ok(other) => Err(RuntimeError::new(
+                        format!(
+                            "cannot spread {} '{name}' into an object literal",
+                            other.type_name()
+                        ),
+                        *span,
+                    )
+                    .with_code(ErrorCode::InvalidSpread)),
+                    Err(error) => Err(error),
+                };
+                let Some(source) = self.recover_value(source)? else {
+                    continue;
+                };
+                self.produce(source.values().map(value_count).sum(), *span)?;
+                for (key, value) in source {
+                    if map.contains_key(&key) && !self.can_override(&key, &spread_keys) {
+                        self.recover(duplicate(
+                            format!("duplicate key '{key}' inside object literal"),
+                            *span,
+                            definitions[&key],
+                        ))?;
+                        continue;
+                    }
+                    spread_keys.insert(key.clone());
+                    definitions.insert(key.clone(), *span);
+                    map.insert(key, value);
+                }
+                continue;
+            }
+
+            self.path.push(entry.key.clone());
+            self.record_annotations(&entry.key, &entry.annotations);
+            let value = self.evaluate_value(&entry.value);
+            let value = self.recover_value(value)?;
+            self.path.pop();
+            let Some(value) = value else {
+                continue;
+            };
+            let value = seal_secret(&entry.annotations, value);
+            if map.contains_key(&entry.key) && !self.can_override(&entry.key, &spread_keys) {
+                self.recover(duplicate(
                     format!("duplicate key '{}' inside object literal", entry.key),
                     entry.span,
-                ));
+                    definitions[&entry.key],
+                ))?;
+                continue;
             }
+            spread_keys.remove(&entry.key);
+            definitions.insert(entry.key.clone(), entry.span);
+            map.insert(entry.key.clone(), value);
         }
         Ok(map)
     }
+
+    // While recovering, an error is recorded and evaluation continues as if
+    // the offending definition were absent.
+    fn recover(&mut self, error: RuntimeError) -> Result<(), RuntimeError> {
+        if !self.recovering {
+            return Err(error);
+        }
+        self.errors.push(error);
+        Ok(())
+    }
+
+    // While recovering, a value that failed to evaluate is marked broken at
+    // the current path and left out. Limit errors still end the evaluation,
+    // since every later value would run into them again.
+    fn recover_value<T>(
+        &mut self,
+        value: Result<T, RuntimeError>,
+    ) -> Result<Option<T>, RuntimeError> {
+        match value {
+            Ok(value) => Ok(Some(value)),
+            Err(error)
+                if self.recovering
+                    && !matches!(
+                        error.code,
+                        ErrorCode::NestingTooDeep | ErrorCode::TooManyValues
+                    ) =>
+            {
+                // The parse error behind an error node is already reported.
+                if error.code != ErrorCode::UnparsedNode {
+                    self.errors.push(error);
+                }
+                self.broken.push(self.path.clone());
+                Ok(None)
+            }
+            Err(error) => Err(error),
+        }
+    }
+
+    fn nested<T>(
+        &mut self,
+        span: Span,
+        evaluate: impl FnOnce(&mut Self) -> Result<T, RuntimeError>,
+    ) -> Result<T, RuntimeError> {
+        let limit = self.options.limits.max_depth;
+        if self.depth >= limit {
+            return Err(RuntimeError::new(
+                format!("nesting exceeds the limit of {limit} levels"),
+                span,
+            )
+            .with_code(ErrorCode::NestingTooDeep));
+        }
+        self.depth += 1;
+        let result = evaluate(self);
+        self.depth -= 1;
+        result
+    }
+
+    // Spreads copy whole values, so a few lines can otherwise describe an
+    // exponentially large result.
+    fn produce(&mut self, count: usize, span: Span) -> Result<(), RuntimeError> {
+        self.values = self.values.saturating_add(count);
+        let limit = self.options.limits.max_output_nodes;
+        if self.values > limit {
+            return Err(RuntimeError::new(
+                format!("evaluation exceeds the limit of {limit} values"),
+                span,
+            )
+            .with_code(ErrorCode::TooManyValues));
+        }
+        Ok(())
+    }
+
+    fn record_annotations(&mut self, key: &str, annotations: &[Annotation]) {
+        if annotations.is_empty() {
+            return;
+        }
+        for annotation in annotations {
+            if annotation.name == DEPRECATED_ANNOTATION {
+                let message = match annotation.string_arg(0) {
+                    Some(note) => format!("key '{key}' is deprecated: {note}"),
+                    None => format!("key '{key}' is deprecated"),
+                };
+                self.warnings.push(
+                    Warning::new(message, annotation.span).with_code(ErrorCode::DeprecatedKey),
+                );
+            }
+        }
+        self.annotations
+            .entry(self.path.clone())
+            .or_default()
+            .extend(annotations.iter().cloned());
+    }
+
+    // Keys written out explicitly never override each other; only a key that
+    // was brought in by a spread may be replaced, and only when the host
+    // opted in.
+    fn can_override(&self, key: &str, spread_keys: &HashSet<String>) -> bool {
+        self.options.allow_spread_overrides && spread_keys.contains(key)
+    }
+
+    fn resolve(&self, name: &str, span: Span) -> Result<&OrbitValue, RuntimeError> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name))
+            .ok_or_else(|| {
+                RuntimeError::new(format!("unknown reference '{name}'"), span)
+                    .with_code(ErrorCode::UnknownReference)
+            })
+    }
+
+    fn current_scope(&mut self) -> &mut Environment {
+        self.scopes
+            .last_mut()
+            .expect("evaluator always has an active scope")
+    }
+}
+
+fn duplicate(message: String, span: Span, first: Span) -> RuntimeError {
+    RuntimeError::new(message, span)
+        .with_code(ErrorCode::DuplicateKey)
+        .with_label(first, "first defined here")
+}
+
+fn value_count(value: &OrbitValue) -> usize {
+    match value {
+        OrbitValue::List(values) => 1 + values.iter().map(value_count).sum::<usize>(),
+        OrbitValue::Object(map) => 1 + map.values().map(value_count).sum::<usize>(),
+        OrbitValue::Secret(secret) => value_count(secret.expose()),
+        _ => 1,
+    }
+}
+
+fn seal_secret(annotations: &[Annotation], value: OrbitValue) -> OrbitValue {
+    if annotations
+        .iter()
+        .any(|annotation| annotation.name == SECRET_ANNOTATION)
+    {
+        OrbitValue::Secret(Secret::new(value))
+    } else {
+        value
+    }
+}
+
+fn merge_objects(target: &mut IndexMap<String, OrbitValue>, overlay: IndexMap<String, OrbitValue>) {
+    for (key, value) in overlay {
+        match target.get_mut(&key) {
+            Some(existing) => merge_value(existing, value),
+            None => {
+                target.insert(key, value);
+            }
+        }
+    }
+}
+
+fn merge_value(existing: &mut OrbitValue, overlay: OrbitValue) {
+    match (existing, overlay) {
+        (OrbitValue::Object(existing), OrbitValue::Object(nested)) => {
+            merge_objects(existing, nested)
+        }
+        // An overlay never unseals a secret of the base: whatever replaces
+        // or extends it stays inside the seal.
+        (OrbitValue::Secret(secret), overlay) => {
+            merge_value(secret.expose_mut(), overlay.reveal_secrets())
+        }
+        (existing, overlay) => *existing = overlay,
+    }
 }
@@ -1,5 +1,11 @@
 pub mod environment;
+pub mod evaluation;
 pub mod evaluator;
+pub mod options;
+pub mod tags;
 
 pub use self::environment::Environment;
+pub use self::evaluation::{Evaluation, EvaluationReport, ValuePath};
 pub use self::evaluator::Evaluator;
+pub use self::options::EvaluationOptions;
+pub use self::tags::TagRegistry;
@@ -0,0 +1,47 @@
+use crate::{ast::Span, error::RuntimeError, limits::Limits, value::OrbitValue};
+
+use super::tags::TagRegistry;
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct EvaluationOptions {
+    pub profiles: Vec<String>,
+    pub allow_spread_overrides: bool,
+    pub tags: TagRegistry,
+    pub limits: Limits,
+    // Reports every warning as an error instead, with the warning's code.
+    pub deny_warnings: bool,
+}
+
+impl EvaluationOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_profile(mut self, name: impl Into<String>) -> Self {
+        self.profiles.push(name.into());
+        self
+    }
+
+    pub fn with_tag<F>(mut self, tag: impl Into<String>, parser: F) -> Self
+    where
+        F: Fn(&str, Span) -> Result<OrbitValue, RuntimeError> + Send + Sync + 'static,
+    {
+        self.tags.register(tag, parser);
+        self
+    }
+
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    pub fn with_spread_overrides(mut self, allow: bool) -> Self {
+        self.allow_spread_overrides = allow;
+        self
+    }
+
+    pub fn with_deny_warnings(mut self, deny: bool) -> Self {
+        self.deny_warnings = deny;
+        self
+    }
+}
@@ -0,0 +1,139 @@
+use std::{fmt, net::IpAddr, sync::Arc};
+
+use indexmap::IndexMap;
+
+use crate::{
+    ast::Span,
+    error::{ErrorCode, RuntimeError},
+    lexer::unicode::normalize_identifier,
+    value::OrbitValue,
+};
+
+pub type TagParser = dyn Fn(&str, Span) -> Result<OrbitValue, RuntimeError> + Send + Sync;
+
+#[derive(Clone)]
+pub struct TagRegistry {
+    parsers: IndexMap<String, Arc<TagParser>>,
+}
+
+impl TagRegistry {
+    pub fn new() -> Self {
+        Self {
+            parsers: IndexMap::new(),
+        }
+    }
+
+    pub fn with_builtins() -> Self {
+        let mut registry = Self::new();
+        registry.register("ip", parse_ip);
+        registry.register("cidr", parse_cidr);
+        registry.register("url", parse_url);
+        registry.register("regex", parse_regex);
+        registry
+    }
+
+    pub fn register<F>(&mut self, tag: impl Into<String>, parser: F)
+    where
+        F: Fn(&str, Span) -> Result<OrbitValue, RuntimeError> + Send + Sync + 'static,
+    {
+        let tag = normalize_identifier(&tag.into());
+        self.parsers.insert(tag, Arc::new(parser));
+    }
+
+    pub fn get(&self, tag: &str) -> Option<&TagParser> {
+        self.parsers
+            .get(normalize_identifier(tag).as_str())
+            .map(Arc::as_ref)
+    }
+
+    pub fn contains(&self, tag: &str) -> bool {
+        self.parsers
+            .contains_key(normalize_identifier(tag).as_str())
+    }
+
+    pub fn tags(&self) -> impl Iterator<Item = &str> {
+        self.parsers.keys().map(String::as_str)
+    }
+}
+
+impl Default for TagRegistry {
+    fn default() -> Self {
+        Self::with_builtins()
+    }
+}
+
+// Parsers cannot be compared, so two registries are equal when they know
+// the same tags.
+impl PartialEq for TagRegistry {
+    fn eq(&self, other: &Self) -> bool {
+        self.parsers.len() == other.parsers.len() && self.tags().all(|tag| other.contains(tag))
+    }
+}
+
+impl Eq for TagRegistry {}
+
+impl fmt::Debug for TagRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.tags()).finish()
+    }
+}
+
+fn invalid(tag: &str, value: &str, reason: impl fmt::Display, span: Span) -> RuntimeError {
+    RuntimeError::new(format!("invalid {tag} literal \"{value}\": {reason}"), span)
+        .with_code(ErrorCode::InvalidTaggedLiteral)
+}
+
+fn parse_ip(value: &str, span: Span) -> Result<OrbitValue, RuntimeError> {
+    let addr: IpAddr = value
+        .parse()
+        .map_err(|err| invalid("ip", value, err, span))?;
+    Ok(OrbitValue::String(addr.to_string()))
+}
+
+fn parse_cidr(value: &str, span: Span) -> Result<OrbitValue, RuntimeError> {
+    let (addr, prefix) = value
+        .split_once('/')
+        .ok_or_else(|| invalid("cidr", value, "missing '/<prefix>'", span))?;
+    let addr: IpAddr = addr
+        .parse()
+        .map_err(|err| invalid("cidr", value, err, span))?;
+    let max_prefix = if addr.is_ipv4() { 32 } else { 128 };
+    // `str::parse` would also take a leading `+`.
+    let prefix: u32 = Some(prefix)
+        .filter(|prefix| !prefix.is_empty() && prefix.bytes().all(|byte| byte.is_ascii_digit()))
+        .and_then(|prefix| prefix.parse().ok())
+        .filter(|prefix| *prefix <= max_prefix)
+        .ok_or_else(|| {
+            invalid(
+                "cidr",
+                value,
+                format!("prefix must be between 0 and {max_prefix}"),
+                span,
+            )
+        })?;
+    let host_bits = match addr {
+        IpAddr::V4(addr) => u128::from(addr.to_bits() & u32::MAX.checked_shr(prefix).unwrap_or(0)),
+        IpAddr::V6(addr) => addr.to_bits() & u128::MAX.checked_shr(prefix).unwrap_or(0),
+    };
+    if host_bits != 0 {
+        return Err(invalid(
+            "cidr",
+            value,
+            format!("address has bits set beyond the /{prefix} prefix"),
+            span,
+        ));
+    }
+    Ok(OrbitValue::String(format!("{addr}/{prefix}")))
+}
+
+fn parse_url(value: &str, span: Span) -> Result<OrbitValue, RuntimeError> {
+    let url = url::Url::parse(value).map_err(|err| invalid("url", value, err, span))?;
+    Ok(OrbitValue::String(url.into()))
+}
+
+fn parse_regex(value: &str, span: Span) -> Result<OrbitValue, RuntimeError> {
+    regex_syntax::Parser::new()
+        .parse(value)
+        .map_err(|err| invalid("regex", value, err, span))?;
+    Ok(OrbitValue::String(value.to_string()))
+}
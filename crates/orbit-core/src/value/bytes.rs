@@ -0,0 +1,42 @@
+use base64::{Engine, engine::general_purpose::STANDARD};
+use serde::Serializer;
+
+pub fn to_base64(bytes: &[u8]) -> String {
+    STANDARD.encode(bytes)
+}
+
+// Whitespace is ignored by both decoders so long values can be grouped.
+pub fn from_base64(encoded: &str) -> Option<Vec<u8>> {
+    let compact: String = encoded.split_whitespace().collect();
+    STANDARD.decode(compact).ok()
+}
+
+pub fn from_hex(encoded: &str) -> Option<Vec<u8>> {
+    let digits: Vec<u8> = encoded
+        .split_whitespace()
+        .flat_map(str::chars)
+        .map(|ch| ch.to_digit(16).map(|digit| digit as u8))
+        .collect::<Option<_>>()?;
+    if !digits.len().is_multiple_of(2) {
+        return None;
+    }
+    Some(
+        digits
+            .chunks(2)
+            .map(|pair| (pair[0] << 4) | pair[1])
+            .collect(),
+    )
+}
+
+// Text formats get a base64 string, binary formats such as MessagePack get a
+// native byte string (`bin`).
+pub fn serialize<S>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    if serializer.is_human_readable() {
+        serializer.serialize_str(&to_base64(bytes))
+    } else {
+        serializer.serialize_bytes(bytes)
+    }
+}
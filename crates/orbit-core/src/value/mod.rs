@@ -1,5 +1,8 @@
+pub mod bytes;
 mod model;
 pub mod number;
+pub mod secret;
 
 pub use self::model::OrbitValue;
 pub use self::number::OrbitNumber;
+pub use self::secret::Secret;
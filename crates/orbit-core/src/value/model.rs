@@ -1,19 +1,59 @@
 use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 
-use super::number::OrbitNumber;
+use super::{bytes, number::OrbitNumber, secret::Secret};
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum OrbitValue {
     String(String),
+    #[serde(serialize_with = "bytes::serialize", skip_deserializing)]
+    Bytes(Vec<u8>),
     Number(OrbitNumber),
     Bool(bool),
     List(Vec<OrbitValue>),
     Object(IndexMap<String, OrbitValue>),
+    #[serde(skip_deserializing)]
+    Secret(Secret),
+    // Only produced by best-effort evaluation, in place of a list item that
+    // failed, so the items after it keep their index.
+    #[serde(skip_deserializing)]
+    Null,
 }
 
 impl OrbitValue {
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            OrbitValue::String(_) => "string",
+            OrbitValue::Bytes(_) => "bytes",
+            OrbitValue::Number(_) => "number",
+            OrbitValue::Bool(_) => "boolean",
+            OrbitValue::List(_) => "list",
+            OrbitValue::Object(_) => "object",
+            OrbitValue::Secret(_) => "secret",
+            OrbitValue::Null => "null",
+        }
+    }
+
+    pub fn is_secret(&self) -> bool {
+        matches!(self, OrbitValue::Secret(_))
+    }
+
+    pub fn reveal_secrets(self) -> OrbitValue {
+        match self {
+            OrbitValue::Secret(secret) => secret.into_exposed().reveal_secrets(),
+            OrbitValue::List(values) => {
+                OrbitValue::List(values.into_iter().map(Self::reveal_secrets).collect())
+            }
+            OrbitValue::Object(map) => OrbitValue::Object(
+                map.into_iter()
+                    .map(|(key, value)| (key, value.reveal_secrets()))
+                    .collect(),
+            ),
+            other => other,
+        }
+    }
+
     pub fn as_object(&self) -> Option<&IndexMap<String, OrbitValue>> {
         match self {
             OrbitValue::Object(map) => Some(map),
@@ -39,3 +79,39 @@ impl OrbitValue {
         Some(current)
     }
 }
+
+impl From<&str> for OrbitValue {
+    fn from(value: &str) -> Self {
+        OrbitValue::String(value.to_string())
+    }
+}
+
+impl From<String> for OrbitValue {
+    fn from(value: String) -> Self {
+        OrbitValue::String(value)
+    }
+}
+
+impl From<i64> for OrbitValue {
+    fn from(value: i64) -> Self {
+        OrbitValue::Number(OrbitNumber::Integer(value))
+    }
+}
+
+impl From<f64> for OrbitValue {
+    fn from(value: f64) -> Self {
+        OrbitValue::Number(OrbitNumber::Float(value))
+    }
+}
+
+impl From<bool> for OrbitValue {
+    fn from(value: bool) -> Self {
+        OrbitValue::Bool(value)
+    }
+}
+
+impl From<Vec<OrbitValue>> for OrbitValue {
+    fn from(values: Vec<OrbitValue>) -> Self {
+        OrbitValue::List(values)
+    }
+}
@@ -0,0 +1,43 @@
+use std::fmt;
+
+use serde::{Serialize, Serializer};
+
+use super::model::OrbitValue;
+
+pub const REDACTED: &str = "***";
+
+#[derive(Clone, PartialEq)]
+pub struct Secret(Box<OrbitValue>);
+
+impl Secret {
+    pub fn new(value: OrbitValue) -> Self {
+        Self(Box::new(value))
+    }
+
+    pub fn expose(&self) -> &OrbitValue {
+        &self.0
+    }
+
+    pub(crate) fn expose_mut(&mut self) -> &mut OrbitValue {
+        &mut self.0
+    }
+
+    pub fn into_exposed(self) -> OrbitValue {
+        *self.0
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(REDACTED, f)
+    }
+}
+
+impl Serialize for Secret {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(REDACTED)
+    }
+}
@@ -0,0 +1,65 @@
+use std::{fmt, str::FromStr};
+
+use serde::Serialize;
+
+pub const PRAGMA_PREFIX: &str = "#!orbit";
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+pub enum LanguageVersion {
+    #[serde(rename = "0.1")]
+    V0_1,
+    #[serde(rename = "0.2")]
+    V0_2,
+    #[default]
+    #[serde(rename = "0.3")]
+    V0_3,
+}
+
+impl LanguageVersion {
+    pub const LATEST: LanguageVersion = LanguageVersion::V0_3;
+
+    pub const ALL: [LanguageVersion; 3] = [
+        LanguageVersion::V0_1,
+        LanguageVersion::V0_2,
+        LanguageVersion::V0_3,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LanguageVersion::V0_1 => "0.1",
+            LanguageVersion::V0_2 => "0.2",
+            LanguageVersion::V0_3 => "0.3",
+        }
+    }
+
+    // Reads the `#!orbit <version>` pragma, which is only recognized on the
+    // very first line. `Ok(None)` means the source does not declare a version.
+    pub fn from_pragma(source: &str) -> Result<Option<Self>, String> {
+        let first_line = source.lines().next().unwrap_or_default();
+        let Some(declared) = first_line.strip_prefix(PRAGMA_PREFIX) else {
+            return Ok(None);
+        };
+        let declared = declared.trim();
+        declared
+            .parse()
+            .map(Some)
+            .map_err(|_| format!("unsupported orbit language version '{declared}'"))
+    }
+}
+
+impl fmt::Display for LanguageVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for LanguageVersion {
+    type Err = ();
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|version| version.as_str() == value)
+            .ok_or(())
+    }
+}
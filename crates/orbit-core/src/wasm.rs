@@ -1,10 +1,10 @@
 #![cfg(target_arch = "wasm32")]
 
 use crate::ast::{AstNode, Span};
-use crate::error::{CoreError, LexError, ParseError, RuntimeError};
+use crate::engine::Engine;
+use crate::error::{CoreError, ErrorCode, LexError, ParseError, RuntimeError};
 use crate::serializer::{to_json_string, to_json_string_pretty, to_msgpack_bytes, to_yaml_string};
 use crate::value::OrbitValue;
-use crate::{evaluate, evaluate_ast, parse, parse_with_recovery};
 use serde::Serialize;
 use std::mem;
 use std::ptr;
@@ -21,9 +21,12 @@ pub(crate) struct OrbitSlice {
     cap: usize,
 }
 
+// `code` is the stable `ErrorCode` of language errors and `null` for
+// failures of the bridge itself (bad input, serialization).
 #[derive(Serialize)]
 struct JsError {
     kind: &'static str,
+    code: Option<ErrorCode>,
     message: String,
     span: Span,
 }
@@ -32,11 +35,17 @@ impl JsError {
     fn new(kind: &'static str, message: impl Into<String>, span: Span) -> Self {
         Self {
             kind,
+            code: None,
             message: message.into(),
             span,
         }
     }
 
+    fn with_code(mut self, code: ErrorCode) -> Self {
+        self.code = Some(code);
+        self
+    }
+
     fn from_core(error: CoreError) -> Self {
         match error {
             CoreError::Lex(err) => err.into(),
@@ -60,19 +69,19 @@ impl JsError {
 
 impl From<LexError> for JsError {
     fn from(error: LexError) -> Self {
-        JsError::new("Lex", error.message, error.span)
+        JsError::new("Lex", error.message, error.span).with_code(error.code)
     }
 }
 
 impl From<ParseError> for JsError {
     fn from(error: ParseError) -> Self {
-        JsError::new("Parse", error.message, error.span)
+        JsError::new("Parse", error.message, error.span).with_code(error.code)
     }
 }
 
 impl From<RuntimeError> for JsError {
     fn from(error: RuntimeError) -> Self {
-        JsError::new("Runtime", error.message, error.span)
+        JsError::new("Runtime", error.message, error.span).with_code(error.code)
     }
 }
 
@@ -110,7 +119,7 @@ pub extern "C" fn orbit_parse(
     result_ptr: *mut OrbitSlice,
 ) -> i32 {
     match read_source(source_ptr, source_len) {
-        Ok(source) => match parse(source) {
+        Ok(source) => match Engine::new().parse(source) {
             Ok(ast) => write_json(result_ptr, &ast),
             Err(err) => write_error(result_ptr, JsError::from_core(err)),
         },
@@ -125,10 +134,7 @@ pub extern "C" fn orbit_parse_with_recovery(
     result_ptr: *mut OrbitSlice,
 ) -> i32 {
     match read_source(source_ptr, source_len) {
-        Ok(source) => match parse_with_recovery(source) {
-            Ok(report) => write_json(result_ptr, &report),
-            Err(err) => write_error(result_ptr, JsError::from_core(err)),
-        },
+        Ok(source) => write_json(result_ptr, &Engine::new().parse_with_recovery(source)),
         Err(err) => write_error(result_ptr, err),
     }
 }
@@ -140,7 +146,7 @@ pub extern "C" fn orbit_evaluate(
     result_ptr: *mut OrbitSlice,
 ) -> i32 {
     match read_source(source_ptr, source_len) {
-        Ok(source) => match evaluate(source) {
+        Ok(source) => match Engine::new().evaluate(source) {
             Ok(value) => write_json(result_ptr, &value),
             Err(err) => write_error(result_ptr, JsError::from_core(err)),
         },
@@ -155,7 +161,7 @@ pub extern "C" fn orbit_evaluate_ast(
     result_ptr: *mut OrbitSlice,
 ) -> i32 {
     match read_source(ast_ptr, ast_len).and_then(|json| deserialize_ast(json)) {
-        Ok(ast) => match evaluate_ast(&ast) {
+        Ok(ast) => match Engine::new().evaluate_ast(&ast) {
             Ok(value) => write_json(result_ptr, &value),
             Err(err) => write_error(result_ptr, JsError::from(err)),
         },
@@ -0,0 +1,785 @@
+use std::fmt::{self, Write};
+
+use orbit_core::{
+    ast::{AstNode, Span, ValueNode},
+    cst::{SyntaxKind, SyntaxNode, SyntaxToken, SyntaxTree},
+    error::CoreError,
+    lexer::{BASE64_TAG, is_identifier, string_value, unicode::normalize_identifier},
+    value::{OrbitNumber, OrbitValue, bytes::to_base64},
+    version::LanguageVersion,
+};
+
+const DEFAULT_INDENT: &str = "    ";
+const SECRET_ANNOTATION: &str = "secret";
+
+#[derive(Debug, thiserror::Error)]
+pub enum EditError {
+    #[error(transparent)]
+    Core(#[from] CoreError),
+    #[error("edit paths must not be empty")]
+    EmptyPath,
+    #[error("nothing found at '{0}'")]
+    NotFound(String),
+    #[error("'{0}' already exists")]
+    AlreadyExists(String),
+    #[error("'{0}' is a block, not a value")]
+    NotAValue(String),
+    #[error("'{0}' is neither a block nor an object literal")]
+    NotAContainer(String),
+    #[error("'{0}' cannot be used as a key here")]
+    InvalidKey(String),
+    #[error("{0} cannot be written as an orbit literal")]
+    UnsupportedValue(String),
+    #[error("the source has no document {0}")]
+    DocumentNotFound(usize),
+    #[error("profile '{0}' does not exist")]
+    ProfileNotFound(String),
+}
+
+// Where a path starts: the top level of a document, or one of its profile
+// sections. The default is the top level of the first document.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Scope<'a> {
+    document: usize,
+    profile: Option<&'a str>,
+}
+
+impl<'a> Scope<'a> {
+    pub fn document(index: usize) -> Self {
+        Self {
+            document: index,
+            profile: None,
+        }
+    }
+
+    pub fn with_profile(mut self, name: &'a str) -> Self {
+        self.profile = Some(name);
+        self
+    }
+}
+
+// A parsed source that can be edited in place. Every edit is spliced into
+// the original text at the positions of the syntax tree's nodes, so bytes
+// outside the edited ranges (comments, blank lines, spacing) are left
+// exactly as they were.
+#[derive(Debug, Clone)]
+pub struct Document {
+    source: String,
+    tree: SyntaxTree,
+}
+
+impl Document {
+    pub fn parse(source: &str) -> Result<Self, EditError> {
+        let tree = SyntaxTree::parse(source);
+        if let Some(error) = tree.errors().first() {
+            return Err(error.clone().into());
+        }
+        Ok(Self {
+            source: source.to_string(),
+            tree,
+        })
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    pub fn tree(&self) -> &SyntaxTree {
+        &self.tree
+    }
+
+    pub fn documents(&self) -> &[AstNode] {
+        self.tree.documents()
+    }
+
+    pub fn contains(&self, path: &[&str]) -> bool {
+        self.contains_in(Scope::default(), path)
+    }
+
+    pub fn contains_in(&self, scope: Scope<'_>, path: &[&str]) -> bool {
+        self.find(scope, path).is_some()
+    }
+
+    pub fn get(&self, path: &[&str]) -> Option<&ValueNode> {
+        self.get_in(Scope::default(), path)
+    }
+
+    pub fn get_in(&self, scope: Scope<'_>, path: &[&str]) -> Option<&ValueNode> {
+        let span = self.find(scope, path)?.value()?.span();
+        value_at(self.tree.documents(), span)
+    }
+
+    // Replaces the value at `path`, or inserts a new entry when the parent
+    // exists but the key does not.
+    pub fn set(&mut self, path: &[&str], value: impl Into<OrbitValue>) -> Result<(), EditError> {
+        self.set_in(Scope::default(), path, value)
+    }
+
+    pub fn set_in(
+        &mut self,
+        scope: Scope<'_>,
+        path: &[&str],
+        value: impl Into<OrbitValue>,
+    ) -> Result<(), EditError> {
+        let value = value.into();
+        let Some(item) = self.find(scope, path) else {
+            return self.insert_entry(scope, path, &value);
+        };
+        let target = item
+            .value()
+            .ok_or_else(|| EditError::NotAValue(display_path(path)))?;
+        let (sealed, value) = unseal(&value);
+        let mut edits = vec![(target.span(), render_value(value, self.version())?)];
+        // Annotations on the entry stay as they are; a secret only adds
+        // `@secret` when the entry is not sealed yet.
+        if sealed && !item.has_annotation(SECRET_ANNOTATION) {
+            self.require_annotations()?;
+            if let Some(key) = item.key() {
+                let at = key.span().start;
+                edits.push((Span::new(at, at), format!("@{SECRET_ANNOTATION} ")));
+            }
+        }
+        self.apply(edits)
+    }
+
+    pub fn insert(&mut self, path: &[&str], value: impl Into<OrbitValue>) -> Result<(), EditError> {
+        self.insert_in(Scope::default(), path, value)
+    }
+
+    pub fn insert_in(
+        &mut self,
+        scope: Scope<'_>,
+        path: &[&str],
+        value: impl Into<OrbitValue>,
+    ) -> Result<(), EditError> {
+        if self.contains_in(scope, path) {
+            return Err(EditError::AlreadyExists(display_path(path)));
+        }
+        self.insert_entry(scope, path, &value.into())
+    }
+
+    pub fn create_block(&mut self, path: &[&str]) -> Result<(), EditError> {
+        self.create_block_in(Scope::default(), path)
+    }
+
+    pub fn create_block_in(&mut self, scope: Scope<'_>, path: &[&str]) -> Result<(), EditError> {
+        let (parent, key) = split_path(path)?;
+        if self.contains_in(scope, path) {
+            return Err(EditError::AlreadyExists(display_path(path)));
+        }
+        let container = self.container(scope, parent)?;
+        if matches!(container, Container::Object { .. }) {
+            return Err(EditError::InvalidKey(display_path(path)));
+        }
+        let text = format!("{} {{}}", self.entry_key(&container, key, path)?);
+        let edits = self.insertion(&container, text);
+        self.apply(edits)
+    }
+
+    // Adds an empty profile section at the end of a document.
+    pub fn create_profile(&mut self, document: usize, name: &str) -> Result<(), EditError> {
+        if self.version() < LanguageVersion::V0_2 {
+            return Err(EditError::UnsupportedValue(format!(
+                "profile sections in orbit {}",
+                self.version()
+            )));
+        }
+        if !is_identifier(name, self.version()) {
+            return Err(EditError::InvalidKey(name.to_string()));
+        }
+        let node = self.document_node(document)?;
+        if profile_node(node, name).is_some() {
+            return Err(EditError::AlreadyExists(format!("profile {name}")));
+        }
+        let container = self.document_container(node);
+        let edits = self.insertion(&container, format!("profile {name} {{}}"));
+        self.apply(edits)
+    }
+
+    pub fn remove(&mut self, path: &[&str]) -> Result<(), EditError> {
+        self.remove_in(Scope::default(), path)
+    }
+
+    pub fn remove_in(&mut self, scope: Scope<'_>, path: &[&str]) -> Result<(), EditError> {
+        split_path(path)?;
+        let item = self
+            .find(scope, path)
+            .ok_or_else(|| EditError::NotFound(display_path(path)))?;
+        let edits = self.removal(&item);
+        self.apply(edits)
+    }
+
+    fn version(&self) -> LanguageVersion {
+        self.tree.version()
+    }
+
+    fn require_annotations(&self) -> Result<(), EditError> {
+        if self.version() < LanguageVersion::V0_2 {
+            return Err(EditError::UnsupportedValue(format!(
+                "secrets in orbit {}",
+                self.version()
+            )));
+        }
+        Ok(())
+    }
+
+    fn insert_entry(
+        &mut self,
+        scope: Scope<'_>,
+        path: &[&str],
+        value: &OrbitValue,
+    ) -> Result<(), EditError> {
+        let (parent, key) = split_path(path)?;
+        let container = self.container(scope, parent)?;
+        let (sealed, value) = unseal(value);
+        let mut text = String::new();
+        if sealed {
+            self.require_annotations()?;
+            let _ = write!(text, "@{SECRET_ANNOTATION} ");
+        }
+        let _ = write!(
+            text,
+            "{}: {}",
+            self.entry_key(&container, key, path)?,
+            render_value(value, self.version())?
+        );
+        let edits = self.insertion(&container, text);
+        self.apply(edits)
+    }
+
+    fn find(&self, scope: Scope<'_>, path: &[&str]) -> Option<Item<'_>> {
+        let (parent, key) = split_path(path).ok()?;
+        self.container(scope, parent).ok()?.lookup(key)
+    }
+
+    fn document_node(&self, index: usize) -> Result<SyntaxNode<'_>, EditError> {
+        self.tree
+            .root()
+            .child_nodes()
+            .filter(|node| node.kind() == SyntaxKind::Document)
+            .nth(index)
+            .ok_or(EditError::DocumentNotFound(index))
+    }
+
+    // The top level of a document ends where the separator of the next one
+    // starts; the last document ends with the source.
+    fn document_container<'t>(&'t self, node: SyntaxNode<'t>) -> Container<'t> {
+        let separator = self
+            .tree
+            .root()
+            .child_tokens()
+            .find(|token| {
+                token.kind() == SyntaxKind::DocumentSeparator
+                    && token.span().start >= node.span().end
+            })
+            .map(|token| token.span().start);
+        Container::Document { node, separator }
+    }
+
+    fn container(&self, scope: Scope<'_>, path: &[&str]) -> Result<Container<'_>, EditError> {
+        let node = self.document_node(scope.document)?;
+        let mut container = match scope.profile {
+            Some(name) => profile_node(node, name)
+                .and_then(Container::braced)
+                .ok_or_else(|| EditError::ProfileNotFound(name.to_string()))?,
+            None => self.document_container(node),
+        };
+        for (depth, key) in path.iter().enumerate() {
+            container = container
+                .lookup(key)
+                .ok_or_else(|| EditError::NotFound(display_path(&path[..=depth])))?
+                .container()
+                .ok_or_else(|| EditError::NotAContainer(display_path(&path[..=depth])))?;
+        }
+        Ok(container)
+    }
+
+    fn entry_key(
+        &self,
+        container: &Container<'_>,
+        key: &str,
+        path: &[&str],
+    ) -> Result<String, EditError> {
+        if is_identifier(key, self.version()) {
+            Ok(key.to_string())
+        } else if matches!(container, Container::Object { .. })
+            && self.version() >= LanguageVersion::V0_2
+        {
+            Ok(quote(key))
+        } else {
+            Err(EditError::InvalidKey(display_path(path)))
+        }
+    }
+
+    fn insertion(&self, container: &Container<'_>, text: String) -> Vec<(Span, String)> {
+        let newline = self.newline();
+        match *container {
+            Container::Document {
+                separator: Some(at),
+                ..
+            } => vec![(Span::new(at, at), format!("{text}{newline}"))],
+            Container::Document {
+                separator: None, ..
+            } => {
+                let end = self.source.len();
+                let separator = if end > 0 && !self.source.ends_with('\n') {
+                    newline
+                } else {
+                    ""
+                };
+                vec![(Span::new(end, end), format!("{separator}{text}{newline}"))]
+            }
+            Container::Body { open, close, .. } | Container::Object { open, close, .. } => {
+                let object = matches!(container, Container::Object { .. });
+                match container.items().last() {
+                    Some(last) => self.append_after(last.span(), close, text, object),
+                    None => self.fill_empty(open, close, text, object),
+                }
+            }
+        }
+    }
+
+    // Adds `text` after the last item of a non-empty container, on a new line
+    // with the same indentation when the container spans several lines.
+    fn append_after(
+        &self,
+        last: Span,
+        close: usize,
+        text: String,
+        comma: bool,
+    ) -> Vec<(Span, String)> {
+        let between = &self.source[last.end..close];
+        let trailing_comma = comma
+            .then(|| between.find(|ch: char| ch != ' ' && ch != '\t'))
+            .flatten()
+            .filter(|offset| between[*offset..].starts_with(','))
+            .map(|offset| last.end + offset + 1);
+        let Some(line_break) = between.find(['\r', '\n']) else {
+            return match trailing_comma {
+                Some(after) => vec![(Span::new(after, after), format!(" {text},"))],
+                None if comma => vec![(Span::new(last.end, last.end), format!(", {text}"))],
+                None => vec![(Span::new(last.end, last.end), format!(" {text}"))],
+            };
+        };
+        let line_end = last.end + line_break;
+        let indent = self.line_indent(last.start);
+        let newline = self.newline();
+        let mut edits = Vec::new();
+        if comma && trailing_comma.is_none() {
+            edits.push((Span::new(last.end, last.end), ",".to_string()));
+        }
+        let suffix = if trailing_comma.is_some() { "," } else { "" };
+        edits.push((
+            Span::new(line_end, line_end),
+            format!("{newline}{indent}{text}{suffix}"),
+        ));
+        edits
+    }
+
+    fn fill_empty(
+        &self,
+        open: usize,
+        close: usize,
+        text: String,
+        object: bool,
+    ) -> Vec<(Span, String)> {
+        let indent = self.line_indent(open);
+        let unit = self.indent_unit();
+        let newline = self.newline();
+        let inner = &self.source[open + 1..close];
+        if inner.contains('\n') {
+            vec![(
+                Span::new(open + 1, open + 1),
+                format!("{newline}{indent}{unit}{text}"),
+            )]
+        } else if object {
+            vec![(Span::new(open + 1, close), format!(" {text} "))]
+        } else {
+            vec![(
+                Span::new(open + 1, close),
+                format!("{newline}{indent}{unit}{text}{newline}{indent}"),
+            )]
+        }
+    }
+
+    // Removes an item together with its doc comments, annotations, separating
+    // comma and, when it sits on lines of its own, those whole lines.
+    fn removal(&self, item: &Item<'_>) -> Vec<(Span, String)> {
+        let span = item.span();
+        let bytes = self.source.as_bytes();
+        let skip_blanks = |mut offset: usize| {
+            while offset < bytes.len() && matches!(bytes[offset], b' ' | b'\t') {
+                offset += 1;
+            }
+            offset
+        };
+
+        let mut end = skip_blanks(span.end);
+        let followed_by_comma = item.is_field() && bytes.get(end) == Some(&b',');
+        if followed_by_comma {
+            end = skip_blanks(end + 1);
+        }
+
+        let line_start = self.line_start(span.start);
+        let owns_line_start = self.source[line_start..span.start].trim().is_empty();
+        let rest_of_line = self.source[end..]
+            .split_inclusive('\n')
+            .next()
+            .unwrap_or_default();
+        let owns_line_end = rest_of_line.trim().is_empty() || rest_of_line.starts_with('#');
+        if owns_line_start && owns_line_end {
+            let end = end + rest_of_line.len();
+            return vec![(Span::new(line_start, end), String::new())];
+        }
+
+        if item.is_field() && !followed_by_comma {
+            let before = self.source[..span.start].trim_end();
+            if before.ends_with(',') {
+                return vec![(Span::new(before.len() - 1, span.end), String::new())];
+            }
+        }
+        let end = if owns_line_end { span.end } else { end };
+        vec![(Span::new(span.start, end), String::new())]
+    }
+
+    // Splices all edits into a copy of the source and only commits the result
+    // when it still parses.
+    fn apply(&mut self, mut edits: Vec<(Span, String)>) -> Result<(), EditError> {
+        edits.sort_by_key(|(span, _)| std::cmp::Reverse(span.start));
+        let mut source = self.source.clone();
+        for (span, text) in edits {
+            source.replace_range(span.start..span.end, &text);
+        }
+        *self = Document::parse(&source)?;
+        Ok(())
+    }
+
+    fn newline(&self) -> &'static str {
+        if self.source.contains("\r\n") {
+            "\r\n"
+        } else {
+            "\n"
+        }
+    }
+
+    fn line_start(&self, offset: usize) -> usize {
+        self.source[..offset]
+            .rfind('\n')
+            .map_or(0, |index| index + 1)
+    }
+
+    fn line_indent(&self, offset: usize) -> &str {
+        let line = &self.source[self.line_start(offset)..];
+        let width = line.len() - line.trim_start_matches([' ', '\t']).len();
+        &line[..width]
+    }
+
+    // Reuses the indentation of the first indented line so inserted entries
+    // match files that indent with tabs or two spaces.
+    fn indent_unit(&self) -> &str {
+        self.source
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| &line[..line.len() - line.trim_start_matches([' ', '\t']).len()])
+            .find(|indent| !indent.is_empty())
+            .unwrap_or(DEFAULT_INDENT)
+    }
+}
+
+impl fmt::Display for Document {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+// `open` and `close` are the offsets of the braces around a block, profile,
+// or object body.
+#[derive(Clone, Copy)]
+enum Container<'t> {
+    Document {
+        node: SyntaxNode<'t>,
+        separator: Option<usize>,
+    },
+    Body {
+        node: SyntaxNode<'t>,
+        open: usize,
+        close: usize,
+    },
+    Object {
+        node: SyntaxNode<'t>,
+        open: usize,
+        close: usize,
+    },
+}
+
+impl<'t> Container<'t> {
+    fn braced(node: SyntaxNode<'t>) -> Option<Self> {
+        let brace = |kind| {
+            node.child_tokens()
+                .find(|token| token.kind() == kind)
+                .map(|token| token.span().start)
+        };
+        let (open, close) = (brace(SyntaxKind::LBrace)?, brace(SyntaxKind::RBrace)?);
+        Some(match node.kind() {
+            SyntaxKind::Object => Container::Object { node, open, close },
+            _ => Container::Body { node, open, close },
+        })
+    }
+
+    // Entries and blocks of a body, or the keyed entries of an object.
+    // Profile sections are only reached through a `Scope`.
+    fn items(&self) -> impl Iterator<Item = Item<'t>> + use<'t> {
+        let (node, field) = match *self {
+            Container::Document { node, .. } | Container::Body { node, .. } => (node, false),
+            Container::Object { node, .. } => (node, true),
+        };
+        node.child_nodes()
+            .filter(|child| {
+                matches!(
+                    child.kind(),
+                    SyntaxKind::Entry | SyntaxKind::Block | SyntaxKind::ObjectEntry
+                )
+            })
+            .map(move |node| Item { node, field })
+            .filter(|item| item.key().is_some())
+    }
+
+    // Keys are compared in NFC, as the evaluator compares them.
+    fn lookup(&self, key: &str) -> Option<Item<'t>> {
+        let key = normalize_identifier(key);
+        self.items()
+            .find(|item| item.name().as_deref() == Some(&key))
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Item<'t> {
+    node: SyntaxNode<'t>,
+    field: bool,
+}
+
+impl<'t> Item<'t> {
+    fn key(&self) -> Option<SyntaxToken<'t>> {
+        significant_tokens(self.node)
+            .next()
+            .filter(|token| matches!(token.kind(), SyntaxKind::Ident | SyntaxKind::String))
+    }
+
+    fn name(&self) -> Option<String> {
+        let key = self.key()?;
+        Some(match key.kind() {
+            SyntaxKind::String => normalize_identifier(&string_value(key.text())),
+            _ => normalize_identifier(key.text()),
+        })
+    }
+
+    // The value is the last node of an entry; a block has none.
+    fn value(&self) -> Option<SyntaxNode<'t>> {
+        if self.node.kind() == SyntaxKind::Block {
+            return None;
+        }
+        self.node
+            .child_nodes()
+            .last()
+            .filter(|child| child.kind() != SyntaxKind::Annotation)
+    }
+
+    fn container(&self) -> Option<Container<'t>> {
+        match self.node.kind() {
+            SyntaxKind::Block => Container::braced(self.node),
+            _ => self
+                .value()
+                .filter(|value| value.kind() == SyntaxKind::Object)
+                .and_then(Container::braced),
+        }
+    }
+
+    fn has_annotation(&self, name: &str) -> bool {
+        self.node
+            .child_nodes()
+            .filter(|child| child.kind() == SyntaxKind::Annotation)
+            .filter_map(|annotation| significant_tokens(annotation).nth(1))
+            .any(|token| normalize_identifier(token.text()) == name)
+    }
+
+    // The node covers the item's doc comments and annotations too.
+    fn span(&self) -> Span {
+        self.node.span()
+    }
+
+    fn is_field(&self) -> bool {
+        self.field
+    }
+}
+
+fn significant_tokens<'t>(node: SyntaxNode<'t>) -> impl Iterator<Item = SyntaxToken<'t>> {
+    node.child_tokens()
+        .filter(|token| !token.kind().is_trivia())
+}
+
+fn profile_node<'t>(document: SyntaxNode<'t>, name: &str) -> Option<SyntaxNode<'t>> {
+    let name = normalize_identifier(name);
+    document
+        .child_nodes()
+        .filter(|node| node.kind() == SyntaxKind::Profile)
+        .find(|node| {
+            significant_tokens(*node)
+                .nth(1)
+                .is_some_and(|token| normalize_identifier(token.text()) == name)
+        })
+}
+
+// The AST value that was derived from the syntax node at `span`.
+fn value_at(nodes: &[AstNode], span: Span) -> Option<&ValueNode> {
+    nodes.iter().find_map(|node| match node {
+        AstNode::Entry { value, .. } => value_in(value, span),
+        AstNode::Document { body, .. }
+        | AstNode::Block { body, .. }
+        | AstNode::Profile { body, .. } => value_at(body, span),
+        _ => None,
+    })
+}
+
+fn value_in(value: &ValueNode, span: Span) -> Option<&ValueNode> {
+    let own = value.span();
+    if span.start < own.start || span.end > own.end {
+        return None;
+    }
+    if own == span {
+        return Some(value);
+    }
+    match value {
+        ValueNode::List { items, .. } => items.iter().find_map(|item| value_in(item, span)),
+        ValueNode::Object { entries, .. } => entries
+            .iter()
+            .find_map(|entry| value_in(&entry.value, span)),
+        _ => None,
+    }
+}
+
+fn split_path<'p>(path: &'p [&'p str]) -> Result<(&'p [&'p str], &'p str), EditError> {
+    match path.split_last() {
+        Some((key, parent)) => Ok((parent, key)),
+        None => Err(EditError::EmptyPath),
+    }
+}
+
+fn display_path(path: &[&str]) -> String {
+    path.join(".")
+}
+
+// `@secret` seals the value of the entry it annotates, so a secret is
+// written as its exposed value behind that annotation.
+fn unseal(value: &OrbitValue) -> (bool, &OrbitValue) {
+    match value {
+        OrbitValue::Secret(secret) => (true, secret.expose()),
+        value => (false, value),
+    }
+}
+
+fn render_value(value: &OrbitValue, version: LanguageVersion) -> Result<String, EditError> {
+    let mut output = String::new();
+    write_value(&mut output, value, version)?;
+    Ok(output)
+}
+
+fn write_value(
+    output: &mut String,
+    value: &OrbitValue,
+    version: LanguageVersion,
+) -> Result<(), EditError> {
+    match value {
+        OrbitValue::String(value) => output.push_str(&quote(value)),
+        OrbitValue::Bytes(bytes) => {
+            if version < LanguageVersion::V0_2 {
+                return Err(EditError::UnsupportedValue(format!(
+                    "bytes in orbit {version}"
+                )));
+            }
+            let _ = write!(output, "{}\"{}\"", BASE64_TAG, to_base64(bytes));
+        }
+        OrbitValue::Number(OrbitNumber::Integer(value)) => {
+            let _ = write!(output, "{value}");
+        }
+        OrbitValue::Number(OrbitNumber::Float(value)) => {
+            if !value.is_finite() {
+                return Err(EditError::UnsupportedValue(value.to_string()));
+            }
+            // Number literals have no exponent form, and a float without a
+            // fractional part would read back as an integer.
+            let text = value.to_string();
+            output.push_str(&text);
+            if !text.contains('.') {
+                output.push_str(".0");
+            }
+        }
+        OrbitValue::Bool(value) => output.push_str(if *value { "true" } else { "false" }),
+        OrbitValue::List(items) => {
+            output.push('[');
+            for (index, item) in items.iter().enumerate() {
+                if index > 0 {
+                    output.push_str(", ");
+                }
+                write_value(output, item, version)?;
+            }
+            output.push(']');
+        }
+        OrbitValue::Object(map) => {
+            if map.is_empty() {
+                output.push_str("{}");
+                return Ok(());
+            }
+            output.push_str("{ ");
+            for (index, (key, value)) in map.iter().enumerate() {
+                if index > 0 {
+                    output.push_str(", ");
+                }
+                let (sealed, value) = unseal(value);
+                if sealed {
+                    if version < LanguageVersion::V0_2 {
+                        return Err(EditError::UnsupportedValue(format!(
+                            "secrets in orbit {version}"
+                        )));
+                    }
+                    let _ = write!(output, "@{SECRET_ANNOTATION} ");
+                }
+                if is_identifier(key, version) {
+                    output.push_str(key);
+                } else if version >= LanguageVersion::V0_2 {
+                    output.push_str(&quote(key));
+                } else {
+                    return Err(EditError::InvalidKey(key.clone()));
+                }
+                output.push_str(": ");
+                write_value(output, value, version)?;
+            }
+            output.push_str(" }");
+        }
+        // Only entries carry annotations, so a secret list item cannot stay
+        // sealed.
+        OrbitValue::Secret(_) => {
+            return Err(EditError::UnsupportedValue(
+                "a secret outside an entry".to_string(),
+            ));
+        }
+        OrbitValue::Null => return Err(EditError::UnsupportedValue("null".to_string())),
+    }
+    Ok(())
+}
+
+fn quote(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for ch in value.chars() {
+        match ch {
+            '\\' => quoted.push_str("\\\\"),
+            '"' => quoted.push_str("\\\""),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            other => quoted.push(other),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
@@ -3,8 +3,12 @@ use std::{fs, path::Path};
 use std::fmt::Write;
 
 use orbit_core::{
-    ast::{AstNode, ValueNode},
+    ast::{Annotation, AstNode, DocComment, Span, ValueNode},
     error::CoreError,
+    lexer::{BASE64_TAG, DOC_COMMENT_PREFIX, DOCUMENT_SEPARATOR, is_identifier},
+    parser::grammar::PROFILE_KEYWORD,
+    value::bytes::to_base64,
+    version::{LanguageVersion, PRAGMA_PREFIX},
 };
 
 #[derive(Debug, thiserror::Error)]
@@ -15,15 +19,45 @@ pub enum FormatError {
     Io(#[from] std::io::Error),
 }
 
+#[derive(Debug)]
+pub struct FormatReport {
+    pub output: String,
+    pub errors: Vec<CoreError>,
+}
+
 pub fn format_source(source: &str) -> Result<String, FormatError> {
-    let ast = orbit_core::parse(source)?;
-    let mut formatter = Formatter::new();
-    formatter.write_document(&ast);
+    let documents = orbit_core::parse_documents(source)?;
+    Ok(format_documents(source, &documents))
+}
+
+// Formats a source that may not parse. The parts that do are formatted as
+// usual; the text of every error node is copied through unchanged, so no
+// input is lost. `errors` has what kept the source from parsing.
+pub fn format_with_recovery(source: &str) -> FormatReport {
+    let tree = orbit_core::parse_cst(source);
+    FormatReport {
+        output: format_documents(source, tree.documents()),
+        errors: tree.errors().to_vec(),
+    }
+}
+
+fn format_documents(source: &str, documents: &[AstNode]) -> String {
+    let pragma = LanguageVersion::from_pragma(source).ok().flatten();
+    let mut formatter = Formatter::new(source, pragma.unwrap_or(LanguageVersion::LATEST));
+    if let Some(version) = pragma {
+        let _ = writeln!(formatter.output, "{} {}", PRAGMA_PREFIX, version);
+    }
+    for (index, document) in documents.iter().enumerate() {
+        if index > 0 {
+            let _ = writeln!(formatter.output, "{}", DOCUMENT_SEPARATOR);
+        }
+        formatter.write_document(document);
+    }
     let mut output = formatter.finish();
     if !output.ends_with('\n') {
         output.push('\n');
     }
-    Ok(output)
+    output
 }
 
 pub fn format_file(path: impl AsRef<Path>) -> Result<String, FormatError> {
@@ -38,16 +72,20 @@ pub fn format_file_in_place(path: impl AsRef<Path>) -> Result<(), FormatError> {
     Ok(())
 }
 
-struct Formatter {
+struct Formatter<'s> {
+    source: &'s str,
     output: String,
     indent: usize,
+    version: LanguageVersion,
 }
 
-impl Formatter {
-    fn new() -> Self {
+impl<'s> Formatter<'s> {
+    fn new(source: &'s str, version: LanguageVersion) -> Self {
         Self {
+            source,
             output: String::new(),
             indent: 0,
+            version,
         }
     }
 
@@ -67,37 +105,70 @@ impl Formatter {
     }
 
     fn write_node(&mut self, node: &AstNode) {
+        self.write_docs(node.docs());
         match node {
-            AstNode::Entry { key, value, .. } => {
+            AstNode::Entry {
+                key,
+                value,
+                annotations,
+                ..
+            } => {
                 self.write_indent();
+                self.write_annotations(annotations);
                 let _ = write!(self.output, "{}: ", key);
                 self.write_value(value);
                 self.output.push('\n');
             }
-            AstNode::Block { name, body, .. } => {
+            AstNode::Block {
+                name,
+                body,
+                annotations,
+                ..
+            } => {
                 self.write_indent();
+                self.write_annotations(annotations);
                 let _ = writeln!(self.output, "{} {{", name);
-                self.indent += 1;
-                for child in body {
-                    self.write_node(child);
-                }
-                self.indent -= 1;
+                self.write_body(body);
+            }
+            AstNode::Profile { name, body, .. } => {
                 self.write_indent();
-                self.output.push_str("}\n");
+                let _ = writeln!(self.output, "{} {} {{", PROFILE_KEYWORD, name);
+                self.write_body(body);
             }
             AstNode::Document { body, .. } => {
                 for entry in body {
                     self.write_node(entry);
                 }
             }
+            AstNode::Error { span } => {
+                self.write_indent();
+                self.write_original(*span);
+                self.output.push('\n');
+            }
         }
     }
 
+    fn write_body(&mut self, body: &[AstNode]) {
+        self.indent += 1;
+        for child in body {
+            self.write_node(child);
+        }
+        self.indent -= 1;
+        self.write_indent();
+        self.output.push_str("}\n");
+    }
+
     fn write_value(&mut self, value: &ValueNode) {
         match value {
             ValueNode::String { value, .. } => {
                 let _ = write!(self.output, "\"{}\"", escape_string(value));
             }
+            ValueNode::Bytes { value, .. } => {
+                let _ = write!(self.output, "{}\"{}\"", BASE64_TAG, to_base64(value));
+            }
+            ValueNode::Tagged { tag, value, .. } => {
+                let _ = write!(self.output, "{}\"{}\"", tag, escape_string(value));
+            }
             ValueNode::Number { value, .. } => {
                 let _ = write!(self.output, "{}", value);
             }
@@ -129,12 +200,21 @@ impl Formatter {
                 } else {
                     self.output.push_str("{\n");
                     self.indent += 1;
+                    // Spreads stay where they are so override order is kept;
+                    // only the keyed entries between them are sorted.
                     let mut items: Vec<_> = entries.iter().collect();
-                    items.sort_by(|a, b| a.key.cmp(&b.key));
+                    for segment in items.split_mut(|entry| entry.is_spread()) {
+                        segment.sort_by(|a, b| a.key.cmp(&b.key));
+                    }
                     let total = items.len();
                     for (index, entry) in items.into_iter().enumerate() {
+                        self.write_docs(&entry.docs);
                         self.write_indent();
-                        let _ = write!(self.output, "{}: ", entry.key);
+                        if !entry.is_spread() {
+                            self.write_annotations(&entry.annotations);
+                            self.write_key(&entry.key);
+                            self.output.push_str(": ");
+                        }
                         self.write_value(&entry.value);
                         if index + 1 != total {
                             self.output.push(',');
@@ -146,9 +226,56 @@ impl Formatter {
                     self.output.push('}');
                 }
             }
+            ValueNode::Spread { name, .. } => {
+                let _ = write!(self.output, "...{}", name);
+            }
+            ValueNode::Error { span } => self.write_original(*span),
+        }
+    }
+
+    fn write_docs(&mut self, docs: &[DocComment]) {
+        for doc in docs {
+            self.write_indent();
+            self.output.push_str(DOC_COMMENT_PREFIX);
+            if !doc.text.is_empty() {
+                self.output.push(' ');
+                self.output.push_str(&doc.text);
+            }
+            self.output.push('\n');
         }
     }
 
+    fn write_annotations(&mut self, annotations: &[Annotation]) {
+        for annotation in annotations {
+            let _ = write!(self.output, "@{}", annotation.name);
+            if !annotation.args.is_empty() {
+                self.output.push('(');
+                for (index, arg) in annotation.args.iter().enumerate() {
+                    if index > 0 {
+                        self.output.push_str(", ");
+                    }
+                    self.write_value(arg);
+                }
+                self.output.push(')');
+            }
+            self.output.push(' ');
+        }
+    }
+
+    fn write_key(&mut self, key: &str) {
+        if is_identifier(key, self.version) {
+            self.output.push_str(key);
+        } else {
+            let _ = write!(self.output, "\"{}\"", escape_string(key));
+        }
+    }
+
+    // Text that did not parse is kept as it was written.
+    fn write_original(&mut self, span: Span) {
+        let text = self.source.get(span.start..span.end).unwrap_or_default();
+        self.output.push_str(text.trim_end());
+    }
+
     fn write_indent(&mut self) {
         for _ in 0..self.indent {
             self.output.push_str("    ");
@@ -0,0 +1,95 @@
+use orbit_core::{AstNode, EvaluationOptions, ValueNode};
+
+const SAMPLE: &str = r#"
+server {
+    @deprecated("use listen_addr") host: "0.0.0.0"
+    listen_addr: "0.0.0.0:8080"
+    @secret password: "hunter2"
+    @since("1.4") @experimental
+    tuning {
+        workers: 4
+    }
+    extra: { @secret token: "abc" }
+}
+"#;
+
+#[test]
+fn annotations_are_stored_on_ast_nodes() {
+    let ast = orbit_core::parse(SAMPLE).expect("parse should succeed");
+    let server = &ast.as_body().expect("document body")[0];
+    let body = server.as_body().expect("block body");
+
+    let deprecated = &body[0].annotations()[0];
+    assert_eq!(deprecated.name, "deprecated");
+    assert_eq!(deprecated.string_arg(0), Some("use listen_addr"));
+
+    let names: Vec<_> = body[3]
+        .annotations()
+        .iter()
+        .map(|annotation| annotation.name.as_str())
+        .collect();
+    assert_eq!(names, ["since", "experimental"]);
+
+    let AstNode::Entry {
+        value: ValueNode::Object { entries, .. },
+        ..
+    } = &body[4]
+    else {
+        panic!("expected object literal entry");
+    };
+    assert_eq!(entries[0].annotations[0].name, "secret");
+}
+
+#[test]
+fn evaluation_exposes_annotation_side_table() {
+    let evaluation = orbit_core::evaluate_detailed(SAMPLE, &EvaluationOptions::default())
+        .expect("evaluation should succeed");
+    assert!(evaluation.has_annotation(&["server", "password"], "secret"));
+    assert!(evaluation.has_annotation(&["server", "extra", "token"], "secret"));
+    assert!(evaluation.has_annotation(&["server", "tuning"], "since"));
+    assert!(
+        evaluation
+            .annotations_at(&["server", "listen_addr"])
+            .is_empty()
+    );
+}
+
+#[test]
+fn deprecated_keys_emit_warnings() {
+    let evaluation = orbit_core::evaluate_detailed(SAMPLE, &EvaluationOptions::default())
+        .expect("evaluation should succeed");
+    assert_eq!(evaluation.warnings.len(), 1);
+    assert_eq!(
+        evaluation.warnings[0].message,
+        "key 'host' is deprecated: use listen_addr"
+    );
+}
+
+#[test]
+fn formatter_keeps_annotations_inline() {
+    let formatted = orbit_fmt::format_source(SAMPLE).expect("formatting should succeed");
+    assert!(formatted.contains("    @deprecated(\"use listen_addr\") host: \"0.0.0.0\"\n"));
+    assert!(formatted.contains("    @since(\"1.4\") @experimental tuning {\n"));
+    assert!(formatted.contains("        @secret token: \"abc\"\n"));
+    let formatted_again = orbit_fmt::format_source(&formatted).expect("formatting should succeed");
+    assert_eq!(formatted, formatted_again);
+}
+
+#[test]
+fn spread_copies_values_but_not_annotations() {
+    let source =
+        "base: { @secret token: \"abc\", @deprecated region: \"eu\" }\ncopy: { ...base }\n";
+    let evaluation = orbit_core::evaluate_detailed(source, &EvaluationOptions::default())
+        .expect("evaluation should succeed");
+    assert!(evaluation.has_annotation(&["base", "token"], "secret"));
+    assert!(evaluation.annotations_at(&["copy", "token"]).is_empty());
+    assert!(evaluation.annotations_at(&["copy", "region"]).is_empty());
+    // The copy is not evaluated again, so it does not warn a second time.
+    assert_eq!(evaluation.warnings.len(), 1);
+    // Sealing is part of the value, so a copied secret stays redacted.
+    let token = evaluation
+        .value
+        .get_path(&["copy", "token"])
+        .expect("copied key");
+    assert!(token.is_secret());
+}
@@ -0,0 +1,52 @@
+use orbit_core::{
+    OrbitValue,
+    serializer::{to_json_string, to_msgpack_bytes, to_yaml_string},
+};
+
+const SAMPLE: &str = r#"
+tls {
+    cert: b64"b3JiaXQ="
+    fingerprint: hex"de ad be ef"
+}
+"#;
+
+#[test]
+fn bytes_literals_evaluate_to_bytes() {
+    let value = orbit_core::evaluate(SAMPLE).expect("evaluation should succeed");
+    assert_eq!(
+        value.get_path(&["tls", "cert"]),
+        Some(&OrbitValue::Bytes(b"orbit".to_vec()))
+    );
+    assert_eq!(
+        value.get_path(&["tls", "fingerprint"]),
+        Some(&OrbitValue::Bytes(vec![0xde, 0xad, 0xbe, 0xef]))
+    );
+}
+
+#[test]
+fn bytes_serialize_as_base64_text_and_msgpack_bin() {
+    let value = orbit_core::evaluate(SAMPLE).expect("evaluation should succeed");
+    let json = to_json_string(&value).expect("json serialization");
+    assert_eq!(
+        json,
+        r#"{"tls":{"cert":"b3JiaXQ=","fingerprint":"3q2+7w=="}}"#
+    );
+    let yaml = to_yaml_string(&value).expect("yaml serialization");
+    assert!(yaml.contains("cert: b3JiaXQ="));
+
+    let bytes = OrbitValue::Bytes(vec![1, 2, 3]);
+    let msgpack = to_msgpack_bytes(&bytes).expect("msgpack serialization");
+    assert_eq!(msgpack, [0xc4, 0x03, 1, 2, 3]);
+}
+
+#[test]
+fn invalid_bytes_literal_is_a_lex_error() {
+    let err = orbit_core::parse("key: hex\"abc\"\n").unwrap_err();
+    assert!(err.to_string().contains("invalid hex bytes literal"));
+}
+
+#[test]
+fn formatter_prints_bytes_as_base64() {
+    let formatted = orbit_fmt::format_source(SAMPLE).expect("formatting should succeed");
+    assert!(formatted.contains("    fingerprint: b64\"3q2+7w==\"\n"));
+}
@@ -0,0 +1,133 @@
+use orbit_core::{AstNode, ErrorCode, SyntaxKind, SyntaxNode, SyntaxTree, ValueNode};
+
+const SOURCE: &str = "#!orbit 0.3\r\n# leading comment\r\n\r\n## The service name.\r\n@deprecated(\"use id\")\r\nname:\t\"orbit\"   # trailing\r\n\r\nserver {\r\n    ports: [80,  443, ] # list\r\n    tls: { cert: \"a.pem\", ...defaults }\r\n}\r\n---\r\n  other: hex\"ff\"  \r\n";
+
+#[test]
+fn syntax_tree_is_lossless() {
+    let tree = SyntaxTree::parse(SOURCE);
+    assert_eq!(tree.to_string(), SOURCE);
+
+    let tokens = tree.root().tokens();
+    let mut offset = 0;
+    for token in tokens {
+        assert_eq!(token.span().start, offset, "tokens must be contiguous");
+        offset = token.span().end;
+    }
+    assert_eq!(offset, SOURCE.len());
+}
+
+#[test]
+fn trivia_is_kept_as_tokens() {
+    let tree = SyntaxTree::parse(SOURCE);
+    let comments: Vec<_> = tree
+        .root()
+        .tokens()
+        .filter(|token| token.kind() == SyntaxKind::Comment)
+        .map(|token| token.text())
+        .collect();
+    assert_eq!(
+        comments,
+        ["#!orbit 0.3", "# leading comment", "# trailing", "# list"]
+    );
+}
+
+#[test]
+fn nodes_mirror_the_ast() {
+    let tree = SyntaxTree::parse(SOURCE);
+    assert_eq!(
+        tree.documents(),
+        orbit_core::parse_documents(SOURCE).expect("parse should succeed")
+    );
+
+    let documents: Vec<_> = tree.root().child_nodes().collect();
+    assert_eq!(documents.len(), 2);
+    assert!(
+        documents
+            .iter()
+            .all(|document| document.kind() == SyntaxKind::Document)
+    );
+
+    // The entry node starts at its doc comment and owns its annotation.
+    let entry = documents[0].child_nodes().next().expect("entry node");
+    assert_eq!(entry.kind(), SyntaxKind::Entry);
+    assert!(entry.text().starts_with("## The service name."));
+    let kinds: Vec<_> = entry.children().map(|child| child.kind()).collect();
+    assert_eq!(kinds[0], SyntaxKind::DocComment);
+    assert!(kinds.contains(&SyntaxKind::Annotation));
+    assert_eq!(kinds.last(), Some(&SyntaxKind::Literal));
+}
+
+#[test]
+fn ast_spans_map_back_to_syntax_nodes() {
+    let tree = SyntaxTree::parse(SOURCE);
+    let AstNode::Document { body, .. } = &tree.documents()[0] else {
+        panic!("expected document");
+    };
+    let AstNode::Block { body: server, .. } = &body[1] else {
+        panic!("expected block");
+    };
+    let AstNode::Entry { value, .. } = &server[0] else {
+        panic!("expected entry");
+    };
+    let node = tree.covering_node(value.span()).expect("covering node");
+    assert_eq!(node.kind(), SyntaxKind::List);
+    assert_eq!(node.text(), "[80,  443, ]");
+}
+
+fn error_nodes(node: SyntaxNode<'_>, found: &mut Vec<(SyntaxKind, String)>) {
+    for child in node.child_nodes() {
+        if child.kind() == SyntaxKind::Error {
+            found.push((node.kind(), child.text()));
+        }
+        error_nodes(child, found);
+    }
+}
+
+#[test]
+fn unreadable_text_becomes_error_nodes() {
+    let source = "a: 'x'\nb: 1 $ 2\n---\nc: [1,\n";
+    let tree = SyntaxTree::parse(source);
+    assert_eq!(tree.to_string(), source);
+
+    let codes: Vec<_> = tree.errors().iter().map(|error| error.code()).collect();
+    assert_eq!(
+        codes,
+        [
+            ErrorCode::SingleQuotedString,
+            ErrorCode::UnexpectedCharacter,
+            ErrorCode::ExpectedIdentifier,
+            ErrorCode::UnclosedDelimiter
+        ]
+    );
+    let mut found = Vec::new();
+    error_nodes(tree.root(), &mut found);
+    assert_eq!(
+        found,
+        [
+            (SyntaxKind::Entry, "'x'".to_string()),
+            (SyntaxKind::Document, "$".to_string()),
+            (SyntaxKind::Document, "2".to_string())
+        ]
+    );
+
+    // The AST is still derived for both documents.
+    let documents = tree.documents();
+    assert_eq!(documents.len(), 2);
+    let AstNode::Document { body, .. } = &documents[0] else {
+        panic!("expected document");
+    };
+    assert!(matches!(
+        &body[0],
+        AstNode::Entry {
+            value: ValueNode::Error { .. },
+            ..
+        }
+    ));
+    let AstNode::Document { body, .. } = &documents[1] else {
+        panic!("expected document");
+    };
+    assert!(matches!(
+        &body[0],
+        AstNode::Entry { value: ValueNode::List { items, .. }, .. } if items.len() == 1
+    ));
+}
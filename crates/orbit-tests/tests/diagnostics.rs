@@ -0,0 +1,65 @@
+use orbit_core::{Diagnostic, EvaluationOptions, FileDatabase, Renderer, Span};
+
+fn render_error(path: &str, source: &str) -> String {
+    let mut files = FileDatabase::new();
+    let file = files.add(path, source);
+    let error = files
+        .evaluate(file, &EvaluationOptions::default())
+        .expect("file is registered")
+        .unwrap_err();
+    Renderer::plain().render_in(&Diagnostic::from(&error), &files)
+}
+
+#[test]
+fn duplicate_keys_point_at_both_definitions() {
+    let rendered = render_error(
+        "app.orb",
+        "server {\n    port: 80\n    host: \"a\"\n    port: 81\n}\n",
+    );
+    assert_eq!(
+        rendered,
+        "error[E0301]: duplicate key 'port'\n --> app.orb:4:5\n  |\n2 |     port: 80\n  |     -------- first defined here\n3 |     host: \"a\"\n4 |     port: 81\n  |     ^^^^^^^^\n"
+    );
+}
+
+#[test]
+fn labels_sharing_a_line_hang_their_messages() {
+    let rendered = render_error("app.orb", "limits: { cpu: 1, cpu: 2 }\n");
+    assert_eq!(
+        rendered,
+        "error[E0301]: duplicate key 'cpu' inside object literal\n --> app.orb:1:19\n  |\n1 | limits: { cpu: 1, cpu: 2 }\n  |           ------  ^^^^^^\n  |           |\n  |           first defined here\n"
+    );
+}
+
+#[test]
+fn errors_without_a_database_use_their_attached_source() {
+    let source = "a: 1\nb 2\n";
+    let error = orbit_core::parse(source)
+        .unwrap_err()
+        .with_source(std::sync::Arc::new(orbit_core::SourceMap::new(
+            "b.orb", source,
+        )));
+    let diagnostic = Diagnostic::from(&error)
+        .with_primary_message("expected ':'")
+        .with_note("entries are written `key: value`")
+        .with_help("insert ':' after 'b'");
+    assert_eq!(
+        Renderer::plain().render(&diagnostic),
+        "error[E0202]: expected ':' after identifier\n --> b.orb:2:3\n  |\n2 | b 2\n  |   ^ expected ':'\n  |\n  = note: expected one of ':', '{'\n  = note: entries are written `key: value`\n  = help: add ':' after the key\n  = help: insert ':' after 'b'\n"
+    );
+    let colored = Renderer::colored().render(&diagnostic);
+    assert!(colored.starts_with("\u{1b}[1;31merror[E0202]\u{1b}[0m: "));
+}
+
+#[test]
+fn secondary_labels_may_point_into_other_files() {
+    let mut files = FileDatabase::new();
+    let base = files.add("base.orb", "port: 80\n");
+    let site = files.add("site.orb", "# site\nport: 81\n");
+    let diagnostic = Diagnostic::error("duplicate key 'port'", Span::new(7, 15).in_file(site))
+        .with_label(Span::new(0, 8).in_file(base), "first defined here");
+    assert_eq!(
+        Renderer::plain().render_in(&diagnostic, &files),
+        "error: duplicate key 'port'\n --> site.orb:2:1\n  |\n2 | port: 81\n  | ^^^^^^^^\n ::: base.orb:1:1\n  |\n1 | port: 80\n  | -------- first defined here\n"
+    );
+}
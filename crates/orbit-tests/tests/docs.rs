@@ -0,0 +1,85 @@
+use orbit_core::{AstNode, ValueNode};
+
+const SAMPLE: &str = r#"
+## Public listener.
+## Shared by every worker.
+server {
+    # regular comment, not documentation
+    host: "0.0.0.0"
+    ## TCP port to bind.
+    port: 8080
+    limits: {
+        ## Requests per second.
+        rps: 100
+    }
+}
+"#;
+
+#[test]
+fn doc_comments_attach_to_following_node() {
+    let ast = orbit_core::parse(SAMPLE).expect("parse should succeed");
+    let server = &ast.as_body().expect("document body")[0];
+    let texts: Vec<_> = server.docs().iter().map(|doc| doc.text.as_str()).collect();
+    assert_eq!(texts, ["Public listener.", "Shared by every worker."]);
+
+    let body = server.as_body().expect("block body");
+    assert!(body[0].docs().is_empty());
+    assert_eq!(body[1].docs()[0].text, "TCP port to bind.");
+
+    let AstNode::Entry {
+        value: ValueNode::Object { entries, .. },
+        ..
+    } = &body[2]
+    else {
+        panic!("expected object literal entry");
+    };
+    assert_eq!(entries[0].docs[0].text, "Requests per second.");
+}
+
+#[test]
+fn trailing_doc_comment_is_not_attached() {
+    let ast = orbit_core::parse("a: 1 ## about a\nb: 2\n").expect("parse should succeed");
+    let body = ast.as_body().expect("document body");
+    assert!(body[1].docs().is_empty());
+}
+
+#[test]
+fn formatter_preserves_doc_comments() {
+    let formatted = orbit_fmt::format_source(SAMPLE).expect("formatting should succeed");
+    assert!(formatted.starts_with("## Public listener.\n## Shared by every worker.\nserver {\n"));
+    assert!(formatted.contains("    ## TCP port to bind.\n    port: 8080\n"));
+    assert!(formatted.contains("        ## Requests per second.\n        rps: 100\n"));
+    assert!(!formatted.contains("regular comment"));
+    let formatted_again = orbit_fmt::format_source(&formatted).expect("formatting should succeed");
+    assert_eq!(formatted, formatted_again);
+}
+
+#[test]
+fn doc_comments_attach_to_profiles_and_spreads() {
+    let source = "#!orbit 0.2\n## Production overrides.\nprofile prod {\n    a: 1\n}\nbase: { x: 1 }\nobj: {\n    ## Defaults first.\n    ...base\n}\n";
+    let ast = orbit_core::parse(source).expect("parse should succeed");
+    let body = ast.as_body().expect("document body");
+    assert_eq!(body[0].docs()[0].text, "Production overrides.");
+
+    let AstNode::Entry {
+        value: ValueNode::Object { entries, .. },
+        ..
+    } = &body[2]
+    else {
+        panic!("expected object literal entry");
+    };
+    assert_eq!(entries[0].docs[0].text, "Defaults first.");
+
+    let formatted = orbit_fmt::format_source(source).expect("formatting should succeed");
+    assert!(formatted.contains("## Production overrides.\nprofile prod {\n"));
+    assert!(formatted.contains("    ## Defaults first.\n    ...base\n"));
+}
+
+#[test]
+fn plain_comments_between_doc_comments_and_their_node_are_skipped() {
+    let ast = orbit_core::parse("## About a.\n# internal note\n## More about a.\na: 1\n")
+        .expect("parse should succeed");
+    let a = &ast.as_body().expect("document body")[0];
+    let texts: Vec<_> = a.docs().iter().map(|doc| doc.text.as_str()).collect();
+    assert_eq!(texts, ["About a.", "More about a."]);
+}
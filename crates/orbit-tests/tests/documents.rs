@@ -0,0 +1,85 @@
+use orbit_core::{AstNode, EvaluationOptions, OrbitValue};
+
+const STREAM: &str = r#"---
+kind: "Service"
+name: "api"
+---
+kind: "Deployment"
+name: "api"
+---
+"#;
+
+#[test]
+fn separators_split_documents() {
+    let documents = orbit_core::parse_documents(STREAM).expect("parse should succeed");
+    assert_eq!(documents.len(), 2);
+    assert!(
+        documents
+            .iter()
+            .all(|document| matches!(document, AstNode::Document { .. }))
+    );
+    assert_eq!(documents[1].as_body().map(<[AstNode]>::len), Some(2));
+}
+
+#[test]
+fn documents_have_separate_duplicate_scopes() {
+    let values = orbit_core::evaluate_documents(STREAM, &EvaluationOptions::default())
+        .expect("evaluation should succeed");
+    let kinds: Vec<_> = values
+        .iter()
+        .map(|value| value.get_path(&["kind"]).cloned())
+        .collect();
+    assert_eq!(
+        kinds,
+        [
+            Some(OrbitValue::String("Service".into())),
+            Some(OrbitValue::String("Deployment".into()))
+        ]
+    );
+}
+
+#[test]
+fn single_document_entry_points_reject_separators() {
+    let err = orbit_core::parse(STREAM).unwrap_err();
+    assert!(err.to_string().contains("use parse_documents"));
+}
+
+#[test]
+fn formatter_keeps_document_boundaries() {
+    let formatted = orbit_fmt::format_source(STREAM).expect("formatting should succeed");
+    assert_eq!(
+        formatted,
+        "kind: \"Service\"\nname: \"api\"\n---\nkind: \"Deployment\"\nname: \"api\"\n"
+    );
+}
+
+#[test]
+fn separators_must_stand_on_their_own_line() {
+    let documents = orbit_core::parse_documents("a: 1\n---  \t\nb: 2\n").expect("parses");
+    assert_eq!(documents.len(), 2);
+
+    for source in [
+        "a: 1 ---\nb: 2\n",
+        "a: 1\n  ---\nb: 2\n",
+        "a: 1\n--- b: 2\n",
+    ] {
+        assert!(orbit_core::parse_documents(source).is_err(), "{source:?}");
+    }
+}
+
+#[test]
+fn every_document_is_evaluated_with_the_options() {
+    let source = "a: 1\nprofile prod { a: 2 }\n---\nb: 1\nprofile prod { b: 3 }\n";
+    let options = EvaluationOptions::new().with_profile("prod");
+    let values = orbit_core::evaluate_documents(source, &options).expect("evaluates");
+    assert_eq!(
+        values,
+        [
+            orbit_core::evaluate("a: 2\n").expect("evaluates"),
+            orbit_core::evaluate("b: 3\n").expect("evaluates")
+        ]
+    );
+    // A document without the profile fails instead of silently ignoring it.
+    let err = orbit_core::evaluate_documents("a: 1\n---\nb: 2\n", &options).unwrap_err();
+    assert!(err.to_string().contains("unknown profile 'prod'"));
+}
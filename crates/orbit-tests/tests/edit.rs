@@ -0,0 +1,172 @@
+use orbit_core::{OrbitValue, Secret, ValueNode};
+use orbit_edit::{Document, EditError, Scope};
+
+const SOURCE: &str = "# deployment settings\n\nimage {\n  ## Container tag.\n  tag: \"v1\"   # bumped by the bot\n  repo: \"registry/app\"\n}\n\nlimits: { cpu: 2, memory: \"1Gi\" }\n";
+
+#[test]
+fn updating_a_value_keeps_every_other_byte() {
+    let mut doc = Document::parse(SOURCE).expect("parse should succeed");
+    doc.set(&["image", "tag"], "v2")
+        .expect("set should succeed");
+    assert_eq!(doc.to_string(), SOURCE.replace("\"v1\"", "\"v2\""));
+
+    doc.set(&["limits", "cpu"], 4).expect("set should succeed");
+    assert!(
+        doc.as_str()
+            .ends_with("limits: { cpu: 4, memory: \"1Gi\" }\n")
+    );
+    assert!(matches!(
+        doc.get(&["limits", "cpu"]),
+        Some(ValueNode::Number { .. })
+    ));
+}
+
+#[test]
+fn inserting_entries_and_blocks() {
+    let mut doc = Document::parse(SOURCE).expect("parse should succeed");
+    doc.set(&["image", "pull"], "always")
+        .expect("insert should succeed");
+    doc.insert(&["limits", "gpu"], 1)
+        .expect("insert should succeed");
+    doc.create_block(&["probe"])
+        .expect("block should be created");
+    doc.set(&["probe", "path"], "/healthz")
+        .expect("insert should succeed");
+    assert_eq!(
+        doc.as_str(),
+        "# deployment settings\n\nimage {\n  ## Container tag.\n  tag: \"v1\"   # bumped by the bot\n  repo: \"registry/app\"\n  pull: \"always\"\n}\n\nlimits: { cpu: 2, memory: \"1Gi\", gpu: 1 }\nprobe {\n  path: \"/healthz\"\n}\n"
+    );
+    assert!(matches!(
+        doc.insert(&["image", "tag"], "v3"),
+        Err(EditError::AlreadyExists(path)) if path == "image.tag"
+    ));
+}
+
+#[test]
+fn removing_entries_drops_their_lines_and_comments() {
+    let mut doc = Document::parse(SOURCE).expect("parse should succeed");
+    doc.remove(&["image", "tag"])
+        .expect("remove should succeed");
+    doc.remove(&["limits", "cpu"])
+        .expect("remove should succeed");
+    assert_eq!(
+        doc.as_str(),
+        "# deployment settings\n\nimage {\n  repo: \"registry/app\"\n}\n\nlimits: { memory: \"1Gi\" }\n"
+    );
+    assert!(matches!(
+        doc.remove(&["image", "missing"]),
+        Err(EditError::NotFound(_))
+    ));
+}
+
+#[test]
+fn multi_line_objects_and_crlf_are_preserved() {
+    let source = "labels: {\r\n    app: \"web\", # primary\r\n}\r\n";
+    let mut doc = Document::parse(source).expect("parse should succeed");
+    doc.insert(&["labels", "tier"], "frontend")
+        .expect("insert should succeed");
+    assert_eq!(
+        doc.as_str(),
+        "labels: {\r\n    app: \"web\", # primary\r\n    tier: \"frontend\",\r\n}\r\n"
+    );
+    let value = orbit_core::evaluate(doc.as_str()).expect("edited document evaluates");
+    assert_eq!(
+        value.get_path(&["labels", "tier"]),
+        Some(&OrbitValue::from("frontend"))
+    );
+}
+
+#[test]
+fn invalid_edits_leave_the_document_untouched() {
+    let mut doc = Document::parse(SOURCE).expect("parse should succeed");
+    assert!(matches!(
+        doc.set(&["image"], "v2"),
+        Err(EditError::NotAValue(_))
+    ));
+    assert!(matches!(
+        doc.set(&["image", "tag", "nested"], 1),
+        Err(EditError::NotAContainer(_))
+    ));
+    assert!(matches!(
+        doc.set(&["bad key"], 1),
+        Err(EditError::InvalidKey(_))
+    ));
+    assert_eq!(doc.as_str(), SOURCE);
+}
+
+#[test]
+fn documents_and_profiles_are_addressed_by_scope() {
+    let source = "a: 1\nprofile prod {\n    a: 2\n}\n---\nb: 1\n";
+    let mut doc = Document::parse(source).expect("parse should succeed");
+    assert_eq!(doc.documents().len(), 2);
+    assert!(!doc.contains(&["b"]));
+    assert!(doc.contains_in(Scope::document(1), &["b"]));
+
+    doc.set_in(Scope::document(0).with_profile("prod"), &["a"], 3)
+        .expect("set should succeed");
+    doc.insert_in(Scope::document(0), &["c"], true)
+        .expect("insert should succeed");
+    doc.set_in(Scope::document(1), &["b"], 2)
+        .expect("set should succeed");
+    doc.create_profile(1, "prod")
+        .expect("profile should be created");
+    doc.set_in(Scope::document(1).with_profile("prod"), &["b"], 5)
+        .expect("insert should succeed");
+    assert_eq!(
+        doc.as_str(),
+        "a: 1\nprofile prod {\n    a: 3\n}\nc: true\n---\nb: 2\nprofile prod {\n    b: 5\n}\n"
+    );
+    assert!(matches!(
+        doc.set_in(Scope::document(0).with_profile("staging"), &["a"], 1),
+        Err(EditError::ProfileNotFound(name)) if name == "staging"
+    ));
+    assert!(matches!(
+        doc.set_in(Scope::document(2), &["a"], 1),
+        Err(EditError::DocumentNotFound(2))
+    ));
+}
+
+#[test]
+fn keys_are_compared_after_nfc_normalization() {
+    // The key is written decomposed; the path uses the composed form.
+    let source = "cafe\u{301}: 1\nmenu: { \"cafe\u{301}\": 2 }\n";
+    let mut doc = Document::parse(source).expect("parse should succeed");
+    doc.set(&["caf\u{e9}"], 3).expect("set should succeed");
+    doc.set(&["menu", "caf\u{e9}"], 4)
+        .expect("set should succeed");
+    assert_eq!(
+        doc.as_str(),
+        "cafe\u{301}: 3\nmenu: { \"cafe\u{301}\": 4 }\n"
+    );
+    assert!(matches!(
+        doc.insert(&["caf\u{e9}"], 5),
+        Err(EditError::AlreadyExists(_))
+    ));
+}
+
+#[test]
+fn secrets_keep_their_annotation() {
+    let source = "## Database password.\n@secret password: \"hunter2\"\nuser: \"app\"\n";
+    let mut doc = Document::parse(source).expect("parse should succeed");
+    doc.set(&["password"], "correct horse")
+        .expect("set should succeed");
+    doc.set(&["user"], OrbitValue::Secret(Secret::new("admin".into())))
+        .expect("set should succeed");
+    doc.insert(&["token"], OrbitValue::Secret(Secret::new("abc".into())))
+        .expect("insert should succeed");
+    assert_eq!(
+        doc.as_str(),
+        "## Database password.\n@secret password: \"correct horse\"\n@secret user: \"admin\"\n@secret token: \"abc\"\n"
+    );
+    let value = orbit_core::evaluate(doc.as_str()).expect("edited document evaluates");
+    assert!(matches!(
+        value.get_path(&["token"]),
+        Some(OrbitValue::Secret(_))
+    ));
+
+    let list = OrbitValue::List(vec![OrbitValue::Secret(Secret::new(1.into()))]);
+    assert!(matches!(
+        doc.set(&["user"], list),
+        Err(EditError::UnsupportedValue(_))
+    ));
+}
@@ -0,0 +1,220 @@
+use std::{
+    collections::HashMap,
+    io,
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use orbit_core::{
+    Engine, EngineError, ErrorCode, EvaluationOptions, Limits, OrbitNumber, OrbitValue,
+    RuntimeError, Span, TextEdit,
+};
+
+fn int(value: i64) -> OrbitValue {
+    OrbitValue::Number(OrbitNumber::Integer(value))
+}
+
+fn memory_engine(files: &Arc<Mutex<HashMap<String, String>>>) -> Engine {
+    let files = files.clone();
+    Engine::new().with_loader(move |path: &Path| {
+        let files = files.lock().expect("lock");
+        files
+            .get(path.to_str().expect("utf-8 path"))
+            .cloned()
+            .ok_or_else(|| io::Error::from(io::ErrorKind::NotFound))
+    })
+}
+
+#[test]
+fn builder_settings_apply_to_every_entry_point() {
+    let engine = Engine::new()
+        .with_profile("prod")
+        .with_tag("port", |text, span| {
+            text.parse()
+                .map(|port: i64| OrbitValue::Number(OrbitNumber::Integer(port)))
+                .map_err(|_| RuntimeError::new("not a port", span))
+        })
+        .with_limits(Limits::new().with_max_depth(4));
+    let source = "#!orbit 0.2\nport: port\"80\"\nprofile prod { port: port\"443\" }\n";
+    let value = engine.evaluate(source).expect("evaluates");
+    assert_eq!(value.get_path(&["port"]), Some(&int(443)));
+
+    let ast = engine.parse(source).expect("parses");
+    assert_eq!(engine.evaluate_ast(&ast).expect("evaluates"), value);
+    assert_eq!(engine.limits().max_depth, 4);
+    let err = engine.parse("a: [[[[[1]]]]]\n").unwrap_err();
+    assert_eq!(err.code(), ErrorCode::NestingTooDeep);
+
+    fn shared<T: Send + Sync>(_: &T) {}
+    shared(&engine);
+}
+
+#[test]
+fn free_functions_match_a_default_engine() {
+    let source = "a: 1\nb: [...a]\nc: 2\n";
+    let engine = Engine::new();
+    assert_eq!(
+        orbit_core::evaluate("a: 1\n").expect("evaluates"),
+        engine.evaluate("a: 1\n").expect("evaluates")
+    );
+    let free = orbit_core::evaluate_report(source, engine.options()).expect("parses");
+    let report = engine.evaluate_report(source).expect("parses");
+    assert_eq!(free.value, report.value);
+    assert_eq!(free.broken, report.broken);
+    assert_eq!(report.errors[0].code(), ErrorCode::InvalidSpread);
+
+    // The report functions take the same options as the engine.
+    let source = "#!orbit 0.2\nname: \"a\"\nname: \"b\"\nprofile prod {\n    name: \"c\"\n}\n";
+    let options = EvaluationOptions::new().with_profile("prod");
+    let engine = Engine::new().with_options(options.clone());
+    let free = orbit_core::evaluate_report(source, &options).expect("parses");
+    let report = engine.evaluate_report(source).expect("parses");
+    assert_eq!(free.value, report.value);
+    assert_eq!(free.value.get_path(&["name"]), Some(&OrbitValue::from("c")));
+    let ast = engine.parse(source).expect("parses");
+    let free = orbit_core::evaluate_ast_report(&ast, &options);
+    assert_eq!(free.value, report.value);
+    assert_eq!(free.errors.len(), 1);
+}
+
+#[test]
+fn files_come_from_the_loader_and_errors_point_into_them() {
+    let files = Arc::new(Mutex::new(HashMap::from([(
+        "app.orb".to_string(),
+        "port: 80\n".to_string(),
+    )])));
+    let engine = memory_engine(&files);
+    assert_eq!(
+        engine.evaluate_file("app.orb").expect("evaluates"),
+        OrbitValue::Object([("port".to_string(), int(80))].into_iter().collect())
+    );
+
+    // An edited file is parsed again instead of served from the cache.
+    files
+        .lock()
+        .expect("lock")
+        .insert("app.orb".into(), "port: 80\nport: 81\n".into());
+    let err = engine.evaluate_file("app.orb").unwrap_err();
+    let EngineError::Core(err) = err else {
+        panic!("expected a language error, got {err:?}");
+    };
+    assert_eq!(err.code(), ErrorCode::DuplicateKey);
+    assert!(err.to_string().starts_with("app.orb:2:1: runtime error"));
+    // Both texts were registered; the error points into the second.
+    let registered = engine.files();
+    assert_eq!(registered.files().count(), 2);
+    assert_eq!(registered.path(err.span().file()), Some("app.orb"));
+    assert_eq!(
+        registered.source(err.span().file()),
+        Some("port: 80\nport: 81\n")
+    );
+
+    engine.clear_cache();
+    let err = engine.evaluate_file("missing.orb").unwrap_err();
+    assert!(matches!(err, EngineError::Io { .. }));
+    assert_eq!(err.to_string(), "failed to read missing.orb");
+}
+
+#[test]
+fn document_reports_keep_warnings_with_their_document() {
+    let engine = Engine::new();
+    let source = "a: \"\\q\"\n---\nb: 1\nempty {}\n---\nc: 9007199254740993\n";
+    let reports = engine.evaluate_documents_report(source).expect("parses");
+    let codes: Vec<Vec<_>> = reports
+        .iter()
+        .map(|report| report.warnings.iter().map(|warning| warning.code).collect())
+        .collect();
+    assert_eq!(
+        codes,
+        [
+            vec![ErrorCode::UnknownEscape],
+            vec![ErrorCode::EmptyBlock],
+            vec![ErrorCode::UnsafeInteger]
+        ]
+    );
+
+    let denied = Engine::new().with_deny_warnings(true);
+    let reports = denied.evaluate_documents_report(source).expect("parses");
+    assert!(reports.iter().all(|report| report.errors.len() == 1));
+    assert!(reports.iter().all(|report| report.warnings.is_empty()));
+}
+
+#[test]
+fn document_reports_recover_a_single_document_only() {
+    let engine = Engine::new();
+    let reports = engine
+        .evaluate_documents_report("a: 1\nb 2\n")
+        .expect("recovers");
+    assert_eq!(reports.len(), 1);
+    assert_eq!(reports[0].errors[0].code(), ErrorCode::MissingColon);
+    assert_eq!(reports[0].value.get_path(&["b"]), Some(&int(2)));
+
+    let err = engine
+        .evaluate_documents_report("a: 1\n---\nb 2\n")
+        .unwrap_err();
+    assert_eq!(err.code(), ErrorCode::MissingColon);
+}
+
+#[test]
+fn documents_deny_lexer_and_parser_warnings() {
+    let source = "a: 1\n---\nb: \"\\q\"\n";
+    assert_eq!(
+        Engine::new()
+            .evaluate_documents(source)
+            .expect("evaluates")
+            .len(),
+        2
+    );
+    let denied = Engine::new().with_deny_warnings(true);
+    let err = denied.evaluate_documents(source).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::UnknownEscape);
+    let err = orbit_core::evaluate_documents(source, denied.options()).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::UnknownEscape);
+}
+
+#[test]
+fn syntax_trees_carry_located_warnings() {
+    let source = "a: 1\nb: \"\\q\"\n";
+    let (_, warnings) = Engine::new().parse_with_warnings(source).expect("parses");
+    assert_eq!(
+        warnings[0].to_string(),
+        "warning at 2:5: unknown escape sequence '\\q'"
+    );
+
+    let denied = Engine::new().with_deny_warnings(true);
+    let err = denied.parse_with_warnings(source).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::UnknownEscape);
+    assert_eq!(
+        err.to_string(),
+        "runtime error at 2:5: unknown escape sequence '\\q'"
+    );
+
+    let shallow = Engine::new().with_limits(Limits::new().with_max_depth(1));
+    let err = shallow.parse_with_warnings("a: [[1]]\n").unwrap_err();
+    assert_eq!(err.code(), ErrorCode::NestingTooDeep);
+}
+
+#[test]
+fn syntax_trees_use_the_engine_limits() {
+    let engine = Engine::new().with_limits(Limits::new().with_max_depth(2));
+    let source = "a: [1]\n";
+    let tree = engine.parse_cst(source);
+    assert_eq!(tree.limits(), engine.limits());
+    assert!(tree.errors().is_empty());
+
+    let edit = TextEdit::new(Span::new(4, 5), "[[1]]");
+    let edited = edit.apply(source);
+    let reparsed = engine.reparse(&tree, &edited, &edit);
+    assert_eq!(reparsed.errors()[0].code(), ErrorCode::NestingTooDeep);
+
+    // A tree parsed with other limits is parsed again in full.
+    let default = orbit_core::parse_cst(source);
+    let reparsed = engine.reparse(&default, &edited, &edit);
+    assert_eq!(reparsed.limits(), engine.limits());
+    assert_eq!(reparsed.errors()[0].code(), ErrorCode::NestingTooDeep);
+    assert!(
+        orbit_core::reparse(&default, &edited, &edit)
+            .errors()
+            .is_empty()
+    );
+}
@@ -0,0 +1,88 @@
+use std::collections::HashSet;
+
+use orbit_core::{ErrorCode, EvaluationOptions, RuntimeError, Span};
+
+fn code_of(source: &str) -> ErrorCode {
+    orbit_core::evaluate(source).unwrap_err().code()
+}
+
+#[test]
+fn each_phase_reports_a_specific_code() {
+    assert_eq!(code_of("name: \"open\n"), ErrorCode::UnterminatedString);
+    assert_eq!(code_of("a: 1 $\n"), ErrorCode::UnexpectedCharacter);
+    assert_eq!(code_of("port 80\n"), ErrorCode::MissingColon);
+    assert_eq!(code_of("ports: [80, 443\n"), ErrorCode::UnclosedDelimiter);
+    assert_eq!(
+        code_of("server {\n    port: 80\n"),
+        ErrorCode::UnterminatedBlock
+    );
+    assert_eq!(code_of("a: 1\na: 2\n"), ErrorCode::DuplicateKey);
+    assert_eq!(code_of("all: [...base]\n"), ErrorCode::UnknownReference);
+    assert_eq!(
+        code_of("addr: ip\"nope\"\n"),
+        ErrorCode::InvalidTaggedLiteral
+    );
+
+    let report = orbit_core::parse_with_recovery("a 1\nb: ~~\nc: [1 2]\n");
+    let codes: Vec<_> = report.errors.iter().map(|error| error.code()).collect();
+    assert_eq!(
+        codes,
+        [
+            ErrorCode::MissingColon,
+            ErrorCode::UnexpectedCharacter,
+            ErrorCode::MissingComma
+        ]
+    );
+}
+
+#[test]
+fn only_nested_profiles_are_misplaced() {
+    assert_eq!(
+        code_of("server {\n    profile prod { port: 80 }\n}\n"),
+        ErrorCode::MisplacedProfile
+    );
+    assert_eq!(code_of("profile prod\nport: 80\n"), ErrorCode::Parse);
+    assert_eq!(
+        code_of("@deprecated\nprofile prod { port: 80 }\n"),
+        ErrorCode::Parse
+    );
+}
+
+#[test]
+fn host_tag_errors_without_a_code_get_the_tag_code() {
+    let options = EvaluationOptions::new().with_tag("port", |_: &str, span: Span| {
+        Err(RuntimeError::new("invalid port", span))
+    });
+    let err = orbit_core::evaluate_with_options("listen: port\"x\"\n", &options).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::InvalidTaggedLiteral);
+}
+
+#[test]
+fn codes_serialize_as_their_stable_string() {
+    let err = orbit_core::evaluate("a: 1\na: 2\n").unwrap_err();
+    let json = serde_json::to_value(&err).expect("error serializes");
+    assert_eq!(json["kind"], "Runtime");
+    assert_eq!(json["code"], "E0301");
+    assert_eq!(ErrorCode::UnterminatedString.to_string(), "E0101");
+}
+
+#[test]
+fn codes_are_unique_and_documented() {
+    let mut seen = HashSet::new();
+    for code in ErrorCode::ALL {
+        assert!(seen.insert(code.as_str()), "{code} is used twice");
+        assert_eq!(code.as_str().parse::<ErrorCode>(), Ok(code));
+        assert!(!code.title().is_empty());
+        assert!(!code.explanation().is_empty());
+    }
+    assert_eq!("e0301".parse::<ErrorCode>(), Ok(ErrorCode::DuplicateKey));
+    assert!("E9999".parse::<ErrorCode>().is_err());
+}
+
+#[test]
+fn every_code_serializes_as_its_string() {
+    for code in ErrorCode::ALL {
+        let json = serde_json::to_value(code).expect("code serializes");
+        assert_eq!(json, code.as_str(), "{code:?}");
+    }
+}
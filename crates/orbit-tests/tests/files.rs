@@ -0,0 +1,141 @@
+use orbit_core::{AstNode, EvaluationOptions, FileDatabase, FileId, Limits, OrbitValue, Span};
+
+fn database() -> (FileDatabase, FileId, FileId) {
+    let mut files = FileDatabase::new();
+    let base = files.add("base.orb", "name: \"orbit\"\nports: [80, 443]\n");
+    let prod = files.add(
+        "prod.orb",
+        "#!orbit 0.3\nname: \"prod\"\nserver {\n  port: 80\n  port: 81\n}\n",
+    );
+    (files, base, prod)
+}
+
+#[test]
+fn every_span_carries_its_file() {
+    let (files, base, prod) = database();
+    assert_ne!(base, prod);
+    assert_eq!(files.path(prod), Some("prod.orb"));
+    assert_eq!(files.files().collect::<Vec<_>>(), [base, prod]);
+
+    let ast = files
+        .parse(prod)
+        .expect("prod is registered")
+        .expect("parse should succeed");
+    assert_eq!(ast.span().file(), prod);
+    let server = &ast.as_body().expect("document body")[1];
+    let AstNode::Entry { value, .. } = &server.as_body().expect("block body")[0] else {
+        panic!("expected entry");
+    };
+    assert_eq!(value.span().file(), prod);
+    assert_eq!(
+        files.location(value.span()).as_deref(),
+        Some("prod.orb:4:9")
+    );
+    assert_eq!(files.snippet(value.span()), Some("  port: 80"));
+
+    // Spans from the single-source entry points stay anonymous and serialize
+    // exactly as before.
+    let plain = orbit_core::parse("a: 1\n").expect("parse should succeed");
+    assert!(plain.span().file().is_anonymous());
+    assert_eq!(files.location(plain.span()), None);
+    let json = serde_json::to_string(&plain).expect("ast serializes");
+    assert!(!json.contains("file"));
+    assert!(
+        serde_json::to_string(&ast)
+            .expect("ast serializes")
+            .contains("\"file\":2")
+    );
+}
+
+#[test]
+fn diagnostics_point_into_the_right_file() {
+    let (mut files, base, prod) = database();
+    let options = EvaluationOptions::default();
+    assert!(
+        files
+            .evaluate(base, &options)
+            .expect("base is registered")
+            .is_ok()
+    );
+    let err = files
+        .evaluate(prod, &options)
+        .expect("prod is registered")
+        .unwrap_err();
+    assert_eq!(err.span().file(), prod);
+    assert_eq!(
+        err.to_string(),
+        "prod.orb:5:3: runtime error: duplicate key 'port'"
+    );
+    assert_eq!(files.snippet(err.span()), Some("  port: 81"));
+
+    let broken = files.add("broken.orb", "a: 1\nb: \"open\n");
+    let err = files
+        .parse(broken)
+        .expect("broken is registered")
+        .unwrap_err();
+    assert_eq!(err.span(), Span::new(8, 13).in_file(broken));
+    assert_eq!(
+        err.to_string(),
+        "broken.orb:2:4: lex error: unterminated string literal"
+    );
+
+    let report = files
+        .parse_with_recovery(broken)
+        .expect("broken is registered");
+    assert_eq!(
+        report
+            .errors
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>(),
+        ["broken.orb:2:4: lex error: unterminated string literal"]
+    );
+}
+
+#[test]
+fn unknown_files_are_not_found() {
+    let (files, _, _) = database();
+    let unknown = FileId::new(7);
+    assert!(files.parse(unknown).is_none());
+    assert!(files.parse_with_recovery(unknown).is_none());
+    assert!(
+        files
+            .evaluate(unknown, &EvaluationOptions::default())
+            .is_none()
+    );
+    assert!(files.parse(FileId::default()).is_none());
+}
+
+#[test]
+fn evaluation_takes_options_and_every_document() {
+    let mut files = FileDatabase::new();
+    let file = files.add(
+        "multi.orb",
+        "#!orbit 0.2\nname: \"alpha\"\nprofile prod {\n  name: \"beta\"\n}\n---\nport: 80\nprofile prod {\n  port: 81\n}\n",
+    );
+    let options = EvaluationOptions::default().with_profile("prod");
+    let value = files
+        .evaluate_documents(file, &options)
+        .expect("file is registered")
+        .expect("evaluation should succeed");
+    assert_eq!(value.len(), 2);
+    assert_eq!(
+        value[0].get_path(&["name"]),
+        Some(&OrbitValue::String("beta".to_string()))
+    );
+    assert_eq!(
+        value[1],
+        orbit_core::evaluate("port: 81\n").expect("evaluates")
+    );
+
+    let options = EvaluationOptions::default().with_limits(Limits {
+        max_string_length: 4,
+        ..Limits::default()
+    });
+    let err = files
+        .evaluate_documents(file, &options)
+        .expect("file is registered")
+        .unwrap_err();
+    assert_eq!(err.span().file(), file);
+    assert!(err.to_string().starts_with("multi.orb:2:7: "));
+}
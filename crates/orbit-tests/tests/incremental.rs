@@ -0,0 +1,252 @@
+use orbit_core::{ErrorCode, Limits, Span, SyntaxNode, SyntaxTree, TextEdit};
+
+const BASE: &str = "#!orbit 0.3\n# settings\nname: \"orbit\"\n\n## Server settings.\n@deprecated(\"use http\")\nserver {\n    host: \"localhost\"\n    ports: [80, 443]\n    tls {\n        cert: \"a.pem\" # inline\n    }\n}\n\nlimits: { cpu: 2, memory: \"1Gi\" }\nfeatures: [\"a\", \"b\"]\nprofile prod {\n    name: \"orbit-prod\"\n}\n";
+
+const FRAGMENTS: &[&str] = &[
+    "a",
+    "1",
+    " ",
+    "\n",
+    "#",
+    "## doc\n",
+    "\"",
+    "{",
+    "}",
+    "[",
+    "]",
+    ",",
+    ":",
+    "x: 2\n",
+    "key: \"value\"\n",
+    "blk {\n    y: 1\n}\n",
+    "@deprecated ",
+    "...limits",
+    "profile p {\n",
+    "-",
+    ".5",
+    "b64\"AA==\"",
+    "---\n",
+];
+
+// Small xorshift generator so the test is deterministic without extra deps.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, bound: usize) -> usize {
+        (self.next() % bound as u64) as usize
+    }
+}
+
+fn random_edit(rng: &mut Rng, source: &str) -> TextEdit {
+    let start = rng.below(source.len() + 1);
+    let end = match rng.below(3) {
+        0 => start,
+        _ => (start + rng.below(12)).min(source.len()),
+    };
+    let replacement = match rng.below(3) {
+        0 => String::new(),
+        _ => FRAGMENTS[rng.below(FRAGMENTS.len())].to_string(),
+    };
+    TextEdit::new(Span::new(start, end), replacement)
+}
+
+// The reparsed tree must be the one a full parse builds, down to every
+// token, with the same diagnostics and AST.
+fn assert_same_tree(actual: &SyntaxTree, expected: &SyntaxTree, context: &str) {
+    assert_eq!(
+        format!("{:?}", actual.root()),
+        format!("{:?}", expected.root()),
+        "{context}"
+    );
+    let messages = |tree: &SyntaxTree| {
+        tree.errors()
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(messages(actual), messages(expected), "{context}");
+    assert_eq!(
+        format!("{:?}", actual.warnings()),
+        format!("{:?}", expected.warnings()),
+        "{context}"
+    );
+    assert_eq!(actual.documents(), expected.documents(), "{context}");
+}
+
+// Nodes of `tree` that are not shared with `previous`.
+fn fresh_nodes(tree: &SyntaxTree, previous: &SyntaxTree) -> usize {
+    fn collect<'t>(node: SyntaxNode<'t>, nodes: &mut Vec<SyntaxNode<'t>>) {
+        nodes.push(node);
+        node.child_nodes().for_each(|child| collect(child, nodes));
+    }
+    fn count(node: SyntaxNode<'_>, old: &[SyntaxNode<'_>]) -> usize {
+        if old.iter().any(|other| other.is_same(&node)) {
+            return 0;
+        }
+        1 + node
+            .child_nodes()
+            .map(|child| count(child, old))
+            .sum::<usize>()
+    }
+    let mut old = Vec::new();
+    collect(previous.root(), &mut old);
+    count(tree.root(), &old)
+}
+
+#[test]
+fn reparse_matches_full_parse_for_random_edits() {
+    let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+    let mut source = BASE.to_string();
+    let mut tree = orbit_core::parse_cst(&source);
+    let mut accepted = 0;
+    for step in 0..4000 {
+        let edit = random_edit(&mut rng, &source);
+        let edited = edit.apply(&source);
+        let expected = orbit_core::parse_cst(&edited);
+        let actual = orbit_core::reparse(&tree, &edited, &edit);
+        assert_same_tree(
+            &actual,
+            &expected,
+            &format!("step {step}: edit {edit:?} on {source:?}"),
+        );
+        if actual.errors().is_empty() && edited.len() < 2000 {
+            source = edited;
+            tree = actual;
+            accepted += 1;
+        }
+    }
+    assert!(
+        accepted > 500,
+        "only {accepted} edits kept the source valid"
+    );
+}
+
+#[test]
+fn edits_inside_a_block_share_every_other_node() {
+    let source = "a: 1\nserver {\n    port: 80\n    host: \"h\"\n}\nb: 2\n";
+    let tree = orbit_core::parse_cst(source);
+    let edit = TextEdit::new(Span::new(24, 26), "8080");
+    let edited = edit.apply(source);
+    assert_eq!(
+        edited,
+        "a: 1\nserver {\n    port: 8080\n    host: \"h\"\n}\nb: 2\n"
+    );
+    let reparsed = orbit_core::reparse(&tree, &edited, &edit);
+    assert_same_tree(&reparsed, &orbit_core::parse_cst(&edited), "block edit");
+
+    fn statements(tree: &SyntaxTree) -> Vec<SyntaxNode<'_>> {
+        tree.root()
+            .child_nodes()
+            .flat_map(|document| document.child_nodes().collect::<Vec<_>>())
+            .collect()
+    }
+    let (old, new) = (statements(&tree), statements(&reparsed));
+    assert!(old[0].is_same(&new[0]));
+    assert!(!old[1].is_same(&new[1]));
+    assert!(old[2].is_same(&new[2]));
+    // Behind the edit the node is shared, only its position moved.
+    assert_eq!(
+        new[2].span(),
+        Span::new(old[2].span().start + 2, old[2].span().end + 2)
+    );
+}
+
+#[test]
+fn reparse_cost_follows_the_edit_not_the_source() {
+    let fresh = |entries: usize| {
+        let mut source = String::from("#!orbit 0.3\n");
+        for index in 0..entries {
+            source.push_str(&format!("key{index}: {index}\n"));
+            if index == entries / 2 {
+                source.push_str("server {\n    port: 80\n}\n");
+            }
+        }
+        let tree = orbit_core::parse_cst(&source);
+        let at = source.find("port: 80").expect("port entry") + "port: ".len();
+        let nested = TextEdit::new(Span::new(at, at + 2), "8080");
+        let at = source.find("key7:").expect("top-level entry");
+        let top_level = TextEdit::new(Span::new(at, at), "extra: true\n");
+        [nested, top_level].map(|edit| {
+            let edited = edit.apply(&source);
+            let reparsed = orbit_core::reparse(&tree, &edited, &edit);
+            assert_same_tree(&reparsed, &orbit_core::parse_cst(&edited), "sized edit");
+            fresh_nodes(&reparsed, &tree)
+        })
+    };
+    let small = fresh(50);
+    let large = fresh(2000);
+    assert_eq!(small, large);
+    assert!(small.iter().all(|&count| count <= 10), "{small:?}");
+}
+
+#[test]
+fn doc_comments_inserted_between_nodes_attach() {
+    let source = "a: 1\n\nb: 2\n";
+    let tree = orbit_core::parse_cst(source);
+    let edit = TextEdit::new(Span::new(6, 6), "## About b.\n");
+    let edited = edit.apply(source);
+    let reparsed = orbit_core::reparse(&tree, &edited, &edit);
+    let body = reparsed.documents()[0].as_body().expect("document body");
+    assert_eq!(body[1].docs()[0].text, "About b.");
+    assert_same_tree(&reparsed, &orbit_core::parse_cst(&edited), "doc comment");
+}
+
+#[test]
+fn invalid_edits_report_the_full_parse_errors() {
+    let source = "a: 1\nserver {\n    port: 80\n}\n";
+    let tree = orbit_core::parse_cst(source);
+    let edit = TextEdit::new(Span::new(21, 21), "# ");
+    let edited = edit.apply(source);
+    let reparsed = orbit_core::reparse(&tree, &edited, &edit);
+    assert!(!reparsed.errors().is_empty());
+    assert_same_tree(&reparsed, &orbit_core::parse_cst(&edited), "invalid edit");
+}
+
+#[test]
+fn edits_in_deep_blocks_keep_the_nesting_limit() {
+    let depth = 127;
+    let mut source = String::new();
+    for level in 0..depth {
+        source += &format!("{}b{level} {{\n", "  ".repeat(level));
+    }
+    source += &format!("{}x: 1\n", "  ".repeat(depth));
+    for level in (0..depth).rev() {
+        source += &format!("{}}}\n", "  ".repeat(level));
+    }
+    let tree = orbit_core::parse_cst(&source);
+    assert!(tree.errors().is_empty(), "{:?}", tree.errors());
+
+    let at = source.find("x: 1").expect("innermost entry") + "x: ".len();
+    let edit = TextEdit::new(Span::new(at, at + 1), "[[1]]");
+    let edited = edit.apply(&source);
+    let full = orbit_core::parse_cst(&edited);
+    assert_eq!(full.errors()[0].code(), ErrorCode::NestingTooDeep);
+    let reparsed = orbit_core::reparse(&tree, &edited, &edit);
+    assert_same_tree(&reparsed, &full, "deep edit");
+}
+
+#[test]
+fn reparse_keeps_the_limits_of_the_previous_tree() {
+    let limits = Limits::default().with_max_depth(2);
+    let source = "server {\n    port: 80\n}\n";
+    let tree = SyntaxTree::parse_with_limits(source, limits);
+    assert!(tree.errors().is_empty());
+
+    let edit = TextEdit::new(Span::new(19, 21), "[[80]]");
+    let edited = edit.apply(source);
+    let reparsed = orbit_core::reparse(&tree, &edited, &edit);
+    assert_eq!(reparsed.limits(), limits);
+    assert_eq!(reparsed.errors()[0].code(), ErrorCode::NestingTooDeep);
+    assert_same_tree(
+        &reparsed,
+        &SyntaxTree::parse_with_limits(&edited, limits),
+        "limited edit",
+    );
+}
@@ -0,0 +1,112 @@
+use orbit_core::{AstNode, ErrorCode, EvaluationOptions, Limits, OrbitNumber, Span, ValueNode};
+
+#[test]
+fn deeply_nested_lists_report_an_error_instead_of_overflowing() {
+    let source = format!("a: {}\n", "[".repeat(100_000));
+    let err = orbit_core::evaluate(&source).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::NestingTooDeep);
+    assert_eq!(err.span().start, 3 + Limits::DEFAULT.max_depth);
+
+    let report = orbit_core::parse_with_recovery(&source);
+    assert!(
+        report
+            .errors
+            .iter()
+            .any(|error| error.code() == ErrorCode::NestingTooDeep)
+    );
+
+    let within = format!("a: {}{}\n", "[".repeat(100), "]".repeat(100));
+    assert!(orbit_core::evaluate(&within).is_ok());
+}
+
+#[test]
+fn nesting_counts_blocks_and_object_literals() {
+    let limits = Limits::new().with_max_depth(3);
+    let options = EvaluationOptions::new().with_limits(limits);
+    let ok = "a {\n    b {\n        c: { d: 1 }\n    }\n}\n";
+    assert!(orbit_core::evaluate_with_options(ok, &options).is_ok());
+
+    let deep = "a {\n    b {\n        c: { d: [1] }\n    }\n}\n";
+    let err = orbit_core::evaluate_with_options(deep, &options).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::NestingTooDeep);
+    assert_eq!(&deep[err.span().start..err.span().end], "[");
+}
+
+#[test]
+fn nested_unterminated_blocks_recover_without_reparsing() {
+    // Every block hands the ones after it back to its parent; parsing them
+    // again at each level used to take exponential time.
+    let source: String = (0..100).map(|i| format!("b{i} {{\n")).collect();
+    let report = orbit_core::parse_with_recovery(&source);
+    assert_eq!(report.errors.len(), 100);
+    assert!(
+        report
+            .errors
+            .iter()
+            .all(|error| error.code() == ErrorCode::UnterminatedBlock)
+    );
+}
+
+#[test]
+fn lexer_enforces_token_and_string_limits() {
+    let options = EvaluationOptions::new().with_limits(Limits::new().with_max_tokens(8));
+    let err = orbit_core::evaluate_with_options("a: 1\nb: 2\nc: 3\n", &options).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::TooManyTokens);
+    assert_eq!(err.span(), Span::new(10, 10));
+
+    let options = EvaluationOptions::new().with_limits(Limits::new().with_max_string_length(4));
+    assert!(orbit_core::evaluate_with_options("a: \"four\"\n", &options).is_ok());
+    let err = orbit_core::evaluate_with_options("a: \"fives\"\n", &options).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::StringTooLong);
+    assert_eq!(err.span(), Span::new(3, 10));
+
+    // Text past the limit, escapes included, still ends at the closing quote.
+    let long = format!("a: \"{}\"\nb: 1\n", "x\\\"".repeat(1000));
+    let err = orbit_core::evaluate_with_options(&long, &options).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::StringTooLong);
+    assert_eq!(
+        err.span(),
+        Span::new(3, long.find('\n').expect("line break"))
+    );
+}
+
+#[test]
+fn repeated_spreads_cannot_explode_the_output() {
+    let mut source = String::from("l0: [1, 2, 3, 4, 5, 6, 7, 8]\n");
+    for level in 1..40 {
+        let previous = level - 1;
+        source.push_str(&format!("l{level}: [...l{previous}, ...l{previous}]\n"));
+    }
+    let err = orbit_core::evaluate(&source).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::TooManyValues);
+
+    let options = EvaluationOptions::new().with_limits(Limits::new().with_max_output_nodes(5));
+    let objects = "base: { a: 1, b: 2 }\ncopy: { ...base }\n";
+    let err = orbit_core::evaluate_with_options(objects, &options).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::TooManyValues);
+    assert_eq!(&objects[err.span().start..err.span().end], "...base");
+}
+
+#[test]
+fn evaluator_limits_hand_built_trees() {
+    let span = Span::new(0, 0);
+    let mut value = ValueNode::Number {
+        value: OrbitNumber::Integer(1),
+        span,
+    };
+    for _ in 0..200 {
+        value = ValueNode::List {
+            items: vec![value],
+            span,
+        };
+    }
+    let ast = AstNode::Entry {
+        key: "a".into(),
+        value,
+        span,
+        docs: Vec::new(),
+        annotations: Vec::new(),
+    };
+    let err = orbit_core::evaluate_ast(&ast).unwrap_err();
+    assert_eq!(err.code, ErrorCode::NestingTooDeep);
+}
@@ -0,0 +1,124 @@
+use orbit_core::{ErrorCode, EvaluationOptions, EvaluationReport, Limits, OrbitNumber, OrbitValue};
+
+fn evaluate(source: &str, options: &EvaluationOptions) -> EvaluationReport {
+    let report = orbit_core::parse_with_recovery(source);
+    orbit_core::evaluate_parse_report(report, options)
+}
+
+fn codes(report: &EvaluationReport) -> Vec<ErrorCode> {
+    report.errors.iter().map(|error| error.code()).collect()
+}
+
+fn int(value: i64) -> OrbitValue {
+    OrbitValue::Number(OrbitNumber::Integer(value))
+}
+
+#[test]
+fn well_formed_values_survive_parse_errors() {
+    let source =
+        "server {\n    host \"x\"\n    port: 80\n    tags: [1, ~, 3]\n}\nname: ~~\nok: true\n";
+    let report = evaluate(source, &EvaluationOptions::default());
+    assert_eq!(
+        codes(&report),
+        [
+            ErrorCode::MissingColon,
+            ErrorCode::UnexpectedCharacter,
+            ErrorCode::UnexpectedCharacter
+        ]
+    );
+    let value = &report.value;
+    assert_eq!(
+        value.get_path(&["server", "host"]),
+        Some(&OrbitValue::String("x".into()))
+    );
+    assert_eq!(value.get_path(&["server", "port"]), Some(&int(80)));
+    assert_eq!(
+        value.get_path(&["server", "tags"]),
+        Some(&OrbitValue::List(vec![int(1), OrbitValue::Null, int(3)]))
+    );
+    assert_eq!(value.get_path(&["name"]), None);
+    assert_eq!(value.get_path(&["ok"]), Some(&OrbitValue::Bool(true)));
+    assert!(report.is_broken(&["server", "tags", "1"]));
+    assert!(report.is_broken(&["name"]));
+    assert_eq!(report.broken.len(), 2);
+}
+
+#[test]
+fn broken_list_items_keep_their_position() {
+    let source = "base: [1, 2]
+list: [...base, ~, 4, [5, ~]]
+";
+    let report = evaluate(source, &EvaluationOptions::default());
+    let list = report.value.get_path(&["list"]).expect("list survives");
+    assert_eq!(
+        list,
+        &OrbitValue::List(vec![
+            int(1),
+            int(2),
+            OrbitValue::Null,
+            int(4),
+            OrbitValue::List(vec![int(5), OrbitValue::Null]),
+        ])
+    );
+    assert!(report.is_broken(&["list", "2"]));
+    assert!(report.is_broken(&["list", "4", "1"]));
+    assert_eq!(
+        serde_json::to_string(list).expect("list serializes"),
+        "[1,2,null,4,[5,null]]"
+    );
+}
+
+#[test]
+fn runtime_errors_are_merged_with_parse_errors_in_source_order() {
+    let source = "a: 1\na: 2\nb: [...missing]\nc 3\nd: { x: 1, x: 2 }\n";
+    let report = evaluate(source, &EvaluationOptions::default());
+    assert_eq!(
+        codes(&report),
+        [
+            ErrorCode::DuplicateKey,
+            ErrorCode::UnknownReference,
+            ErrorCode::MissingColon,
+            ErrorCode::DuplicateKey
+        ]
+    );
+    assert_eq!(report.value.get_path(&["a"]), Some(&int(1)));
+    assert_eq!(
+        report.value.get_path(&["b"]),
+        Some(&OrbitValue::List(Vec::new()))
+    );
+    assert_eq!(report.value.get_path(&["c"]), Some(&int(3)));
+    assert_eq!(report.value.get_path(&["d", "x"]), Some(&int(1)));
+    assert_eq!(report.broken, [vec!["b".to_string()]]);
+}
+
+#[test]
+fn broken_entries_without_a_key_mark_their_block() {
+    let source = "server {\n    : 80\n    port: 81\n}\n";
+    let report = evaluate(source, &EvaluationOptions::default());
+    assert_eq!(codes(&report), [ErrorCode::ExpectedIdentifier]);
+    assert!(report.is_broken(&["server"]));
+    assert_eq!(report.value.get_path(&["server", "port"]), Some(&int(81)));
+}
+
+#[test]
+fn profile_and_limit_errors_are_reported() {
+    let source = "port: 80\nprofile prod { port: 443 }\nprofile prod { port: 8443 }\n";
+    let options = EvaluationOptions::new()
+        .with_profile("prod")
+        .with_profile("staging");
+    let report = evaluate(source, &options);
+    assert_eq!(
+        codes(&report),
+        [ErrorCode::UnknownProfile, ErrorCode::DuplicateProfile]
+    );
+    assert_eq!(report.value.get_path(&["port"]), Some(&int(443)));
+
+    let options = EvaluationOptions::new().with_limits(Limits::new().with_max_output_nodes(2));
+    let report = evaluate("a: 1\nb: 2\nc: 3\nd: ~\n", &options);
+    assert_eq!(
+        codes(&report),
+        [ErrorCode::TooManyValues, ErrorCode::UnexpectedCharacter]
+    );
+    assert_eq!(report.value, OrbitValue::Object(Default::default()));
+    assert!(report.is_broken(&[]));
+}
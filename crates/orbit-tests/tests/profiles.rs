@@ -0,0 +1,81 @@
+use orbit_core::{EvaluationOptions, OrbitNumber, OrbitValue};
+
+const SAMPLE: &str = r#"
+server {
+    host: "127.0.0.1"
+    port: 8080
+    tls {
+        enabled: false
+        cert: "dev.pem"
+    }
+}
+
+profile prod {
+    server {
+        host: "0.0.0.0"
+        tls {
+            enabled: true
+        }
+    }
+}
+
+profile eu {
+    server {
+        host: "eu.example.com"
+    }
+    region: "eu-west-1"
+}
+"#;
+
+#[test]
+fn profiles_are_ignored_unless_selected() {
+    let value = orbit_core::evaluate(SAMPLE).expect("evaluation should succeed");
+    assert_eq!(
+        value.get_path(&["server", "host"]),
+        Some(&OrbitValue::String("127.0.0.1".into()))
+    );
+    assert_eq!(value.get_path(&["region"]), None);
+}
+
+#[test]
+fn selected_profiles_merge_in_order() {
+    let options = EvaluationOptions::new()
+        .with_profile("prod")
+        .with_profile("eu");
+    let value =
+        orbit_core::evaluate_with_options(SAMPLE, &options).expect("evaluation should succeed");
+    assert_eq!(
+        value.get_path(&["server", "host"]),
+        Some(&OrbitValue::String("eu.example.com".into()))
+    );
+    assert_eq!(
+        value.get_path(&["server", "port"]),
+        Some(&OrbitValue::Number(OrbitNumber::Integer(8080)))
+    );
+    assert_eq!(
+        value.get_path(&["server", "tls", "enabled"]),
+        Some(&OrbitValue::Bool(true))
+    );
+    assert_eq!(
+        value.get_path(&["server", "tls", "cert"]),
+        Some(&OrbitValue::String("dev.pem".into()))
+    );
+    assert_eq!(
+        value.get_path(&["region"]),
+        Some(&OrbitValue::String("eu-west-1".into()))
+    );
+}
+
+#[test]
+fn unknown_profile_is_an_error() {
+    let options = EvaluationOptions::new().with_profile("staging");
+    let err = orbit_core::evaluate_with_options(SAMPLE, &options).unwrap_err();
+    assert!(err.to_string().contains("unknown profile 'staging'"));
+}
+
+#[test]
+fn nested_profiles_are_rejected() {
+    let err = orbit_core::parse("app {\n    profile prod {\n        debug: false\n    }\n}\n")
+        .unwrap_err();
+    assert!(err.to_string().contains("only allowed at the top level"));
+}
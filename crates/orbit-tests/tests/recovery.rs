@@ -0,0 +1,171 @@
+use orbit_core::{AstNode, CoreError, ParseReport, ValueNode};
+
+fn recover(source: &str) -> ParseReport {
+    orbit_core::parse_with_recovery(source)
+}
+
+fn messages(report: &ParseReport) -> Vec<&str> {
+    report.errors.iter().map(|error| error.message()).collect()
+}
+
+fn keys(body: &[AstNode]) -> Vec<&str> {
+    body.iter()
+        .filter_map(|node| match node {
+            AstNode::Entry { key, .. } => Some(key.as_str()),
+            AstNode::Block { name, .. } => Some(name.as_str()),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn missing_colon_inside_a_block_keeps_the_block() {
+    let report = recover("server {\n    host \"localhost\"\n    port: 80\n}\nname: \"x\"\n");
+    assert_eq!(messages(&report), ["expected ':' after identifier"]);
+    let body = report.document.as_body().expect("document body");
+    assert_eq!(keys(body), ["server", "name"]);
+    let server = body[0].as_body().expect("block body");
+    assert_eq!(keys(server), ["host", "port"]);
+    assert!(matches!(
+        &server[0],
+        AstNode::Entry { value: ValueNode::String { value, .. }, .. } if value == "localhost"
+    ));
+}
+
+#[test]
+fn missing_commas_report_one_error_each() {
+    let report =
+        recover("ports: [80 443]\nlimits: {\n    cpu: 2\n    memory: \"1Gi\"\n}\nname: \"x\"\n");
+    assert_eq!(
+        messages(&report),
+        [
+            "expected ',' between list items",
+            "expected ',' between object entries"
+        ]
+    );
+    let body = report.document.as_body().expect("document body");
+    assert_eq!(keys(body), ["ports", "limits", "name"]);
+    let AstNode::Entry {
+        value: ValueNode::Object { entries, .. },
+        ..
+    } = &body[1]
+    else {
+        panic!("expected object entry");
+    };
+    assert_eq!(entries.len(), 2);
+}
+
+#[test]
+fn unclosed_containers_end_where_the_enclosing_scope_resumes() {
+    let report = recover(
+        "server {\n    ports: [1, 2\n    limits: { cpu: 2\n}\nclient {\n    retries: 3\n\nname: \"x\"\n",
+    );
+    assert_eq!(
+        messages(&report),
+        [
+            "expected ']' to close list",
+            "expected '}' to close object",
+            "unterminated block"
+        ]
+    );
+    let body = report.document.as_body().expect("document body");
+    assert_eq!(keys(body), ["server", "client", "name"]);
+    assert_eq!(
+        keys(body[0].as_body().expect("block body")),
+        ["ports", "limits"]
+    );
+    assert_eq!(keys(body[1].as_body().expect("block body")), ["retries"]);
+}
+
+#[test]
+fn unreadable_input_becomes_placeholder_nodes() {
+    let source = "server {\n    a: ]\n    1 2 3\n    b: 2\n}\nc: 3\n";
+    let report = recover(source);
+    assert_eq!(
+        messages(&report),
+        [
+            "unexpected token ] while parsing value",
+            "expected identifier"
+        ]
+    );
+    let server = report.document.as_body().expect("document body")[0]
+        .as_body()
+        .expect("block body");
+    assert!(matches!(
+        &server[0],
+        AstNode::Entry {
+            value: ValueNode::Error { .. },
+            ..
+        }
+    ));
+    let AstNode::Error { span } = &server[1] else {
+        panic!("expected error node");
+    };
+    assert_eq!(&source[span.start..span.end], "1 2 3");
+    assert_eq!(keys(server), ["a", "b"]);
+    assert!(orbit_core::evaluate_ast(&report.document).is_err());
+}
+
+#[test]
+fn every_lexical_error_is_reported_in_one_pass() {
+    let source = "a: 1 $\nb: \"open\nc: hex\"zz\"\nd: [1, ?, 2]\ne: 5\n";
+    let report = recover(source);
+    assert_eq!(
+        messages(&report),
+        [
+            "unexpected character '$'",
+            "unterminated string literal",
+            "invalid hex bytes literal",
+            "unexpected character '?'"
+        ]
+    );
+    assert!(
+        report
+            .errors
+            .iter()
+            .all(|error| matches!(error, CoreError::Lex(_)))
+    );
+    let body = report.document.as_body().expect("document body");
+    assert_eq!(keys(body), ["a", "b", "c", "d", "e"]);
+    let AstNode::Entry {
+        value: ValueNode::List { items, .. },
+        ..
+    } = &body[4]
+    else {
+        panic!("expected list entry");
+    };
+    assert!(matches!(items[1], ValueNode::Error { .. }));
+
+    // The strict entry points still stop at the first problem.
+    let err = orbit_core::parse(source).unwrap_err();
+    assert_eq!(err.message(), "unexpected character '$'");
+}
+
+#[test]
+fn lexical_and_parse_errors_are_ordered_by_position() {
+    let report = recover("a 1\nb: ~~\nc: 3\n");
+    let errors: Vec<_> = report
+        .errors
+        .iter()
+        .map(|error| (error.span().start, error.message()))
+        .collect();
+    assert_eq!(
+        errors,
+        [
+            (2, "expected ':' after identifier"),
+            (7, "unexpected characters '~~'"),
+        ]
+    );
+}
+
+#[test]
+fn formatting_with_recovery_keeps_the_text_of_error_nodes() {
+    let source = "b: 2\na: 'x'\nserver {\n  port: 80 $ 3\n  host:   \"h\"\n}\n";
+    let report = orbit_fmt::format_with_recovery(source);
+    assert_eq!(
+        report.output,
+        "b: 2\na: 'x'\nserver {\n    port: 80\n    $\n    3\n    host: \"h\"\n}\n"
+    );
+    assert_eq!(report.errors.len(), 3);
+    assert!(orbit_fmt::format_source(source).is_err());
+}
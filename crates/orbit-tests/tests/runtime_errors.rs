@@ -0,0 +1,82 @@
+use orbit_core::{ErrorCode, EvaluationOptions, EvaluationReport, OrbitNumber, OrbitValue};
+
+fn int(value: i64) -> OrbitValue {
+    OrbitValue::Number(OrbitNumber::Integer(value))
+}
+
+fn codes(report: &EvaluationReport) -> Vec<ErrorCode> {
+    report.errors.iter().map(|error| error.code()).collect()
+}
+
+#[test]
+fn every_duplicate_is_reported_and_the_first_definition_kept() {
+    let source =
+        "a: 1\na: 2\nserver {\n    port: 80\n    port: 81\n}\nserver {}\nb: { x: 1, x: 2 }\n";
+    let report =
+        orbit_core::evaluate_report(source, &EvaluationOptions::default()).expect("source parses");
+    assert_eq!(codes(&report), [ErrorCode::DuplicateKey; 4]);
+    let messages: Vec<_> = report.errors.iter().map(|error| error.message()).collect();
+    assert_eq!(
+        messages,
+        [
+            "duplicate key 'a'",
+            "duplicate key 'port'",
+            "duplicate block 'server'",
+            "duplicate key 'x' inside object literal"
+        ]
+    );
+    assert_eq!(report.value.get_path(&["a"]), Some(&int(1)));
+    assert_eq!(report.value.get_path(&["server", "port"]), Some(&int(80)));
+    assert_eq!(report.value.get_path(&["b", "x"]), Some(&int(1)));
+    assert!(report.broken.is_empty());
+}
+
+#[test]
+fn unresolvable_spreads_are_skipped_and_mark_their_container() {
+    let source = "base: [1]\nlist: [0, ...missing, ...base]\nobj: { ...base, y: 2 }\nlast: true\n";
+    let report =
+        orbit_core::evaluate_report(source, &EvaluationOptions::default()).expect("source parses");
+    assert_eq!(
+        codes(&report),
+        [ErrorCode::UnknownReference, ErrorCode::InvalidSpread]
+    );
+    assert_eq!(
+        report.value.get_path(&["list"]),
+        Some(&OrbitValue::List(vec![int(0), int(1)]))
+    );
+    assert_eq!(report.value.get_path(&["obj", "y"]), Some(&int(2)));
+    assert_eq!(
+        report.value.get_path(&["last"]),
+        Some(&OrbitValue::Bool(true))
+    );
+    assert!(report.is_broken(&["list"]));
+    assert!(report.is_broken(&["obj"]));
+}
+
+#[test]
+fn ast_reports_keep_warnings_next_to_errors() {
+    let ast = orbit_core::parse("@deprecated\nold: 1\nport: dur\"5s\"\nold: 2\n").expect("parses");
+    let report = orbit_core::evaluate_ast_report(&ast, &EvaluationOptions::default());
+    assert_eq!(
+        codes(&report),
+        [ErrorCode::UnknownTag, ErrorCode::DuplicateKey]
+    );
+    assert_eq!(report.warnings.len(), 1);
+    assert!(report.is_broken(&["port"]));
+    assert_eq!(report.value.get_path(&["old"]), Some(&int(1)));
+}
+
+#[test]
+fn clean_sources_match_evaluate_and_syntax_errors_still_fail() {
+    let source = "server {\n    port: 80\n}\n";
+    let report =
+        orbit_core::evaluate_report(source, &EvaluationOptions::default()).expect("source parses");
+    assert!(report.errors.is_empty());
+    assert_eq!(
+        report.value,
+        orbit_core::evaluate(source).expect("evaluates")
+    );
+
+    let err = orbit_core::evaluate_report("port 80\n", &EvaluationOptions::default()).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::MissingColon);
+}
@@ -0,0 +1,83 @@
+use orbit_core::{
+    EvaluationOptions, OrbitValue,
+    serializer::{to_json_string, to_msgpack_bytes, to_yaml_string},
+};
+
+const SAMPLE: &str = r#"
+database {
+    user: "orbit"
+    @secret password: "hunter2"
+    @secret keys {
+        primary: "k1"
+    }
+}
+"#;
+
+#[test]
+fn secrets_are_redacted_by_serializers_and_debug() {
+    let value = orbit_core::evaluate(SAMPLE).expect("evaluation should succeed");
+
+    let json = to_json_string(&value).expect("json serialization");
+    assert_eq!(
+        json,
+        r#"{"database":{"user":"orbit","password":"***","keys":"***"}}"#
+    );
+    let yaml = to_yaml_string(&value).expect("yaml serialization");
+    assert!(yaml.contains("password: '***'"));
+    let msgpack = to_msgpack_bytes(&value).expect("msgpack serialization");
+    assert!(!msgpack.windows(7).any(|window| window == b"hunter2"));
+    assert!(!format!("{value:?}").contains("hunter2"));
+}
+
+#[test]
+fn secrets_are_only_revealed_explicitly() {
+    let value = orbit_core::evaluate(SAMPLE).expect("evaluation should succeed");
+    let Some(OrbitValue::Secret(secret)) = value.get_path(&["database", "password"]) else {
+        panic!("password should be a secret");
+    };
+    assert_eq!(secret.expose(), &OrbitValue::String("hunter2".into()));
+
+    let revealed = value.reveal_secrets();
+    assert_eq!(
+        revealed.get_path(&["database", "keys", "primary"]),
+        Some(&OrbitValue::String("k1".into()))
+    );
+    let json = to_json_string(&revealed).expect("json serialization");
+    assert!(json.contains("hunter2"));
+}
+
+#[test]
+fn profile_overrides_of_secrets_stay_redacted() {
+    let source = r#"
+@secret password: "dev"
+@secret keys {
+    primary: "k1"
+}
+
+profile prod {
+    password: "prod-password"
+    keys {
+        backup: "k2"
+    }
+}
+"#;
+    let options = EvaluationOptions::new().with_profile("prod");
+    let value =
+        orbit_core::evaluate_with_options(source, &options).expect("evaluation should succeed");
+    let json = to_json_string(&value).expect("json serialization");
+    assert_eq!(json, r#"{"password":"***","keys":"***"}"#);
+
+    let revealed = value.reveal_secrets();
+    assert_eq!(
+        revealed.get_path(&["password"]),
+        Some(&OrbitValue::String("prod-password".into()))
+    );
+    assert_eq!(
+        revealed.get_path(&["keys", "primary"]),
+        Some(&OrbitValue::String("k1".into()))
+    );
+    assert_eq!(
+        revealed.get_path(&["keys", "backup"]),
+        Some(&OrbitValue::String("k2".into()))
+    );
+}
@@ -0,0 +1,60 @@
+use std::sync::Arc;
+
+use orbit_core::{LineCol, LineIndex, SourceMap, Span};
+
+#[test]
+fn offsets_resolve_to_one_based_lines_and_columns() {
+    let source = "a: 1\r\nb: 2\rc: 3\n\nd: 4";
+    let index = LineIndex::new(source);
+    assert_eq!(index.line_count(), 5);
+    let at = |offset| index.line_col(source, offset);
+    assert_eq!(at(0), LineCol { line: 1, column: 1 });
+    assert_eq!(at(4), LineCol { line: 1, column: 5 });
+    assert_eq!(at(6), LineCol { line: 2, column: 1 });
+    assert_eq!(at(11), LineCol { line: 3, column: 1 });
+    assert_eq!(at(16), LineCol { line: 4, column: 1 });
+    assert_eq!(at(source.len()), LineCol { line: 5, column: 5 });
+    assert_eq!(at(1000), LineCol { line: 5, column: 5 });
+    assert_eq!(index.line_start(3), Some(11));
+    assert_eq!(index.line_start(6), None);
+}
+
+#[test]
+fn columns_count_characters_or_utf16_units() {
+    let source = "név: \"🚀x\"";
+    let index = LineIndex::new(source);
+    let x = source.find('x').expect("x in source");
+    assert_eq!(index.line_col(source, x).column, 8);
+    assert_eq!(index.line_col_utf16(source, x).column, 9);
+    // An offset inside a multi-byte character resolves to that character.
+    assert_eq!(index.line_col(source, 2).column, 2);
+}
+
+#[test]
+fn errors_print_file_line_and_column_once_a_source_is_attached() {
+    let source = "name: \"orbit\"\nserver {\n    port 80\n}\n";
+    let err = orbit_core::parse(source).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "parse error at byte range Span { start: 32, end: 34 }: expected ':' after identifier"
+    );
+    let source_map = Arc::new(SourceMap::new("app.orb", source));
+    assert_eq!(source_map.location(Span::new(32, 34)), "app.orb:3:10");
+    assert_eq!(
+        err.with_source(source_map.clone()).to_string(),
+        "app.orb:3:10: parse error: expected ':' after identifier"
+    );
+
+    let err = orbit_core::evaluate("a: 1\na: 2\n").unwrap_err();
+    let source_map = Arc::new(SourceMap::new("dup.orb", "a: 1\na: 2\n"));
+    assert_eq!(
+        err.with_source(source_map).to_string(),
+        "dup.orb:2:1: runtime error: duplicate key 'a'"
+    );
+}
+
+#[test]
+fn snippets_end_on_a_character_boundary() {
+    let map = SourceMap::new("x", "a: \"é\"\n");
+    assert_eq!(map.snippet(Span::new(0, 5)), "a: \"é\"");
+}
@@ -0,0 +1,69 @@
+use orbit_core::{EvaluationOptions, OrbitValue};
+
+const SAMPLE: &str = r#"
+base_plugins: ["metrics", "tracing"]
+common_headers: { Accept: "application/json", "X-Team": "platform" }
+
+gateway {
+    plugins: [...base_plugins, "audit"]
+    headers: { ...common_headers, "X-Request-Id": "on" }
+}
+"#;
+
+fn strings(values: &[&str]) -> OrbitValue {
+    OrbitValue::List(
+        values
+            .iter()
+            .map(|value| OrbitValue::String((*value).into()))
+            .collect(),
+    )
+}
+
+#[test]
+fn spreads_splice_lists_and_objects() {
+    let value = orbit_core::evaluate(SAMPLE).expect("evaluation should succeed");
+    assert_eq!(
+        value.get_path(&["gateway", "plugins"]),
+        Some(&strings(&["metrics", "tracing", "audit"]))
+    );
+    let headers = value
+        .get_path(&["gateway", "headers"])
+        .and_then(OrbitValue::as_object)
+        .expect("headers object");
+    let keys: Vec<_> = headers.keys().map(String::as_str).collect();
+    assert_eq!(keys, ["Accept", "X-Team", "X-Request-Id"]);
+}
+
+#[test]
+fn spread_type_mismatch_is_an_error() {
+    let err = orbit_core::evaluate("base: { a: 1 }\nitems: [...base]\n").unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("cannot spread object 'base' into a list")
+    );
+}
+
+#[test]
+fn spread_collisions_require_opt_in() {
+    let source = "defaults: { level: \"info\" }\nlog: { ...defaults, level: \"debug\" }\n";
+    let err = orbit_core::evaluate(source).unwrap_err();
+    assert!(err.to_string().contains("duplicate key 'level'"));
+
+    let options = EvaluationOptions::new().with_spread_overrides(true);
+    let value =
+        orbit_core::evaluate_with_options(source, &options).expect("override should succeed");
+    assert_eq!(
+        value.get_path(&["log", "level"]),
+        Some(&OrbitValue::String("debug".into()))
+    );
+}
+
+#[test]
+fn formatter_keeps_spreads_in_place() {
+    let source = "headers: { z: 1, ...common, \"b c\": 2, a: 3 }\n";
+    let formatted = orbit_fmt::format_source(source).expect("formatting should succeed");
+    assert_eq!(
+        formatted,
+        "headers: {\n    z: 1,\n    ...common,\n    a: 3,\n    \"b c\": 2\n}\n"
+    );
+}
@@ -0,0 +1,123 @@
+use orbit_core::{AstNode, CoreError, ErrorCode, OrbitValue, ParseError, Suggestion, SyntaxKind};
+
+// Applies the first suggestion of every error, back to front so earlier
+// spans stay valid.
+fn fix(source: &str) -> String {
+    let report = orbit_core::parse_with_recovery(source);
+    let mut suggestions: Vec<&Suggestion> = report
+        .errors
+        .iter()
+        .filter_map(|error| error.suggestions().first())
+        .collect();
+    assert_eq!(
+        suggestions.len(),
+        report.errors.len(),
+        "{:?}",
+        report.errors
+    );
+    suggestions.sort_by_key(|suggestion| std::cmp::Reverse(suggestion.span.start));
+    suggestions
+        .into_iter()
+        .fold(source.to_string(), |fixed, suggestion| {
+            suggestion.apply(&fixed)
+        })
+}
+
+fn parse_error(source: &str) -> ParseError {
+    match orbit_core::parse(source).unwrap_err() {
+        CoreError::Parse(error) => error,
+        other => panic!("expected a parse error, got {other:?}"),
+    }
+}
+
+#[test]
+fn equals_signs_are_fixed_to_colons() {
+    let source = "port = 80\nhost=\"a\"\n";
+    let err = orbit_core::parse(source).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::UnexpectedCharacter);
+    assert_eq!(
+        err.suggestions()[0].message,
+        "use ':' to separate a key from its value"
+    );
+    assert_eq!(fix(source), "port : 80\nhost:\"a\"\n");
+
+    // The lexer's report is the only one; the entry still gets its value.
+    let report = orbit_core::parse_with_recovery(source);
+    assert_eq!(report.errors.len(), 2);
+    assert!(matches!(
+        &report.document.as_body().expect("document body")[0],
+        AstNode::Entry { value, .. } if !matches!(value, orbit_core::ValueNode::Error { .. })
+    ));
+}
+
+#[test]
+fn semicolons_become_commas_or_line_breaks() {
+    let source =
+        "ports: [80; 443]\nlimits: { cpu: 2; memory: 1 }\na: 1;\nserver {\n    b: 2; c: 3\n}\n";
+    let fixed = fix(source);
+    assert_eq!(
+        fixed,
+        "ports: [80, 443]\nlimits: { cpu: 2, memory: 1 }\na: 1\nserver {\n    b: 2\n    c: 3\n}\n"
+    );
+    let value = orbit_core::evaluate(&fixed).expect("fixed source evaluates");
+    assert_eq!(value.get_path(&["server", "c"]), Some(&OrbitValue::from(3)));
+}
+
+#[test]
+fn single_quoted_strings_are_requoted() {
+    let source = "name: 'say \"hi\"'\n";
+    let err = orbit_core::parse(source).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::SingleQuotedString);
+    let fixed = fix(source);
+    assert_eq!(fixed, "name: \"say \\\"hi\\\"\"\n");
+    assert_eq!(
+        orbit_core::evaluate(&fixed)
+            .expect("fixed source evaluates")
+            .get_path(&["name"]),
+        Some(&OrbitValue::from("say \"hi\""))
+    );
+}
+
+#[test]
+fn requoting_escapes_backslashes() {
+    let source = "path: 'C:\\new'\n";
+    let fixed = fix(source);
+    assert_eq!(fixed, "path: \"C:\\\\new\"\n");
+    let value = orbit_core::evaluate(&fixed).expect("fixed source evaluates");
+    assert_eq!(
+        value.get_path(&["path"]),
+        Some(&OrbitValue::from("C:\\new"))
+    );
+    // The fixed text is stable under another fix and under formatting.
+    assert_eq!(fix(&fixed), fixed);
+    let formatted = orbit_fmt::format_source(&fixed).expect("fixed source formats");
+    assert_eq!(
+        orbit_core::evaluate(&formatted).expect("formatted source evaluates"),
+        value
+    );
+}
+
+#[test]
+fn parse_errors_list_the_accepted_tokens() {
+    let err = parse_error("ports: [80 443]\n");
+    assert_eq!(err.code, ErrorCode::MissingComma);
+    assert_eq!(err.expected, [SyntaxKind::Comma, SyntaxKind::RBracket]);
+    assert_eq!(
+        err.suggestions[0].apply("ports: [80 443]\n"),
+        "ports: [80, 443]\n"
+    );
+
+    let err = parse_error("port 80\n");
+    assert_eq!(err.expected, [SyntaxKind::Colon, SyntaxKind::LBrace]);
+    assert_eq!(err.suggestions[0].apply("port 80\n"), "port: 80\n");
+
+    let err = parse_error("a: ]\n");
+    assert_eq!(err.code, ErrorCode::UnexpectedToken);
+    assert!(err.expected.contains(&SyntaxKind::String));
+    assert!(err.suggestions.is_empty());
+
+    assert_eq!(
+        fix("limits: { cpu: 2 memory: 1 }\nports: [1 2 3]\n"),
+        "limits: { cpu: 2, memory: 1 }\nports: [1, 2, 3]\n"
+    );
+}
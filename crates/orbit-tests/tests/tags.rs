@@ -0,0 +1,95 @@
+use orbit_core::{ErrorCode, EvaluationOptions, OrbitNumber, OrbitValue, RuntimeError, Span};
+
+#[test]
+fn builtin_tags_validate_and_normalize() {
+    let source = r#"
+net {
+    gateway: ip"10.0.0.1"
+    v6: ip"2001:0db8::0001"
+    private: cidr"10.0.0.0/8"
+    docs: url"https://example.com"
+    pattern: regex"^[a-z]+$"
+}
+"#;
+    let value = orbit_core::evaluate(source).expect("evaluation should succeed");
+    let get = |key: &str| value.get_path(&["net", key]).cloned();
+    assert_eq!(get("gateway"), Some(OrbitValue::String("10.0.0.1".into())));
+    assert_eq!(get("v6"), Some(OrbitValue::String("2001:db8::1".into())));
+    assert_eq!(
+        get("private"),
+        Some(OrbitValue::String("10.0.0.0/8".into()))
+    );
+    assert_eq!(
+        get("docs"),
+        Some(OrbitValue::String("https://example.com/".into()))
+    );
+    assert_eq!(get("pattern"), Some(OrbitValue::String("^[a-z]+$".into())));
+}
+
+#[test]
+fn invalid_tagged_literal_reports_literal_span() {
+    let source = "addr: cidr\"10.0.0.0/33\"\n";
+    let err = orbit_core::evaluate(source).unwrap_err();
+    let orbit_core::CoreError::Runtime(err) = err else {
+        panic!("expected runtime error");
+    };
+    assert!(err.message.starts_with("invalid cidr literal"));
+    assert_eq!(err.span, Span::new(6, 23));
+}
+
+#[test]
+fn hosts_can_register_custom_tags() {
+    let options = EvaluationOptions::new().with_tag("port", |value: &str, span: Span| {
+        value
+            .parse::<u16>()
+            .map(|port| OrbitValue::Number(OrbitNumber::Integer(port.into())))
+            .map_err(|_| RuntimeError::new("invalid port", span))
+    });
+    let value = orbit_core::evaluate_with_options("listen: port\"8080\"\n", &options)
+        .expect("evaluation should succeed");
+    assert_eq!(
+        value.get_path(&["listen"]),
+        Some(&OrbitValue::Number(OrbitNumber::Integer(8080)))
+    );
+
+    let err = orbit_core::evaluate("listen: port\"8080\"\n").unwrap_err();
+    assert!(err.to_string().contains("unknown literal tag 'port'"));
+}
+
+#[test]
+fn cidr_rejects_host_bits_and_signed_prefixes() {
+    for literal in ["10.0.0.1/8", "10.0.0.0/+8", "10.0.0.0/", "fd00::1/64"] {
+        let source = format!("net: cidr\"{literal}\"\n");
+        let err = orbit_core::evaluate(&source).unwrap_err();
+        assert_eq!(err.code(), ErrorCode::InvalidTaggedLiteral, "{literal}");
+    }
+    for (literal, canonical) in [
+        ("10.0.0.1/32", "10.0.0.1/32"),
+        ("0.0.0.0/0", "0.0.0.0/0"),
+        ("fd00:0::/64", "fd00::/64"),
+        ("::1/128", "::1/128"),
+    ] {
+        let source = format!("net: cidr\"{literal}\"\n");
+        let value = orbit_core::evaluate(&source).expect("valid cidr");
+        assert_eq!(
+            value.get_path(&["net"]),
+            Some(&OrbitValue::String(canonical.into()))
+        );
+    }
+}
+
+#[test]
+fn options_compare_tags_by_name() {
+    let port = |value: &str, _: Span| Ok(OrbitValue::String(value.into()));
+    assert_eq!(EvaluationOptions::new(), EvaluationOptions::default());
+    assert_eq!(
+        EvaluationOptions::new().with_tag("port", port),
+        EvaluationOptions::new().with_tag("port", |_: &str, span: Span| {
+            Err(RuntimeError::new("unused", span))
+        })
+    );
+    assert_ne!(
+        EvaluationOptions::new().with_tag("port", port),
+        EvaluationOptions::new()
+    );
+}
@@ -0,0 +1,75 @@
+use orbit_core::{EvaluationOptions, OrbitValue, Parser};
+
+#[test]
+fn unicode_identifiers_are_accepted() {
+    let value = orbit_core::evaluate("名前: \"orbit\"\nservice {\n  größe: 3\n}\n")
+        .expect("evaluation should succeed");
+    let OrbitValue::Object(map) = value else {
+        panic!("expected object");
+    };
+    assert_eq!(map["名前"], OrbitValue::String("orbit".into()));
+    let OrbitValue::Object(service) = &map["service"] else {
+        panic!("expected nested object");
+    };
+    assert!(service.contains_key("größe"));
+}
+
+#[test]
+fn identifiers_are_nfc_normalized() {
+    // "cafe\u{301}" is the decomposed spelling of "café".
+    let err = orbit_core::evaluate("café: 1\ncafe\u{301}: 2\n").unwrap_err();
+    assert!(err.to_string().contains("duplicate key 'café'"));
+}
+
+#[test]
+fn unicode_identifiers_require_0_3() {
+    let err = orbit_core::parse("#!orbit 0.2\nnamé: 1\n").unwrap_err();
+    assert!(err.to_string().contains("unexpected character 'é'"));
+}
+
+#[test]
+fn confusable_identifiers_produce_warnings() {
+    // The second key starts with a Cyrillic 'р' and 'а'.
+    let source = "path: 1\n\u{440}\u{430}th: 2\n";
+    let parser = Parser::from_source(source).expect("lex should succeed");
+    let messages: Vec<_> = parser
+        .warnings()
+        .iter()
+        .map(|warning| warning.message.as_str())
+        .collect();
+    assert_eq!(
+        messages,
+        [
+            "identifier '\u{440}\u{430}th' mixes characters from several scripts",
+            "identifier '\u{440}\u{430}th' is confusable with 'path'",
+        ]
+    );
+
+    let evaluation = orbit_core::evaluate_detailed(source, &EvaluationOptions::default())
+        .expect("evaluation should succeed");
+    assert_eq!(evaluation.warnings.len(), 2);
+}
+
+#[test]
+fn ascii_sources_skip_confusable_checks() {
+    let parser = Parser::from_source("rn: 1\nm: 2\nl: 3\nI: 4\n").expect("lex should succeed");
+    assert!(parser.warnings().is_empty());
+}
+
+#[test]
+fn quoted_keys_and_tag_names_are_nfc_normalized() {
+    let err = orbit_core::evaluate("obj: { \"café\": 1, \"cafe\u{301}\": 2 }\n").unwrap_err();
+    assert!(err.to_string().contains("duplicate key 'café'"));
+    let err = orbit_core::evaluate("obj: { café: 1, \"cafe\u{301}\": 2 }\n").unwrap_err();
+    assert!(err.to_string().contains("duplicate key 'café'"));
+
+    let options = EvaluationOptions::new().with_tag("cafe\u{301}", |value: &str, _| {
+        Ok(OrbitValue::String(value.to_uppercase()))
+    });
+    let value = orbit_core::evaluate_with_options("a: café\"x\"\n", &options)
+        .expect("evaluation should succeed");
+    assert_eq!(
+        value.get_path(&["a"]),
+        Some(&OrbitValue::String("X".into()))
+    );
+}
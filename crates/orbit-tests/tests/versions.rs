@@ -0,0 +1,41 @@
+use orbit_core::{LanguageVersion, Parser};
+
+#[test]
+fn pragma_selects_language_version() {
+    let parser = Parser::from_source("#!orbit 0.1\nname: \"orbit\"\n").expect("lex should succeed");
+    assert_eq!(parser.version(), LanguageVersion::V0_1);
+    let parser = Parser::from_source("name: \"orbit\"\n").expect("lex should succeed");
+    assert_eq!(parser.version(), LanguageVersion::LATEST);
+}
+
+#[test]
+fn newer_syntax_is_rejected_for_older_versions() {
+    let err = orbit_core::parse("#!orbit 0.1\nbase: [1]\nall: [...base, 2]\n").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "parse error at byte range Span { start: 28, end: 31 }: orbit 0.1 does not support spread syntax (added in 0.2)"
+    );
+    assert!(orbit_core::parse("#!orbit 0.2\nbase: [1]\nall: [...base, 2]\n").is_ok());
+}
+
+#[test]
+fn doc_comments_are_plain_comments_before_0_2() {
+    let ast = orbit_core::parse("#!orbit 0.1\n## not documentation\nport: 1\n")
+        .expect("parse should succeed");
+    assert!(ast.as_body().expect("document body")[0].docs().is_empty());
+}
+
+#[test]
+fn unknown_versions_are_rejected() {
+    let err = orbit_core::parse("#!orbit 9.9\nport: 1\n").unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("unsupported orbit language version '9.9'")
+    );
+}
+
+#[test]
+fn formatter_keeps_pragma() {
+    let formatted = orbit_fmt::format_source("#!orbit 0.1\nport:   1\n").expect("format");
+    assert_eq!(formatted, "#!orbit 0.1\nport: 1\n");
+}
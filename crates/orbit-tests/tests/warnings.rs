@@ -0,0 +1,156 @@
+use orbit_core::{
+    CoreError, Engine, ErrorCode, EvaluationOptions, FileDatabase, OrbitNumber, OrbitValue, Parser,
+    Warning,
+};
+
+fn codes(warnings: &[Warning]) -> Vec<ErrorCode> {
+    warnings.iter().map(|warning| warning.code).collect()
+}
+
+#[test]
+fn lexer_parser_and_evaluator_emit_coded_warnings() {
+    let source = "path: \"C:\\data\"\nid: 9007199254740993\npi: 3.14159265358979323846\nempty {}\n";
+    let evaluation =
+        orbit_core::evaluate_detailed(source, &EvaluationOptions::default()).expect("evaluates");
+    assert_eq!(
+        codes(&evaluation.warnings),
+        [
+            ErrorCode::UnknownEscape,
+            ErrorCode::UnsafeInteger,
+            ErrorCode::LossyFloat,
+            ErrorCode::EmptyBlock
+        ]
+    );
+    let escape = &evaluation.warnings[0];
+    assert_eq!(&source[escape.span.start..escape.span.end], "\\d");
+    assert_eq!(
+        evaluation.value.get_path(&["path"]),
+        Some(&OrbitValue::String("C:data".into()))
+    );
+    assert_eq!(
+        evaluation.value.get_path(&["id"]),
+        Some(&OrbitValue::Number(OrbitNumber::Integer(9007199254740993)))
+    );
+}
+
+#[test]
+fn numbers_that_round_trip_do_not_warn() {
+    let source = "a: 0.1\nb: 100.50\nc: -0.000\nd: 9007199254740991\ne: -9007199254740991\n";
+    let (_, warnings) = Parser::from_source(source)
+        .expect("lexes")
+        .parse_document_with_warnings()
+        .expect("parses");
+    assert!(warnings.is_empty());
+
+    let (_, warnings) = Parser::from_source("a: -9007199254740992\nb: 0.30000000000000000001\n")
+        .expect("lexes")
+        .parse_document_with_warnings()
+        .expect("parses");
+    assert_eq!(
+        codes(&warnings),
+        [ErrorCode::UnsafeInteger, ErrorCode::LossyFloat]
+    );
+}
+
+#[test]
+fn denied_warnings_fail_strict_evaluation() {
+    let options = EvaluationOptions::new().with_deny_warnings(true);
+    assert!(orbit_core::evaluate_with_options("a: \"\\q\"\n", &EvaluationOptions::new()).is_ok());
+    let err = orbit_core::evaluate_with_options("a: \"\\q\"\n", &options).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::UnknownEscape);
+
+    let err = orbit_core::evaluate_detailed("ok: 1\nempty {}\n", &options).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::EmptyBlock);
+    assert!(orbit_core::evaluate_with_options("ok: 1\n", &options).is_ok());
+}
+
+#[test]
+fn denying_reports_every_warning() {
+    let options = EvaluationOptions::new().with_deny_warnings(true);
+    let source = "a: \"\\q\"\nempty {}\n@deprecated\nold: 1\n";
+    let CoreError::Runtime(err) = orbit_core::evaluate_detailed(source, &options).unwrap_err()
+    else {
+        panic!("denied warnings are runtime errors");
+    };
+    assert_eq!(err.code, ErrorCode::UnknownEscape);
+    let labels: Vec<_> = err
+        .labels
+        .iter()
+        .map(|label| label.message.as_str())
+        .collect();
+    assert_eq!(
+        labels,
+        ["block 'empty' is empty", "key 'old' is deprecated"]
+    );
+}
+
+#[test]
+fn warnings_display_their_line_and_column() {
+    let source = "name: \"orbit\"\npath: \"C:\\data\"\n";
+    let evaluation =
+        orbit_core::evaluate_detailed(source, &EvaluationOptions::default()).expect("evaluates");
+    assert_eq!(
+        evaluation.warnings[0].to_string(),
+        "warning at 2:10: unknown escape sequence '\\d'"
+    );
+
+    let mut files = FileDatabase::new();
+    let file = files.add("app.orb", source);
+    let report = files.parse_with_recovery(file).expect("file is registered");
+    assert_eq!(
+        report.warnings[0].to_string(),
+        "app.orb:2:10: warning: unknown escape sequence '\\d'"
+    );
+}
+
+#[test]
+fn denied_warnings_display_their_line_and_column() {
+    let engine = Engine::new().with_deny_warnings(true);
+    let expected = "runtime error at 2:1: block 'b' is empty";
+    let err = engine.evaluate_detailed("a: 1\nb {}\n").unwrap_err();
+    assert_eq!(err.to_string(), expected);
+
+    let report = engine.evaluate_report("a: 1\nb {}\n").expect("parses");
+    assert_eq!(report.errors[0].to_string(), expected);
+
+    let reports = engine
+        .evaluate_documents_report("a: 1\nb {}\n---\nc: 1\n")
+        .expect("parses");
+    assert_eq!(reports[0].errors[0].to_string(), expected);
+
+    let reports = engine
+        .evaluate_documents_report("a: 1\nb {}\nc 1\n")
+        .expect("recovers");
+    assert_eq!(reports[0].errors[0].to_string(), expected);
+}
+
+#[test]
+fn denied_warnings_join_report_errors_in_source_order() {
+    let source = "@deprecated\nold: 1\nport 80\nbig: 123456789012345678\n";
+    let report = orbit_core::parse_with_recovery(source);
+    let options = EvaluationOptions::new().with_deny_warnings(true);
+    let report = orbit_core::evaluate_parse_report(report, &options);
+    let codes: Vec<_> = report.errors.iter().map(|error| error.code()).collect();
+    assert_eq!(
+        codes,
+        [
+            ErrorCode::DeprecatedKey,
+            ErrorCode::MissingColon,
+            ErrorCode::UnsafeInteger
+        ]
+    );
+    assert!(report.warnings.is_empty());
+    assert_eq!(
+        report.value.get_path(&["old"]),
+        Some(&OrbitValue::Number(OrbitNumber::Integer(1)))
+    );
+}
+
+#[test]
+fn host_warnings_use_the_generic_code() {
+    let warning = Warning::new("custom", orbit_core::Span::new(0, 1));
+    assert_eq!(warning.code, ErrorCode::Warning);
+    let error = warning.with_code(ErrorCode::EmptyBlock).into_error();
+    assert_eq!(error.code, ErrorCode::EmptyBlock);
+    assert_eq!(error.message, "custom");
+}
@@ -1,18 +1,25 @@
-use std::{fs, path::PathBuf};
+use std::path::PathBuf;
 
 use anyhow::{Context, Result};
-use orbit_core::{self, serializer::to_json_string_pretty};
+use orbit_core::{Engine, serializer::to_json_string_pretty};
 
 fn main() -> Result<()> {
+    let engine = Engine::new();
     let config_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("sample.orbit");
-    let source = fs::read_to_string(&config_path)
-        .with_context(|| format!("failed to read {}", config_path.display()))?;
+    let source_map = engine.load(&config_path)?;
     println!("Loaded config from {}", config_path.display());
 
-    let ast = orbit_core::parse(&source).context("failed to parse Orbit document")?;
+    // The file is read and parsed once; the AST is then evaluated.
+    let ast = engine
+        .parse(source_map.source())
+        .map_err(|err| err.with_source(source_map.clone()))
+        .context("failed to parse Orbit document")?;
     println!("AST:\n{:#?}", ast);
 
-    let value = orbit_core::evaluate_ast(&ast).context("failed to evaluate Orbit document")?;
+    let value = engine
+        .evaluate_ast(&ast)
+        .map_err(|err| err.with_source(source_map.clone()))
+        .context("failed to evaluate Orbit document")?;
     let json = to_json_string_pretty(&value).context("failed to serialize value to JSON")?;
     println!("\nResolved value as JSON:\n{}", json);
 